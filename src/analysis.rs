@@ -0,0 +1,113 @@
+use crate::{shanten, tiles};
+
+/// The analysis of a single hand (see `analyze_hands()`)
+#[derive(Debug, Clone)]
+pub struct HandReport {
+    /// the hand, in MSPZ notation (see `tiles::tiles_from_string()`)
+    pub hand: String,
+    /// the shanten of the hand (for a 14-tile hand, before discarding)
+    pub shanten: i32,
+    /// the ukiere tiles of the 13-tile hand, or of the 14-tile hand after its best discard
+    pub ukiere_tiles: Vec<tiles::Tile>,
+    /// the total number of copies of the ukiere tiles that aren't in the hand
+    pub acceptance: u32,
+    /// (only for a 14-tile hand) the discards that are tied for the best, i.e. lowest shanten then most acceptance
+    /// (see `shanten::ranked_discards()`). Empty for a 13-tile hand.
+    pub best_discards: Vec<tiles::Tile>,
+}
+
+/// Analyzes each hand (one hand per line, in MSPZ notation, e.g. from a file of hands to study or to check for
+/// regressions): its shanten and acceptance, and for a 14-tile hand, its best discards. Blank lines and comment lines
+/// (starting with "#") are skipped. Returns a report for each hand, or an Err for a line that isn't a valid 13- or
+/// 14-tile hand.
+pub fn analyze_hands(lines: impl Iterator<Item = String>) -> Vec<Result<HandReport, &'static str>> {
+    lines
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| analyze_hand(&line))
+        .collect()
+}
+
+fn analyze_hand(hand_string: &str) -> Result<HandReport, &'static str> {
+    let hand_tiles = tiles::try_tiles_from_string(hand_string)?;
+    let shanten = shanten::try_get_shanten(&hand_tiles)?;
+    if hand_tiles.len() == 13 {
+        let (ukiere_tiles, acceptance) = shanten::get_acceptance(&hand_tiles, &Vec::new());
+        return Ok(HandReport {
+            hand: hand_string.to_string(),
+            shanten,
+            ukiere_tiles,
+            acceptance,
+            best_discards: Vec::new(),
+        });
+    }
+
+    let discard_evals = shanten::ranked_discards(&hand_tiles, &Vec::new());
+    let best_eval = discard_evals
+        .first()
+        .ok_or("Hand must have a tile to discard!")?;
+    let best_discards = discard_evals
+        .iter()
+        .filter(|discard_eval| {
+            discard_eval.shanten == best_eval.shanten
+                && discard_eval.acceptance == best_eval.acceptance
+        })
+        .map(|discard_eval| discard_eval.tile)
+        .collect();
+    Ok(HandReport {
+        hand: hand_string.to_string(),
+        shanten,
+        ukiere_tiles: best_eval.ukiere_tiles.clone(),
+        acceptance: best_eval.acceptance,
+        best_discards,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile_strings(tiles: &[tiles::Tile]) -> Vec<String> {
+        tiles.iter().map(|tile| tile.to_string()).collect()
+    }
+
+    #[test]
+    fn test_analyze_hands() {
+        let lines = [
+            "# tenpai, 1-shanten, and a 14-tile hand",
+            "123m456p789s1155z",
+            "",
+            "1239m456p3478s55z",
+            "123667m6889p1278s",
+            "123m456p",
+        ];
+        let reports = analyze_hands(lines.iter().map(|line| line.to_string()));
+        assert_eq!(reports.len(), 4);
+
+        // 123m 456p 789s 11z 55z: a shanpon wait on 1z or 5z
+        let report = reports[0].as_ref().unwrap();
+        assert_eq!(report.hand, "123m456p789s1155z");
+        assert_eq!(report.shanten, 0);
+        assert_eq!(tile_strings(&report.ukiere_tiles), vec!["1z", "5z"]);
+        assert_eq!(report.acceptance, 4);
+        assert!(report.best_discards.is_empty());
+
+        // 123m 456p 34s 78s 55z 9m: 1-shanten, with ukiere 2s, 5s, 6s, 9s
+        let report = reports[1].as_ref().unwrap();
+        assert_eq!(report.shanten, 1);
+        assert_eq!(
+            tile_strings(&report.ukiere_tiles),
+            vec!["2s", "5s", "6s", "9s"]
+        );
+        assert_eq!(report.acceptance, 16);
+
+        // discarding 6p or 9p are tied for the best discard (2-shanten, 28 tiles)
+        let report = reports[2].as_ref().unwrap();
+        assert_eq!(report.shanten, 2);
+        assert_eq!(tile_strings(&report.best_discards), vec!["6p", "9p"]);
+        assert_eq!(report.acceptance, 28);
+
+        // not a 13- or 14-tile hand
+        assert!(reports[3].is_err());
+    }
+}
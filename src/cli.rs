@@ -0,0 +1,286 @@
+use crate::{scoring, shanten, state, tile_grouping, tiles, yaku};
+
+/// The usage of the command line interface (see `run()`)
+pub const USAGE: &str = "usage:
+  riichi-bot shanten <hand>
+  riichi-bot ukiere <hand>
+  riichi-bot discards <14-tile hand>
+  riichi-bot score <hand> --win <tile> [--tsumo] [--riichi] [--first-turn] [--seat <E|S|W|N>] [--round <E|S|W|N>] [--dora <indicators>]
+hands and tiles are in MSPZ notation (e.g. 123m456p789s1155z, with 0 for a red five), and the hand to score
+can include its declared melds (e.g. \"123m456p111z9s + pon5z\"). The hand is scored as won in the middle of the round:
+a first turn win (tenhou or chiihou) is only scored with --first-turn, and a win on the last tile isn't scored as such";
+
+/// Runs the command given by the command line arguments (not including the program name), and returns its output
+/// (see `USAGE`). With no command (or an unknown command), returns an Err with the usage.
+pub fn run(args: &[String]) -> Result<String, &'static str> {
+    let (command, command_args) = args.split_first().ok_or(USAGE)?;
+    match command.as_str() {
+        "shanten" => run_shanten(command_args),
+        "ukiere" => run_ukiere(command_args),
+        "discards" => run_discards(command_args),
+        "score" => run_score(command_args),
+        _ => Err(USAGE),
+    }
+}
+
+fn hand_arg(command_args: &[String]) -> Result<&str, &'static str> {
+    command_args
+        .first()
+        .map(|hand_string| hand_string.as_str())
+        .ok_or("Command must have a hand!")
+}
+
+fn tiles_string(tiles: &[tiles::Tile]) -> String {
+    tiles
+        .iter()
+        .map(|tile| tile.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn wind_from_arg(wind_string: &str) -> Result<state::WindDirection, &'static str> {
+    match wind_string {
+        "E" => Ok(state::WindDirection::East),
+        "S" => Ok(state::WindDirection::South),
+        "W" => Ok(state::WindDirection::West),
+        "N" => Ok(state::WindDirection::North),
+        _ => Err("Wind must be one of E, S, W, or N!"),
+    }
+}
+
+fn run_shanten(command_args: &[String]) -> Result<String, &'static str> {
+    let hand_tiles = tiles::try_tiles_from_string(hand_arg(command_args)?)?;
+    let shanten = shanten::try_get_shanten(&hand_tiles)?;
+    Ok(format!("shanten: {}", shanten))
+}
+
+fn run_ukiere(command_args: &[String]) -> Result<String, &'static str> {
+    let hand_tiles = tiles::try_tiles_from_string(hand_arg(command_args)?)?;
+    if hand_tiles.len() != 13 {
+        return Err("Hand must have 13 tiles!");
+    }
+    let shanten = shanten::try_get_shanten(&hand_tiles)?;
+    let (ukiere_tiles, acceptance) = shanten::get_acceptance(&hand_tiles, &Vec::new());
+    Ok(format!(
+        "shanten: {}\nukiere: {} ({} tiles)",
+        shanten,
+        tiles_string(&ukiere_tiles),
+        acceptance
+    ))
+}
+
+fn run_discards(command_args: &[String]) -> Result<String, &'static str> {
+    let hand_tiles = tiles::try_tiles_from_string(hand_arg(command_args)?)?;
+    if hand_tiles.len() != 14 {
+        return Err("Hand must have 14 tiles!");
+    }
+    // the shanten and tile counts are checked before ranking the discards, which panics on an invalid hand
+    shanten::try_get_shanten(&hand_tiles)?;
+    let discard_lines: Vec<String> = shanten::ranked_discards(&hand_tiles, &Vec::new())
+        .iter()
+        .map(|discard_eval| {
+            format!(
+                "{}: shanten {}, {} tiles ({})",
+                discard_eval.tile,
+                discard_eval.shanten,
+                discard_eval.acceptance,
+                tiles_string(&discard_eval.ukiere_tiles)
+            )
+        })
+        .collect();
+    Ok(discard_lines.join("\n"))
+}
+
+fn run_score(command_args: &[String]) -> Result<String, &'static str> {
+    let (player_tiles, declared_groups) =
+        tiles::hand_and_melds_from_string(hand_arg(command_args)?)?;
+    let mut hand_state = state::HandState {
+        round_wind: state::WindDirection::East,
+        any_calls_made: !declared_groups.is_empty(),
+        // (not scored, i.e. not the last tile, see `USAGE`)
+        tiles_remaining: state::NUM_LIVE_WALL_TILES,
+        dora_indicators: Vec::new(),
+        ura_dora_indicators: Vec::new(),
+        riichi_sticks: 0,
+        honba_sticks: 0,
+    };
+    let mut player_state = state::PlayerState {
+        discards: Vec::new(),
+        seat_wind: state::WindDirection::South,
+        in_riichi: false,
+        in_double_riichi: false,
+        in_open_riichi: false,
+        in_ippatsu_turn: false,
+        any_discards_called_by_others: false,
+        winning_tile_source: None,
+    };
+    let mut winning_tile: Option<tiles::Tile> = None;
+    let mut is_tsumo = false;
+    let mut is_first_go_around = false;
+    let mut option_args = command_args[1..].iter();
+    while let Some(option) = option_args.next() {
+        match option.as_str() {
+            "--tsumo" => is_tsumo = true,
+            "--riichi" => player_state.in_riichi = true,
+            "--first-turn" => is_first_go_around = true,
+            "--win" | "--seat" | "--round" | "--dora" => {
+                let value = option_args.next().ok_or("Option must have a value!")?;
+                match option.as_str() {
+                    "--win" => winning_tile = Some(value.parse()?),
+                    "--seat" => player_state.seat_wind = wind_from_arg(value)?,
+                    "--round" => hand_state.round_wind = wind_from_arg(value)?,
+                    _ => hand_state.dora_indicators = tiles::try_tiles_from_string(value)?,
+                }
+            }
+            _ => return Err(USAGE),
+        }
+    }
+    let winning_tile = winning_tile.ok_or("Score command must have a winning tile (--win)!")?;
+    player_state.winning_tile_source = Some(if is_tsumo {
+        state::WinningTileSource::SelfDraw { is_first_go_around }
+    } else {
+        state::WinningTileSource::Discard { is_first_go_around }
+    });
+
+    let rules = scoring::ScoringRules::default();
+    let score_grouping = |tile_grouping: &Vec<tiles::TileGroup>| {
+        scoring::score_hand(
+            &player_tiles,
+            &winning_tile,
+            tile_grouping,
+            &hand_state,
+            &player_state,
+            &rules,
+        )
+    };
+    let groupings =
+        tile_grouping::decompose_winning_hand(&player_tiles, &declared_groups, &winning_tile);
+    let hand_score = if groupings.is_empty() {
+        // kokushi musou can't be grouped (see `scoring::score_hand()`)
+        yaku::han_from_kokushi_yaku(&player_tiles, &winning_tile, &hand_state, &player_state)
+            .ok_or("Hand must be a winning hand!")?;
+        score_grouping(&Vec::new())
+    } else {
+        groupings
+            .iter()
+            .map(score_grouping)
+            .max_by_key(|hand_score| (hand_score.ron, hand_score.han, hand_score.fu))
+            .expect("Winning hand should have a grouping")
+    };
+    if hand_score.han == 0 {
+        return Err("Hand must have a yaku!");
+    }
+
+    let mut output = format!("{} han {} fu", hand_score.han, hand_score.fu);
+    if let Some(limit_name) = &hand_score.limit_name {
+        output.push_str(&format!(" ({})", limit_name));
+    }
+    if matches!(
+        player_state.winning_tile_source,
+        Some(state::WinningTileSource::SelfDraw { .. })
+    ) {
+        if hand_score.tsumo_dealer_pays == 0 {
+            output.push_str(&format!("\ntsumo: {} all", hand_score.tsumo_others_pay));
+        } else {
+            output.push_str(&format!(
+                "\ntsumo: {}/{}",
+                hand_score.tsumo_others_pay, hand_score.tsumo_dealer_pays
+            ));
+        }
+    } else {
+        output.push_str(&format!("\nron: {}", hand_score.ron));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_command(command: &[&str]) -> Result<String, &'static str> {
+        let args: Vec<String> = command.iter().map(|arg| arg.to_string()).collect();
+        run(&args)
+    }
+
+    #[test]
+    fn test_run_commands() {
+        assert_eq!(
+            run_command(&["shanten", "1239m456p3478s55z"]),
+            Ok(String::from("shanten: 1"))
+        );
+        assert_eq!(
+            run_command(&["ukiere", "123m456p789s1155z"]),
+            Ok(String::from("shanten: 0\nukiere: 1z 5z (4 tiles)"))
+        );
+        let discards = run_command(&["discards", "123667m6889p1278s"]).unwrap();
+        assert_eq!(discards.lines().count(), 12);
+        assert!(discards.starts_with("6p: shanten 2, 28 tiles"));
+
+        // non-dealer riichi + pinfu by ron, then riichi + pinfu + menzen tsumo by tsumo
+        assert_eq!(
+            run_command(&["score", "123m456p789s2267s", "--win", "5s", "--riichi"]),
+            Ok(String::from("2 han 30 fu\nron: 2000"))
+        );
+        assert_eq!(
+            run_command(&[
+                "score",
+                "123m456p789s2267s",
+                "--win",
+                "5s",
+                "--riichi",
+                "--tsumo"
+            ]),
+            Ok(String::from("3 han 20 fu\ntsumo: 700/1300"))
+        );
+        // dealer yakuhai (the seat wind and the round wind) with a called pon, and a dora
+        assert_eq!(
+            run_command(&[
+                "score",
+                "123m456p111z9s + pon5z",
+                "--win",
+                "9s",
+                "--seat",
+                "E",
+                "--dora",
+                "8s",
+                "--tsumo"
+            ]),
+            Ok(String::from("5 han 40 fu (mangan)\ntsumo: 4000 all"))
+        );
+
+        // a first turn win is only scored as one with --first-turn (chiihou for a non-dealer)
+        assert_eq!(
+            run_command(&["score", "123m456p789s2267s", "--win", "5s", "--tsumo"]),
+            Ok(String::from("2 han 20 fu\ntsumo: 400/700"))
+        );
+        assert_eq!(
+            run_command(&[
+                "score",
+                "123m456p789s2267s",
+                "--win",
+                "5s",
+                "--tsumo",
+                "--first-turn"
+            ]),
+            Ok(String::from("13 han 0 fu (yakuman)\ntsumo: 8000/16000"))
+        );
+
+        // errors
+        assert_eq!(run_command(&[]), Err(USAGE));
+        assert_eq!(run_command(&["solve", "123m"]), Err(USAGE));
+        assert!(run_command(&["shanten", "123m"]).is_err());
+        assert!(run_command(&["discards", "123m456p789s1155z"]).is_err());
+        assert!(run_command(&["score", "123m456p789s2267s"]).is_err());
+        assert!(run_command(&["score", "123m456p789s2267s", "--win", "9m"]).is_err());
+        // kokushi musou on a 13-sided wait (a double yakuman)
+        assert_eq!(
+            run_command(&["score", "19m19p19s1234567z", "--win", "9m"]),
+            Ok(String::from("26 han 0 fu (2x yakuman)\nron: 64000"))
+        );
+        // no yaku: not in riichi, and a shanpon wait won by ron
+        assert_eq!(
+            run_command(&["score", "123m456p789s2244s", "--win", "4s"]),
+            Err("Hand must have a yaku!")
+        );
+    }
+}
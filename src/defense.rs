@@ -0,0 +1,199 @@
+use crate::tiles;
+
+/// How safe a tile is to discard against an opponent in tenpai, based on the opponent's discards.
+/// Ordered from most dangerous to safest.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub enum TileSafety {
+    /// no information from the opponent's discards
+    Unknown,
+    /// (only for numbered tiles) the opponent can't be waiting on this tile with a two-sided wait,
+    /// since the other tile(s) of every two-sided wait on this tile are in their discards
+    /// e.g. if 4m is discarded, 1m and 7m are suji, and if 2m and 8m are discarded, 5m is suji
+    Suji,
+    /// the tile is in the opponent's discards, so they can't win on it by ron (furiten)
+    Genbutsu,
+}
+
+/// How safe the tile is to discard against an opponent in tenpai with the given discards
+pub fn tile_safety(tile: &tiles::Tile, opponent_discards: &Vec<tiles::Tile>) -> TileSafety {
+    let kind_index = tile.kind_index();
+    let is_discarded = |other_kind_index: usize| {
+        opponent_discards
+            .iter()
+            .any(|discard| discard.kind_index() == other_kind_index)
+    };
+
+    if is_discarded(kind_index) {
+        return TileSafety::Genbutsu;
+    }
+    if let Some(rank) = tile.sequence_rank_num() {
+        // a two-sided wait on this tile also waits on the tile 3 ranks below or 3 ranks above it (in the same suit)
+        let is_lower_suji = rank <= 3 || is_discarded(kind_index - 3);
+        let is_upper_suji = rank >= 7 || is_discarded(kind_index + 3);
+        if is_lower_suji && is_upper_suji {
+            return TileSafety::Suji;
+        }
+    }
+    TileSafety::Unknown
+}
+
+/// The tiles (one tile per tile kind) that are genbutsu against the opponent, i.e. completely safe since the opponent
+/// can't win on them by ron (see `TileSafety::Genbutsu`)
+pub fn genbutsu_tiles(opponent_discards: &Vec<tiles::Tile>) -> Vec<tiles::Tile> {
+    tiles_with_safety(opponent_discards, TileSafety::Genbutsu)
+}
+
+/// The tiles (one tile per tile kind) that are suji against the opponent, i.e. safe against a two-sided wait
+/// (see `TileSafety::Suji`). Doesn't include the genbutsu tiles (see `genbutsu_tiles()`).
+pub fn suji_tiles(opponent_discards: &Vec<tiles::Tile>) -> Vec<tiles::Tile> {
+    tiles_with_safety(opponent_discards, TileSafety::Suji)
+}
+
+fn tiles_with_safety(opponent_discards: &Vec<tiles::Tile>, safety: TileSafety) -> Vec<tiles::Tile> {
+    (0..tiles::NUM_TILE_KINDS)
+        .map(tiles::Tile::from_kind_index)
+        .filter(|tile| tile_safety(tile, opponent_discards) == safety)
+        .collect()
+}
+
+/// The safest tile to discard out of the candidate tiles against an opponent in tenpai (genbutsu, then suji).
+/// If multiple candidates are equally safe, returns the first of them. Returns None if there are no candidates.
+pub fn get_safest_discard(
+    opponent_discards: &Vec<tiles::Tile>,
+    candidate_tiles: &Vec<tiles::Tile>,
+) -> Option<tiles::Tile> {
+    let mut safest_tile: Option<(tiles::Tile, TileSafety)> = None;
+    for candidate_tile in candidate_tiles.iter() {
+        let safety = tile_safety(candidate_tile, opponent_discards);
+        if safest_tile.is_none_or(|(_, safest_safety)| safety > safest_safety) {
+            safest_tile = Some((*candidate_tile, safety));
+        }
+    }
+    safest_tile.map(|(tile, _)| tile)
+}
+
+/// Which tile kinds (see `tiles::Tile::kind_index()`) in the player's hand are safe to discard against the opponent's
+/// (estimated) wait, i.e. the tile kinds that are in the hand but not in the wait tiles.
+/// Tile kinds that aren't in the hand are always false.
+pub fn get_safe_tiles_mask(
+    hand_tiles: &Vec<tiles::Tile>,
+    opponent_wait_tiles: &Vec<tiles::Tile>,
+) -> [bool; tiles::NUM_TILE_KINDS] {
+    let mut safe_tiles_mask = [false; tiles::NUM_TILE_KINDS];
+    for tile in hand_tiles.iter() {
+        safe_tiles_mask[tile.kind_index()] = true;
+    }
+    for wait_tile in opponent_wait_tiles.iter() {
+        safe_tiles_mask[wait_tile.kind_index()] = false;
+    }
+    safe_tiles_mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_safety() {
+        let opponent_discards = tiles::tiles_from_string("4m28p1z");
+        assert_eq!(
+            tile_safety(&tiles::Tile::from_string("4m"), &opponent_discards),
+            TileSafety::Genbutsu
+        );
+        assert_eq!(
+            tile_safety(&tiles::Tile::from_string("1z"), &opponent_discards),
+            TileSafety::Genbutsu
+        );
+        // 1m and 7m are suji from 4m
+        assert_eq!(
+            tile_safety(&tiles::Tile::from_string("1m"), &opponent_discards),
+            TileSafety::Suji
+        );
+        assert_eq!(
+            tile_safety(&tiles::Tile::from_string("7m"), &opponent_discards),
+            TileSafety::Suji
+        );
+        // 5p is suji from both 2p and 8p (and a red five is the same)
+        assert_eq!(
+            tile_safety(&tiles::Tile::from_string("0p"), &opponent_discards),
+            TileSafety::Suji
+        );
+        // 5m needs both 2m and 8m to be suji
+        assert_eq!(
+            tile_safety(&tiles::Tile::from_string("5m"), &opponent_discards),
+            TileSafety::Unknown
+        );
+        // suji doesn't cross suits, and doesn't apply to honor tiles
+        assert_eq!(
+            tile_safety(&tiles::Tile::from_string("1s"), &opponent_discards),
+            TileSafety::Unknown
+        );
+        assert_eq!(
+            tile_safety(&tiles::Tile::from_string("4z"), &opponent_discards),
+            TileSafety::Unknown
+        );
+    }
+
+    #[test]
+    fn test_genbutsu_and_suji_tiles() {
+        let tile_strings = |tiles: Vec<tiles::Tile>| -> Vec<String> {
+            tiles.iter().map(|tile| tile.to_string()).collect()
+        };
+
+        let opponent_discards = tiles::tiles_from_string("5s");
+        assert_eq!(tile_strings(genbutsu_tiles(&opponent_discards)), vec!["5s"]);
+        assert_eq!(
+            tile_strings(suji_tiles(&opponent_discards)),
+            vec!["2s", "8s"]
+        );
+
+        // 1p is suji from 4p, and 7p is also suji from 4p, but it's genbutsu (so it isn't counted as suji)
+        let opponent_discards = tiles::tiles_from_string("47p7z");
+        assert_eq!(
+            tile_strings(genbutsu_tiles(&opponent_discards)),
+            vec!["4p", "7p", "7z"]
+        );
+        assert_eq!(tile_strings(suji_tiles(&opponent_discards)), vec!["1p"]);
+        assert_eq!(
+            tile_strings(suji_tiles(&tiles::tiles_from_string("28p"))),
+            vec!["5p"]
+        );
+    }
+
+    #[test]
+    fn test_safest_discard() {
+        let opponent_discards = tiles::tiles_from_string("4m28p1z");
+
+        // genbutsu is chosen over suji
+        let candidate_tiles = tiles::tiles_from_string("7m5p1z");
+        let safest_tile = get_safest_discard(&opponent_discards, &candidate_tiles);
+        assert_eq!(
+            safest_tile.map(|tile| tile.to_string()),
+            Some(String::from("1z"))
+        );
+
+        // suji is chosen over unknown
+        let candidate_tiles = tiles::tiles_from_string("5m3s7m");
+        let safest_tile = get_safest_discard(&opponent_discards, &candidate_tiles);
+        assert_eq!(
+            safest_tile.map(|tile| tile.to_string()),
+            Some(String::from("7m"))
+        );
+
+        assert!(get_safest_discard(&opponent_discards, &vec![]).is_none());
+    }
+
+    #[test]
+    fn test_safe_tiles_mask() {
+        let hand_tiles = tiles::tiles_from_string("1478m055p369s11z");
+        // opponent is waiting on 4m, 7m (two-sided wait) or 5p
+        let opponent_wait_tiles = tiles::tiles_from_string("47m5p");
+        let safe_tiles_mask = get_safe_tiles_mask(&hand_tiles, &opponent_wait_tiles);
+
+        let safe_tile_strings: Vec<String> = (0..tiles::NUM_TILE_KINDS)
+            .filter(|kind_index| safe_tiles_mask[*kind_index])
+            .map(|kind_index| tiles::Tile::from_kind_index(kind_index).to_string())
+            .collect();
+        assert_eq!(safe_tile_strings, vec!["1m", "8m", "3s", "6s", "9s", "1z"]);
+    }
+}
@@ -1,12 +1,37 @@
 use std::collections::HashMap;
 
+pub mod analysis;
+pub mod cli;
+pub mod defense;
 pub mod mini_mahjong;
+#[cfg(feature = "serde")]
+pub mod mjai;
+pub mod scoring;
+pub mod shanten;
+pub mod shanten_table;
+pub mod solitaire;
 pub mod state;
 pub mod tile_grouping;
 pub mod tiles;
 pub mod yaku;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        match cli::run(&args) {
+            Ok(output) => println!("{}", output),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    simulate_mini_game();
+}
+
+/// Simulates the mini mahjong game with each discard strategy, starting from a fixed hand
+fn simulate_mini_game() {
     // for serial in 0..tiles::NUM_TILES {
     //     let tile = tiles::Tile { serial };
     //     // print!("{} ", tile.to_string());
@@ -49,6 +74,10 @@ fn main() {
         //     mini_mahjong::strategy::hardcoded_initial_wait,
         // ),
         ("hold_tenpai", mini_mahjong::strategy::hold_tenpai),
+        // (
+        //     "discard_best_shanten_ukiere",
+        //     mini_mahjong::strategy::discard_best_shanten_ukiere,
+        // ),
     ];
     for (strategy_name, discard_strategy) in strategies {
         println!("discard strategy: {:?}", strategy_name);
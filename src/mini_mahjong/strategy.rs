@@ -1,5 +1,8 @@
+use crate::mini_mahjong::mini_game;
 use crate::mini_mahjong::simulator;
 use crate::mini_mahjong::tenpai;
+use crate::shanten;
+use crate::tiles;
 use std::collections::HashMap;
 
 use rand::prelude::*;
@@ -185,3 +188,96 @@ pub fn hold_tenpai(game_state: &simulator::MiniGameState) -> usize {
         discard_random(game_state)
     }
 }
+
+// the mini game's single suit is evaluated as the pin suit by the shanten module
+const MINI_SUIT_FIRST_KIND_INDEX: usize = 9;
+
+/// Counts the mini tiles by tile kind (see `shanten::count_tiles_by_kind()`), as if they were pin tiles
+fn mini_tile_kind_counts(tiles: &[mini_game::MiniTile]) -> [u8; tiles::NUM_TILE_KINDS] {
+    let mut tile_kind_counts = [0u8; tiles::NUM_TILE_KINDS];
+    for tile in tiles.iter() {
+        tile_kind_counts[MINI_SUIT_FIRST_KIND_INDEX + (tile.rank() as usize) - 1] += 1;
+    }
+    tile_kind_counts
+}
+
+/// Discards the tile that minimizes the shanten of the remaining hand, then maximizes the number of live ukiere tiles
+/// (i.e. the copies of each ukiere tile that aren't dead), using the shanten module of the full game
+pub fn discard_best_shanten_ukiere(game_state: &simulator::MiniGameState) -> usize {
+    let mut shanten_cache = shanten::ShantenCache::new();
+    // (index of the tile to discard, shanten after discarding, number of live ukiere tiles)
+    let mut best_discard: Option<(usize, i32, u32)> = None;
+    for tile_idx in 0..game_state.hand_tiles.len() {
+        let mut remaining_tiles_after_discard = game_state.hand_tiles.clone();
+        remaining_tiles_after_discard.swap_remove(tile_idx);
+        let tile_kind_counts = mini_tile_kind_counts(&remaining_tiles_after_discard);
+
+        let shanten = shanten_cache.get_shanten(&tile_kind_counts);
+        let mut ukiere_tile_count = 0;
+        for ukiere_tile in shanten_cache.get_ukiere(&tile_kind_counts).iter() {
+            let rank = (ukiere_tile.kind_index() - MINI_SUIT_FIRST_KIND_INDEX + 1) as u32;
+            let num_dead = *(game_state.dead_tiles_by_rank.get(&rank).unwrap_or(&0));
+            ukiere_tile_count += 4 - num_dead;
+        }
+
+        let is_better_discard = match best_discard {
+            None => true,
+            Some((_, best_shanten, best_ukiere_tile_count)) => {
+                shanten < best_shanten
+                    || (shanten == best_shanten && ukiere_tile_count > best_ukiere_tile_count)
+            }
+        };
+        if is_better_discard {
+            best_discard = Some((tile_idx, shanten, ukiere_tile_count));
+        }
+    }
+    best_discard.expect("Hand must have at least one tile to discard").0
+}
+
+#[cfg(test)]
+mod tests {
+    // importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_discard_best_shanten_ukiere() {
+        // the fixed game state in main.rs: 12257p (and one 1p, two 2p, one 5p, and one 7p are dead i.e. in the hand)
+        // discarding 1p is the only discard that reaches tenpai: 2257p waits on 6p
+        let game_state = simulator::MiniGameState {
+            hand_tiles: vec![
+                mini_game::MiniTile { serial: 1 }, // 2p
+                mini_game::MiniTile { serial: 10 }, // 2p
+                mini_game::MiniTile { serial: 4 }, // 5p
+                mini_game::MiniTile { serial: 6 }, // 7p
+                mini_game::MiniTile { serial: 0 }, // 1p
+            ],
+            dead_tiles_by_rank: HashMap::from([(2, 2), (5, 1), (7, 1), (1, 1)]),
+        };
+        assert_eq!(discard_best_shanten_ukiere(&game_state), 4);
+    }
+
+    #[test]
+    fn test_discard_best_shanten_ukiere_prefers_live_tiles() {
+        // 34569p: discarding 9p leaves 3456p (waiting on 3p or 6p), and discarding 3p or 6p leaves a single wait on 9p
+        let hand_tiles = vec![
+            mini_game::MiniTile { serial: 2 }, // 3p
+            mini_game::MiniTile { serial: 3 }, // 4p
+            mini_game::MiniTile { serial: 4 }, // 5p
+            mini_game::MiniTile { serial: 5 }, // 6p
+            mini_game::MiniTile { serial: 8 }, // 9p
+        ];
+        let game_state = simulator::MiniGameState {
+            hand_tiles: hand_tiles.clone(),
+            dead_tiles_by_rank: HashMap::from([(3, 1), (4, 1), (5, 1), (6, 1), (9, 1)]),
+        };
+        // 3456p waits on 6 live tiles, vs 3 live tiles for the single wait
+        assert_eq!(discard_best_shanten_ukiere(&game_state), 4);
+
+        // if most of the 3p and 6p are dead, the single wait on 9p has more live tiles
+        let game_state = simulator::MiniGameState {
+            hand_tiles,
+            dead_tiles_by_rank: HashMap::from([(3, 3), (4, 1), (5, 1), (6, 3), (9, 1)]),
+        };
+        assert_eq!(discard_best_shanten_ukiere(&game_state), 0);
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{shanten, tiles};
+use serde::{Deserialize, Serialize};
+
+/// A message of the MJAI protocol (a JSON object with a "type" field). Only the fields that the bot uses are parsed,
+/// and messages of any other type are parsed as `Unknown`. Tiles are in MJAI notation (see `tile_from_mjai()`).
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MjaiEvent {
+    StartGame {
+        id: usize,
+    },
+    StartKyoku {
+        dora_marker: String,
+        /// the starting hand of each player (the other players' tiles are hidden, i.e. "?")
+        tehais: Vec<Vec<String>>,
+    },
+    Tsumo {
+        actor: usize,
+        pai: String,
+    },
+    Dahai {
+        actor: usize,
+        pai: String,
+        tsumogiri: bool,
+    },
+    Chi {
+        actor: usize,
+        target: usize,
+        pai: String,
+        consumed: Vec<String>,
+    },
+    Pon {
+        actor: usize,
+        target: usize,
+        pai: String,
+        consumed: Vec<String>,
+    },
+    Daiminkan {
+        actor: usize,
+        target: usize,
+        pai: String,
+        consumed: Vec<String>,
+    },
+    Ankan {
+        actor: usize,
+        consumed: Vec<String>,
+    },
+    Kakan {
+        actor: usize,
+        pai: String,
+        consumed: Vec<String>,
+    },
+    Dora {
+        dora_marker: String,
+    },
+    EndKyoku,
+    EndGame,
+    /// the response when the bot doesn't take any action
+    #[serde(rename = "none")]
+    Pass,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Converts a tile in MJAI notation (e.g. "1m", "5pr" for a red five, "E" for the east wind, "P" for the white dragon)
+/// to a tile
+pub fn tile_from_mjai(pai: &str) -> Result<tiles::Tile, &'static str> {
+    let tile_string = match pai {
+        "E" => "1z",
+        "S" => "2z",
+        "W" => "3z",
+        "N" => "4z",
+        "P" => "5z",
+        "F" => "6z",
+        "C" => "7z",
+        "5mr" => "0m",
+        "5pr" => "0p",
+        "5sr" => "0s",
+        "?" => return Err("Tile is hidden!"),
+        _ => pai,
+    };
+    tile_string.parse()
+}
+
+/// Converts the tile to MJAI notation (see `tile_from_mjai()`)
+pub fn mjai_from_tile(tile: &tiles::Tile) -> String {
+    if tile.is_honor() {
+        let honor_index = tile.kind_index() - 3 * 9;
+        return ["E", "S", "W", "N", "P", "F", "C"][honor_index].to_string();
+    }
+    let tile_string = tile.to_string();
+    if tile.is_red_five() {
+        return format!("5{}r", &tile_string[1..]);
+    }
+    tile_string
+}
+
+/// A bot that plays one seat of an MJAI game: it tracks its own hand and the tiles that are visible to it
+/// (the dora indicators, and the other players' discards and called tiles), and after each of its draws,
+/// it discards the tile that minimizes shanten then maximizes the live ukiere
+/// (see `shanten::get_best_discard_by_ukiere()`). Calls (chi, pon, and kan) aren't supported yet.
+#[derive(Debug, Clone)]
+pub struct MjaiBot {
+    pub player_id: usize,
+    pub hand_tiles: Vec<tiles::Tile>,
+    pub visible_tiles: Vec<tiles::Tile>,
+}
+
+impl MjaiBot {
+    pub fn new(player_id: usize) -> Self {
+        MjaiBot {
+            player_id,
+            hand_tiles: Vec::new(),
+            visible_tiles: Vec::new(),
+        }
+    }
+
+    /// Updates the bot's state from the event, and returns the bot's response to it (`MjaiEvent::Pass` if the bot
+    /// doesn't take any action)
+    pub fn handle_event(&mut self, event: &MjaiEvent) -> Result<MjaiEvent, &'static str> {
+        match event {
+            MjaiEvent::StartGame { id } => {
+                self.player_id = *id;
+            }
+            MjaiEvent::StartKyoku {
+                dora_marker,
+                tehais,
+            } => {
+                let tehai = tehais
+                    .get(self.player_id)
+                    .ok_or("Starting hands must include the player's hand!")?;
+                self.hand_tiles = tehai
+                    .iter()
+                    .map(|pai| tile_from_mjai(pai))
+                    .collect::<Result<Vec<tiles::Tile>, &'static str>>()?;
+                self.visible_tiles = vec![tile_from_mjai(dora_marker)?];
+            }
+            MjaiEvent::Tsumo { actor, pai } if *actor == self.player_id => {
+                self.hand_tiles.push(tile_from_mjai(pai)?);
+                if self.hand_tiles.len() != 14 {
+                    return Err("Hand must have 14 tiles after drawing!");
+                }
+                let discard_tile =
+                    shanten::get_best_discard_by_ukiere(&self.hand_tiles, &self.visible_tiles);
+                let discard_pai = mjai_from_tile(&discard_tile);
+                return Ok(MjaiEvent::Dahai {
+                    actor: self.player_id,
+                    tsumogiri: discard_pai == *pai,
+                    pai: discard_pai,
+                });
+            }
+            MjaiEvent::Dahai { actor, pai, .. } => {
+                let tile = tile_from_mjai(pai)?;
+                if *actor == self.player_id {
+                    let hand_index = self
+                        .hand_tiles
+                        .iter()
+                        .position(|hand_tile| hand_tile.to_string() == tile.to_string())
+                        .ok_or("Discarded tile must be in the hand!")?;
+                    self.hand_tiles.remove(hand_index);
+                } else {
+                    self.visible_tiles.push(tile);
+                }
+            }
+            MjaiEvent::Chi {
+                actor, consumed, ..
+            }
+            | MjaiEvent::Pon {
+                actor, consumed, ..
+            }
+            | MjaiEvent::Daiminkan {
+                actor, consumed, ..
+            }
+            | MjaiEvent::Ankan { actor, consumed } => {
+                if *actor == self.player_id {
+                    return Err("Calls aren't supported yet!");
+                }
+                // the called tile (if any) was already visible as a discard
+                for pai in consumed.iter() {
+                    self.visible_tiles.push(tile_from_mjai(pai)?);
+                }
+            }
+            MjaiEvent::Kakan { actor, pai, .. } => {
+                if *actor == self.player_id {
+                    return Err("Calls aren't supported yet!");
+                }
+                // the consumed tiles were already visible in the called pon, only the added tile is new
+                self.visible_tiles.push(tile_from_mjai(pai)?);
+            }
+            MjaiEvent::Dora { dora_marker } => {
+                self.visible_tiles.push(tile_from_mjai(dora_marker)?);
+            }
+            _ => {}
+        }
+        Ok(MjaiEvent::Pass)
+    }
+
+    /// Same as `handle_event()`, but the event and the response are JSON messages
+    pub fn handle_message(&mut self, message: &str) -> Result<String, &'static str> {
+        let event: MjaiEvent =
+            serde_json::from_str(message).map_err(|_| "Message must be a valid MJAI event!")?;
+        let response = self.handle_event(&event)?;
+        serde_json::to_string(&response).map_err(|_| "Response must serialize to JSON!")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mjai_tile_notation() {
+        for (pai, tile_string) in [
+            ("1m", "1m"),
+            ("9s", "9s"),
+            ("5pr", "0p"),
+            ("E", "1z"),
+            ("N", "4z"),
+            ("P", "5z"),
+            ("F", "6z"),
+            ("C", "7z"),
+        ] {
+            let tile = tile_from_mjai(pai).unwrap();
+            assert_eq!(tile.to_string(), tile_string);
+            assert_eq!(mjai_from_tile(&tile), pai);
+        }
+        assert!(tile_from_mjai("?").is_err());
+        assert!(tile_from_mjai("X").is_err());
+    }
+
+    #[test]
+    fn test_mjai_events_parse() {
+        let event: MjaiEvent =
+            serde_json::from_str(r#"{"type":"dahai","actor":2,"pai":"5sr","tsumogiri":false}"#)
+                .unwrap();
+        assert_eq!(
+            event,
+            MjaiEvent::Dahai {
+                actor: 2,
+                pai: String::from("5sr"),
+                tsumogiri: false
+            }
+        );
+        // unused fields and unknown message types are ignored
+        let event: MjaiEvent = serde_json::from_str(r#"{"type":"reach","actor":1}"#).unwrap();
+        assert_eq!(event, MjaiEvent::Unknown);
+        assert_eq!(
+            serde_json::to_string(&MjaiEvent::Pass).unwrap(),
+            r#"{"type":"none"}"#
+        );
+    }
+
+    #[test]
+    fn test_mjai_bot_discards() {
+        let hidden_tehai = format!("[{}]", vec![r#""?""#; 13].join(","));
+        let messages = [
+            String::from(r#"{"type":"start_game","id":1,"names":["a","b","c","d"]}"#),
+            format!(
+                r#"{{"type":"start_kyoku","bakaze":"E","kyoku":1,"honba":0,"kyotaku":0,"oya":0,"dora_marker":"2s","tehais":[{},["1m","2m","3m","5pr","6p","9p","3s","4s","7s","8s","E","E","C"],{},{}]}}"#,
+                hidden_tehai, hidden_tehai, hidden_tehai
+            ),
+            String::from(r#"{"type":"tsumo","actor":0,"pai":"?"}"#),
+            String::from(r#"{"type":"dahai","actor":0,"pai":"5s","tsumogiri":true}"#),
+        ];
+        let mut bot = MjaiBot::new(0);
+        for message in messages.iter() {
+            assert_eq!(bot.handle_message(message).unwrap(), r#"{"type":"none"}"#);
+        }
+        assert_eq!(bot.player_id, 1);
+        assert_eq!(
+            bot.visible_tiles
+                .iter()
+                .map(|tile| tile.to_string())
+                .collect::<Vec<String>>(),
+            vec!["2s", "5s"]
+        );
+
+        // after drawing, the bot discards the solver's best discard for the hand
+        let response = bot
+            .handle_message(r#"{"type":"tsumo","actor":1,"pai":"7p"}"#)
+            .unwrap();
+        let expected_hand = tiles::tiles_from_string("123m0679p3478s117z");
+        let expected_discard =
+            shanten::get_best_discard_by_ukiere(&expected_hand, &tiles::tiles_from_string("25s"));
+        let response_event: MjaiEvent = serde_json::from_str(&response).unwrap();
+        assert_eq!(
+            response_event,
+            MjaiEvent::Dahai {
+                actor: 1,
+                pai: mjai_from_tile(&expected_discard),
+                tsumogiri: expected_discard.to_string() == "7p"
+            }
+        );
+
+        // the discard is removed from the hand once the server confirms it
+        let discard_pai = mjai_from_tile(&expected_discard);
+        bot.handle_message(&format!(
+            r#"{{"type":"dahai","actor":1,"pai":"{}","tsumogiri":false}}"#,
+            discard_pai
+        ))
+        .unwrap();
+        assert_eq!(bot.hand_tiles.len(), 13);
+        assert!(bot
+            .handle_message(
+                r#"{"type":"pon","actor":1,"target":0,"pai":"5s","consumed":["5s","5s"]}"#
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_mjai_bot_visible_called_tiles() {
+        let mut bot = MjaiBot::new(0);
+        for message in [
+            r#"{"type":"dahai","actor":2,"pai":"P","tsumogiri":false}"#,
+            r#"{"type":"pon","actor":3,"target":2,"pai":"P","consumed":["P","P"]}"#,
+            r#"{"type":"kakan","actor":3,"pai":"P","consumed":["P","P","P"]}"#,
+            r#"{"type":"ankan","actor":2,"consumed":["9m","9m","9m","9m"]}"#,
+        ] {
+            bot.handle_message(message).unwrap();
+        }
+        // each tile of the added quad (and the closed quad) is counted once
+        assert_eq!(
+            bot.visible_tiles
+                .iter()
+                .map(|tile| tile.to_string())
+                .collect::<Vec<String>>(),
+            vec!["5z", "5z", "5z", "5z", "9m", "9m", "9m", "9m"]
+        );
+    }
+}
@@ -0,0 +1,2002 @@
+use crate::{shanten, state, tile_grouping, tiles, yaku};
+
+/// The result of scoring a winning hand: either a yakuman hand, or a normal hand that is scored with han and fu
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScoreResult {
+    /// the hand has at least one yakuman, and is scored as a multiple of a single yakuman
+    /// (e.g. 2 for a double yakuman, or for two different yakuman). The regular yaku and fu aren't counted.
+    Yakuman {
+        multiplier: u8,
+        yaku: Vec<yaku::Yaku>,
+    },
+    /// the hand doesn't have any yakuman. A hand with 13+ han is a counted yakuman (kazoe yakuman),
+    /// but is still a normal hand, i.e. it's never combined with (or counted as multiple) yakuman.
+    Normal { han: u32, fu: u32 },
+}
+
+impl ScoreResult {
+    /// The limit hand of a normal hand (see `classify_limit`). A yakuman hand isn't classified as a limit hand.
+    pub fn limit_hand(&self) -> Option<LimitHand> {
+        match self {
+            Self::Yakuman { .. } => None,
+            Self::Normal { han, fu } => classify_limit(*han, *fu),
+        }
+    }
+}
+
+/// The limit hands (i.e. the hands whose base points are capped rather than computed from han and fu)
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum LimitHand {
+    Mangan,
+    Haneman,
+    Baiman,
+    Sanbaiman,
+    KazoeYakuman,
+}
+
+impl LimitHand {
+    /// The human-readable name of the limit hand, e.g. "mangan"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Mangan => "mangan",
+            Self::Haneman => "haneman",
+            Self::Baiman => "baiman",
+            Self::Sanbaiman => "sanbaiman",
+            Self::KazoeYakuman => "kazoe yakuman",
+        }
+    }
+}
+
+/// Which limit hand a normal hand with the han and fu is (using the same thresholds as `compute_base_points`),
+/// or None if the hand is below mangan. e.g. 4 han 40 fu and 3 han 70 fu are mangan, but 4 han 30 fu isn't.
+pub fn classify_limit(han: u32, fu: u32) -> Option<LimitHand> {
+    limit_from_base_points(compute_base_points(han, fu))
+}
+
+fn limit_from_base_points(base_points: u32) -> Option<LimitHand> {
+    match base_points {
+        8000 => Some(LimitHand::KazoeYakuman),
+        6000 => Some(LimitHand::Sanbaiman),
+        4000 => Some(LimitHand::Baiman),
+        3000 => Some(LimitHand::Haneman),
+        2000 => Some(LimitHand::Mangan),
+        _ => None,
+    }
+}
+
+/// How renhou (a non-dealer winning by ron before their first draw, see `yaku::has_renhou`) is scored,
+/// which varies by ruleset
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum RenhouValue {
+    /// renhou isn't a yaku
+    Disabled,
+    /// renhou is worth mangan (or the hand's value without renhou, if that's higher)
+    Mangan,
+    /// renhou is a yakuman
+    Yakuman,
+}
+
+/// The local yaku (yaku that only some rulesets score) that are enabled. By default, none of them are scored.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct LocalYakuConfig {
+    /// daisharin, and its variants in the other suits, as yakuman (see `yaku::daisharin_yaku_from_counts`)
+    pub daisharin: bool,
+    /// open riichi, worth 1 han more than riichi (see `yaku::has_open_riichi`)
+    pub open_riichi: bool,
+}
+
+/// Optional (house) rules that change how hands are scored. The default is the standard rules
+/// (open tanyao, no kiriage mangan, 3 red fives, no renhou, and no local yaku).
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct ScoringRules {
+    /// aotenjou (no-limit) scoring: the base points are always fu * 2^(2 + han) without the limit hands,
+    /// and yakuman are counted as 13 han each (added to the han from the other yaku and dora) rather than as a multiplier
+    pub aotenjou: bool,
+    /// kuitan: whether tanyao can be scored with an open hand
+    pub kuitan: bool,
+    /// kiriage mangan: 4 han 30 fu and 3 han 60 fu (1920 base points) are rounded up to mangan
+    pub kiriage_mangan: bool,
+    /// the number of red fives in the tiles. If 0, red fives aren't counted as dora
+    pub aka_count: u8,
+    /// whether renhou is a yaku, and how much it's worth
+    pub renhou: RenhouValue,
+    /// which local yaku are scored
+    pub local_yaku: LocalYakuConfig,
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        Self {
+            aotenjou: false,
+            kuitan: true,
+            kiriage_mangan: false,
+            aka_count: 3,
+            renhou: RenhouValue::Disabled,
+            local_yaku: LocalYakuConfig::default(),
+        }
+    }
+}
+
+impl ScoringRules {
+    /// The base points of a normal hand from its han and fu under these rules (see `compute_base_points`).
+    /// Under aotenjou, the base points are uncapped (saturating at u32::MAX).
+    pub fn base_points(&self, han: u32, fu: u32) -> u32 {
+        if self.aotenjou {
+            return 2u32
+                .checked_pow(2 + han)
+                .and_then(|multiplier| fu.checked_mul(multiplier))
+                .unwrap_or(u32::MAX);
+        }
+        let base_points = compute_base_points(han, fu);
+        if self.kiriage_mangan && base_points == 1920 {
+            return 2000;
+        }
+        base_points
+    }
+}
+
+/// Scores the winning hand grouping. If the hand has any yakuman, the yakuman are stacked into a single multiplier
+/// (a double yakuman counts as 2), otherwise the hand is scored by the han from its yaku (see `yaku::han_from_yaku`),
+/// plus the han from dora and red fives (see `yaku::han_from_dora`) if the hand has a yaku, and its fu (see `compute_fu`).
+/// Under aotenjou (see `ScoringRules`), a yakuman hand is scored as a normal hand, with 13 han per yakuman,
+/// and without kuitan, an open hand doesn't score tanyao. Renhou is scored as configured by the rules (see `RenhouValue`).
+pub fn compute_score(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+    rules: &ScoringRules,
+) -> ScoreResult {
+    let is_renhou = rules.renhou != RenhouValue::Disabled
+        && yaku::has_renhou(tile_grouping, hand_state, player_state);
+    let mut yakuman_han = yaku::han_from_yakuman(
+        player_tiles,
+        winning_tile,
+        tile_grouping,
+        hand_state,
+        player_state,
+    );
+    if is_renhou && rules.renhou == RenhouValue::Yakuman {
+        yakuman_han += yaku::Yaku::han_value(&yaku::Yaku::KazoeYakuman);
+    }
+    let is_hand_open = tile_grouping.iter().any(|tile_group| tile_group.is_open());
+    let local_yakuman = if rules.local_yaku.daisharin && !is_hand_open {
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(*winning_tile);
+        yaku::daisharin_yaku_from_counts(&shanten::count_tiles_by_kind(&winning_tiles))
+    } else {
+        None
+    };
+    if let Some(local_yakuman) = local_yakuman {
+        yakuman_han += yaku::Yaku::han_value(&local_yakuman);
+    }
+    if yakuman_han > 0 && !rules.aotenjou {
+        let mut yakuman_list = yaku::get_yakuman_list(
+            player_tiles,
+            winning_tile,
+            tile_grouping,
+            hand_state,
+            player_state,
+        );
+        if is_renhou && rules.renhou == RenhouValue::Yakuman {
+            yakuman_list.push(yaku::Yaku::Renhou);
+        }
+        yakuman_list.extend(local_yakuman);
+        return ScoreResult::Yakuman {
+            multiplier: (yakuman_han / yaku::Yaku::han_value(&yaku::Yaku::KazoeYakuman)) as u8,
+            yaku: yakuman_list,
+        };
+    }
+    let mut han =
+        yakuman_han + yaku::han_from_yaku(tile_grouping, winning_tile, hand_state, player_state);
+    let fu = compute_fu(
+        player_tiles,
+        winning_tile,
+        tile_grouping,
+        hand_state,
+        player_state,
+    );
+    if !rules.kuitan && is_hand_open && yaku::has_tanyao(tile_grouping, hand_state, player_state) {
+        han -= yaku::Yaku::han_value(&yaku::Yaku::Tanyao);
+    }
+    if han > 0 {
+        han += yaku::han_from_dora(tile_grouping, hand_state, player_state);
+        if rules.aka_count > 0 {
+            han += yaku::han_from_red_fives(tile_grouping, hand_state, player_state);
+        }
+    }
+    if rules.local_yaku.open_riichi
+        && yaku::has_open_riichi(tile_grouping, hand_state, player_state)
+    {
+        han += yaku::Yaku::han_value(&yaku::Yaku::OpenRiichi);
+    }
+    if is_renhou && rules.renhou == RenhouValue::Mangan {
+        han = han.max(yaku::Yaku::han_value(&yaku::Yaku::Renhou));
+    }
+    ScoreResult::Normal { han, fu }
+}
+
+/// The score of a winning hand, with the payments for winning by ron and by tsumo (see `score_hand`).
+/// With the serde feature, it serializes to JSON, with the winning tile in MSPZ notation (e.g. "5s").
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandScore {
+    /// the winning tile, in MSPZ notation (see `tiles::Tile::to_string()`)
+    pub winning_tile: String,
+    /// the han of the hand (13 han per yakuman for a yakuman hand)
+    pub han: u32,
+    /// the fu of the hand (0 for a yakuman hand, which is scored without fu)
+    pub fu: u32,
+    /// the name and han of each yaku (see `compute_han_breakdown`), or of each yakuman for a yakuman hand
+    pub yaku: Vec<(String, u32)>,
+    /// the number of dora in the hand (see `yaku::num_dora_from_indicators`)
+    pub dora: u32,
+    /// the number of ura dora in the hand (0 if the player didn't declare riichi)
+    pub ura_dora: u32,
+    /// the number of red fives in the hand (0 if the rules don't have red fives)
+    pub aka_dora: u32,
+    /// the name of the limit hand (e.g. "mangan", or "yakuman" and "2x yakuman" for a yakuman hand),
+    /// or None if the hand is below mangan
+    pub limit_name: Option<String>,
+    /// the payment from the discarding player when winning by ron (see `compute_ron_payment`)
+    pub ron: u32,
+    /// the payment from the dealer when winning by tsumo (0 if the winner is the dealer, see `compute_tsumo_payments`)
+    pub tsumo_dealer_pays: u32,
+    /// the payment from each of the other non-dealers when winning by tsumo (see `compute_tsumo_payments`)
+    pub tsumo_others_pay: u32,
+}
+
+/// Scores the winning hand grouping (see `compute_score`) and computes its payments. The han and fu are scored for
+/// the player's winning tile source, and the ron and tsumo payments are both from that han and fu (only the payment
+/// for how the hand was won applies). The payments don't include honba or riichi sticks (see `compute_ron_total`).
+/// The player is the dealer if their seat wind is East. Kokushi musou can't be grouped, so it's checked from the
+/// player's tiles and the winning tile (see `yaku::han_from_kokushi_yaku`) and scored as a yakuman with any grouping
+/// (e.g. an empty grouping).
+pub fn score_hand(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+    rules: &ScoringRules,
+) -> HandScore {
+    let is_dealer = matches!(player_state.seat_wind, state::WindDirection::East);
+    let score_result = kokushi_score_result(player_tiles, winning_tile, hand_state, player_state)
+        .unwrap_or_else(|| {
+            compute_score(
+                player_tiles,
+                winning_tile,
+                tile_grouping,
+                hand_state,
+                player_state,
+                rules,
+            )
+        });
+    let (han, fu, base_points, limit_name, yaku_list) = match score_result {
+        ScoreResult::Yakuman {
+            multiplier,
+            yaku: yakuman_list,
+        } => {
+            let limit_name = if multiplier == 1 {
+                String::from("yakuman")
+            } else {
+                format!("{}x yakuman", multiplier)
+            };
+            (
+                multiplier as u32 * yaku::Yaku::han_value(&yaku::Yaku::KazoeYakuman),
+                0,
+                yakuman_base_points(multiplier),
+                Some(limit_name),
+                yakuman_list
+                    .iter()
+                    .map(|yakuman| (String::from(yakuman.name()), yaku::Yaku::han_value(yakuman)))
+                    .collect(),
+            )
+        }
+        ScoreResult::Normal { han, fu } => {
+            let base_points = rules.base_points(han, fu);
+            let limit_name = if rules.aotenjou {
+                None
+            } else {
+                limit_from_base_points(base_points).map(|limit_hand| limit_hand.name().to_string())
+            };
+            let breakdown =
+                compute_han_breakdown(tile_grouping, winning_tile, hand_state, player_state, rules);
+            (han, fu, base_points, limit_name, breakdown)
+        }
+    };
+    let ura_dora = if player_state.in_riichi || player_state.in_double_riichi {
+        yaku::num_dora_from_indicators(tile_grouping, &hand_state.ura_dora_indicators)
+    } else {
+        0
+    };
+    let aka_dora = if rules.aka_count > 0 {
+        yaku::han_from_red_fives(tile_grouping, hand_state, player_state)
+    } else {
+        0
+    };
+    let (tsumo_dealer_pays, tsumo_others_pay) = compute_tsumo_payments(base_points, is_dealer);
+    HandScore {
+        winning_tile: winning_tile.to_string(),
+        han,
+        fu,
+        yaku: yaku_list,
+        dora: yaku::num_dora_from_indicators(tile_grouping, &hand_state.dora_indicators),
+        ura_dora,
+        aka_dora,
+        limit_name,
+        ron: compute_ron_payment(base_points, is_dealer),
+        tsumo_dealer_pays,
+        tsumo_others_pay,
+    }
+}
+
+/// The han from each yaku in the winning hand grouping with the name of the yaku, plus the han from dora and red fives
+/// (if the hand has a yaku), for displaying how a normal hand is scored, e.g. Riichi 1 / Pinfu 1 / Tanyao 1 / Dora 2.
+/// The total han is the same as the han from `compute_score` for a hand without yakuman.
+pub fn compute_han_breakdown(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    winning_tile: &tiles::Tile,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+    rules: &ScoringRules,
+) -> Vec<(String, u32)> {
+    let is_hand_open = tile_grouping.iter().any(|tile_group| tile_group.is_open());
+    let mut breakdown: Vec<(String, u32)> = Vec::new();
+    for yaku in yaku::get_yaku_list(tile_grouping, winning_tile, hand_state, player_state) {
+        let han = match yaku {
+            yaku::Yaku::Yakuhai => {
+                yaku::han_from_yakuhai_yaku(tile_grouping, hand_state, player_state).unwrap_or(0)
+            }
+            yaku::Yaku::Ittsu => {
+                yaku::han_from_ittsu_yaku(tile_grouping, hand_state, player_state).unwrap_or(0)
+            }
+            yaku::Yaku::SanshokuDoujun => {
+                yaku::han_from_sanshoku_doujun_yaku(tile_grouping, hand_state, player_state)
+                    .unwrap_or(0)
+            }
+            yaku::Yaku::Tanyao if is_hand_open && !rules.kuitan => continue,
+            _ => yaku::Yaku::han_value(&yaku),
+        };
+        breakdown.push((String::from(yaku.name()), han));
+    }
+    if rules.local_yaku.open_riichi
+        && yaku::has_open_riichi(tile_grouping, hand_state, player_state)
+    {
+        let open_riichi = yaku::Yaku::OpenRiichi;
+        breakdown.push((
+            String::from(open_riichi.name()),
+            yaku::Yaku::han_value(&open_riichi),
+        ));
+    }
+    if breakdown.is_empty() {
+        return breakdown;
+    }
+    let dora_han = yaku::han_from_dora(tile_grouping, hand_state, player_state);
+    if dora_han > 0 {
+        breakdown.push((String::from("Dora"), dora_han));
+    }
+    if rules.aka_count > 0 {
+        let red_five_han = yaku::han_from_red_fives(tile_grouping, hand_state, player_state);
+        if red_five_han > 0 {
+            breakdown.push((String::from("Aka Dora"), red_five_han));
+        }
+    }
+    breakdown
+}
+
+// the maximum fu of a winning hand: four closed kans of terminals or honors (4 * 32 fu), a double wind pair (4 fu),
+// a tanki wait (2 fu), and a closed ron (10 fu), plus the 20 base fu, is 164 fu, rounded up to 170 fu.
+// This is suukantsu (a yakuman), so its fu only matters under aotenjou.
+const MAX_FU: u32 = 170;
+
+/// The fu of the winning hand grouping: 20 fu (base fu) plus the fu from the groups, pair, wait, and winning condition
+/// (see `yaku::scoring_fu`), rounded up to the nearest 10. Seven pairs (chiitoitsu) is always 25 fu.
+/// An open hand with the pinfu shape (all sequences, a non-yakuhai pair, and an open wait) can't score pinfu,
+/// and is always scored as 30 fu (aka kuipinfu), by ron (which would otherwise be 20 fu) or by tsumo.
+/// Panics if the fu is more than any winning hand can score (see `try_compute_fu`).
+pub fn compute_fu(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> u32 {
+    try_compute_fu(
+        player_tiles,
+        winning_tile,
+        tile_grouping,
+        hand_state,
+        player_state,
+    )
+    .unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Same as `compute_fu`, but returns an Err if the fu is more than any winning hand can score (170 fu),
+/// which means that the hand grouping isn't a valid winning hand (e.g. it has more than four groups)
+pub fn try_compute_fu(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Result<u32, &'static str> {
+    if tile_grouping::number_pair_groups(tile_grouping) == 7 {
+        return Ok(25);
+    }
+    let raw_fu = yaku::scoring_fu(
+        player_tiles,
+        winning_tile,
+        tile_grouping,
+        hand_state,
+        player_state,
+    );
+    let fu = round_up_to_ten(20 + raw_fu);
+    if fu > MAX_FU {
+        return Err("Fu is more than the maximum fu of a winning hand!");
+    }
+
+    let is_hand_open = tile_grouping.iter().any(|tile_group| tile_group.is_open());
+    if is_hand_open && fu < 30 {
+        // kuipinfu: an open hand never earns fewer than 30 fu
+        return Ok(30);
+    }
+    Ok(fu)
+}
+
+// the first and last fu values in the base points table (every 10 fu in between)
+const BASE_POINTS_TABLE_MIN_FU: u32 = 20;
+const BASE_POINTS_TABLE_MAX_FU: u32 = 110;
+
+/// The base points of each hand below mangan, i.e. fu * 2^(2 + han) capped at 2000 (mangan):
+/// indexed by [han - 1][(fu - 20) / 10], for 1-4 han and 20-110 fu
+#[rustfmt::skip]
+const BASE_POINTS_TABLE: [[u32; 10]; 4] = [
+    //   20    30    40    50    60    70    80    90   100   110 fu
+    [ 160,  240,  320,  400,  480,  560,  640,  720,  800,  880],
+    [ 320,  480,  640,  800,  960, 1120, 1280, 1440, 1600, 1760],
+    [ 640,  960, 1280, 1600, 1920, 2000, 2000, 2000, 2000, 2000],
+    [1280, 1920, 2000, 2000, 2000, 2000, 2000, 2000, 2000, 2000],
+];
+
+/// The base points of a normal hand (before the payment multipliers), from its han and fu.
+/// Below mangan, the base points are fu * 2^(2 + han) (looked up in a table, except for 25 fu i.e. seven pairs),
+/// and the limit hands are: mangan (5 han, or a smaller hand over 2000 base points) = 2000, haneman (6-7 han) = 3000,
+/// baiman (8-10 han) = 4000, sanbaiman (11-12 han) = 6000, and kazoe yakuman (13+ han) = 8000
+pub fn compute_base_points(han: u32, fu: u32) -> u32 {
+    match han {
+        1..=4
+            if (BASE_POINTS_TABLE_MIN_FU..=BASE_POINTS_TABLE_MAX_FU).contains(&fu)
+                && fu.is_multiple_of(10) =>
+        {
+            BASE_POINTS_TABLE[(han - 1) as usize][((fu - BASE_POINTS_TABLE_MIN_FU) / 10) as usize]
+        }
+        0..=4 => (fu * 2u32.pow(2 + han)).min(2000),
+        5 => 2000,
+        6..=7 => 3000,
+        8..=10 => 4000,
+        11..=12 => 6000,
+        _ => 8000,
+    }
+}
+
+/// The payment from the discarding player when winning by ron: 6x the base points for the dealer,
+/// or 4x the base points for a non-dealer, rounded up to the nearest 100
+pub fn compute_ron_payment(base_points: u32, is_dealer: bool) -> u32 {
+    let multiplier = if is_dealer { 6 } else { 4 };
+    round_up_to_hundred(base_points * multiplier)
+}
+
+/// The payments when winning by tsumo, as (the payment from the dealer, the payment from each non-dealer),
+/// each rounded up to the nearest 100. If the winner is the dealer, every other player pays 2x the base points
+/// (and the payment from the dealer is 0). Otherwise, the dealer pays 2x and each non-dealer pays 1x the base points.
+pub fn compute_tsumo_payments(base_points: u32, is_dealer: bool) -> (u32, u32) {
+    if is_dealer {
+        (0, round_up_to_hundred(2 * base_points))
+    } else {
+        (
+            round_up_to_hundred(2 * base_points),
+            round_up_to_hundred(base_points),
+        )
+    }
+}
+
+/// The total points the winner receives when winning by ron: the ron payment, plus 300 per honba stick
+/// (paid by the discarding player), plus 1000 per riichi stick on the table
+pub fn compute_ron_total(base_points: u32, is_dealer: bool, hand_state: &state::HandState) -> u32 {
+    compute_ron_payment(base_points, is_dealer)
+        + 300 * hand_state.honba_sticks
+        + 1000 * hand_state.riichi_sticks
+}
+
+/// The total points the winner receives when winning by tsumo: the tsumo payments from the other three players,
+/// plus 100 per honba stick from each of them, plus 1000 per riichi stick on the table
+pub fn compute_tsumo_total(
+    base_points: u32,
+    is_dealer: bool,
+    hand_state: &state::HandState,
+) -> u32 {
+    let (dealer_payment, non_dealer_payment) = compute_tsumo_payments(base_points, is_dealer);
+    let num_non_dealers_paying = if is_dealer { 3 } else { 2 };
+    dealer_payment
+        + num_non_dealers_paying * non_dealer_payment
+        + 3 * 100 * hand_state.honba_sticks
+        + 1000 * hand_state.riichi_sticks
+}
+
+/// The payments for nagashi mangan (see `yaku::is_nagashi_mangan`) at an exhaustive draw, which is paid as a mangan tsumo:
+/// (the payment from the dealer, the payment from each non-dealer), see `compute_tsumo_payments`.
+/// Returns None if the player doesn't have nagashi mangan.
+pub fn compute_nagashi_mangan_payments(
+    player_state: &state::PlayerState,
+    is_dealer: bool,
+) -> Option<(u32, u32)> {
+    if !yaku::is_nagashi_mangan(
+        &player_state.discards,
+        player_state.any_discards_called_by_others,
+    ) {
+        return None;
+    }
+    let mangan_han = yaku::Yaku::han_value(&yaku::Yaku::NagashiMangan);
+    Some(compute_tsumo_payments(
+        compute_base_points(mangan_han, 0),
+        is_dealer,
+    ))
+}
+
+/// A player who is liable (pao) for a yakuman: the player who discarded the tile that completed the third dragon group
+/// (for daisangen) or the fourth wind group (for daisuushii) into the winner's open hand by a call
+#[derive(Copy, Clone)]
+pub struct PaoLiability {
+    /// the seat wind of the liable player
+    pub liable_seat: state::WindDirection,
+}
+
+/// The payments for a yakuman hand (excluding honba and riichi sticks), from each player indexed by their seat wind
+/// (East = 0, South = 1, West = 2, North = 3). The winner is the dealer if they are the East seat,
+/// and `discarder_seat` is None if the winner won by tsumo.
+/// If a player is liable for the yakuman (see `PaoLiability`), they pay the entire payment on a tsumo,
+/// and they split the payment evenly with the discarding player on a ron (unless they are the discarding player).
+pub fn compute_yakuman_payments(
+    multiplier: u8,
+    winner_seat: state::WindDirection,
+    discarder_seat: Option<state::WindDirection>,
+    pao: Option<PaoLiability>,
+) -> [u32; 4] {
+    let base_points = yakuman_base_points(multiplier);
+    let is_dealer = matches!(winner_seat, state::WindDirection::East);
+    let mut payments = [0; 4];
+    match (discarder_seat, pao) {
+        (Some(discarder_seat), Some(pao)) => {
+            let ron_payment = compute_ron_payment(base_points, is_dealer);
+            payments[seat_index(discarder_seat)] += ron_payment / 2;
+            payments[seat_index(pao.liable_seat)] += ron_payment / 2;
+        }
+        (Some(discarder_seat), None) => {
+            payments[seat_index(discarder_seat)] = compute_ron_payment(base_points, is_dealer);
+        }
+        (None, Some(pao)) => {
+            // the liable player pays the total of the tsumo payments, i.e. the same as a ron payment
+            payments[seat_index(pao.liable_seat)] = compute_ron_payment(base_points, is_dealer);
+        }
+        (None, None) => {
+            let (dealer_payment, non_dealer_payment) =
+                compute_tsumo_payments(base_points, is_dealer);
+            for (index, payment) in payments.iter_mut().enumerate() {
+                *payment = if index == 0 {
+                    dealer_payment
+                } else {
+                    non_dealer_payment
+                };
+            }
+            payments[seat_index(winner_seat)] = 0;
+        }
+    }
+    payments
+}
+
+/// Estimates the expected value (in points) of declaring riichi with the closed 13-tile tenpai hand: the probability
+/// of winning times the average value of a win, minus the 1000 point riichi stick (which is returned on a win).
+/// - the probability of winning is the probability of drawing one of the live wait tiles (not in the hand or the
+///   visible tiles) out of the unseen tiles, in each of the player's remaining draws (a quarter of the tiles remaining)
+/// - the value of each wait is the ron total (see `compute_ron_total`) of the most valuable way to win on it (see
+///   `live_wait_values`), including riichi, and the chance of ura dora: the expected number of ura dora (see
+///   `yaku::expected_num_ura_dora`, for the ura dora indicator that will be flipped under each dora indicator) is
+///   counted as that probability of scoring 1 more han
+/// - the average value is weighted by the number of live copies of each wait
+///
+/// Returns 0 if the hand isn't in tenpai or none of its waits are live.
+pub fn riichi_ev(
+    player_tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+    rules: &ScoringRules,
+) -> f64 {
+    let hand_counts = shanten::count_tiles_by_kind(player_tiles);
+    let num_unseen_tiles: u32 = (0..tiles::NUM_TILE_KINDS)
+        .map(|kind_index| {
+            shanten::live_count_of(
+                &tiles::Tile::from_kind_index(kind_index),
+                &hand_counts,
+                visible_tiles,
+            )
+        })
+        .sum();
+
+    let mut riichi_hand_state = hand_state.clone();
+    // the player's own riichi stick is on the table, and is returned on a win
+    riichi_hand_state.riichi_sticks += 1;
+    let mut riichi_player_state = player_state.clone();
+    riichi_player_state.in_riichi = true;
+    riichi_player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+        is_first_go_around: false,
+    });
+    let is_dealer = matches!(player_state.seat_wind, state::WindDirection::East);
+    // one ura dora indicator is flipped under each dora indicator
+    let num_ura_indicators = hand_state.dora_indicators.len() as u32;
+
+    let mut num_live_waits = 0;
+    let mut total_value = 0.0;
+    for (num_live, wait_value) in live_wait_values(
+        player_tiles,
+        visible_tiles,
+        &riichi_hand_state,
+        &riichi_player_state,
+        rules,
+        |wait_tile, score_result| match score_result {
+            ScoreResult::Normal { han, fu } if *han > 0 => {
+                let mut winning_tiles = player_tiles.clone();
+                winning_tiles.push(*wait_tile);
+                let ura_probability =
+                    yaku::expected_num_ura_dora(&winning_tiles, visible_tiles, num_ura_indicators)
+                        .min(1.0);
+                let value_without_ura =
+                    compute_ron_total(rules.base_points(*han, *fu), is_dealer, &riichi_hand_state);
+                let value_with_ura = compute_ron_total(
+                    rules.base_points(han + 1, *fu),
+                    is_dealer,
+                    &riichi_hand_state,
+                );
+                (1.0 - ura_probability) * value_without_ura as f64
+                    + ura_probability * value_with_ura as f64
+            }
+            _ => compute_ron_total(
+                base_points_of(score_result, rules),
+                is_dealer,
+                &riichi_hand_state,
+            ) as f64,
+        },
+    ) {
+        num_live_waits += num_live;
+        total_value += num_live as f64 * wait_value;
+    }
+    if num_live_waits == 0 || num_unseen_tiles == 0 {
+        return 0.0;
+    }
+
+    let average_value = total_value / num_live_waits as f64;
+    let num_draws = hand_state.tiles_remaining.div_ceil(4) as i32;
+    let win_probability =
+        1.0 - (1.0 - num_live_waits as f64 / num_unseen_tiles as f64).powi(num_draws);
+    win_probability * average_value - 1000.0
+}
+
+/// Evaluates each discard from the 14-tile hand that keeps the lowest shanten (see `shanten::ranked_discards()`) by its
+/// expected score: the sum over each wait of the tenpai hand after the discard of (live copies of the wait, i.e. not in
+/// the hand, the visible tiles, or the discard) * (the ron total of the winning hand with the most points, without
+/// riichi, see `live_wait_values`). This is the acceptance times the average value of the completed hand, so a
+/// narrower but more valuable wait can be better than a wide cheap wait. A wait without a yaku can't win by ron, so it has no value.
+/// Discards that don't reach tenpai don't have waits yet, so their expected score is 0.
+/// Returns each discard with its expected score, sorted from highest to lowest (ties in order of acceptance).
+pub fn best_value_discards(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+    rules: &ScoringRules,
+) -> Vec<(tiles::Tile, f64)> {
+    let discard_evals = shanten::ranked_discards(tiles, visible_tiles);
+    let best_shanten = match discard_evals.first() {
+        Some(discard_eval) => discard_eval.shanten,
+        None => return Vec::new(),
+    };
+    let mut ron_player_state = player_state.clone();
+    ron_player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+        is_first_go_around: false,
+    });
+    let is_dealer = matches!(player_state.seat_wind, state::WindDirection::East);
+
+    let mut discard_values: Vec<(tiles::Tile, f64)> = Vec::new();
+    for discard_eval in discard_evals
+        .iter()
+        .filter(|discard_eval| discard_eval.shanten == best_shanten)
+    {
+        let mut remaining_tiles = tiles.clone();
+        let discard_index = remaining_tiles
+            .iter()
+            .position(|tile| tile.kind_index() == discard_eval.tile.kind_index())
+            .expect("Discarded tile must be in the hand");
+        remaining_tiles.remove(discard_index);
+        let mut seen_tiles = visible_tiles.clone();
+        seen_tiles.push(discard_eval.tile);
+
+        let mut expected_score = 0.0;
+        if discard_eval.shanten == 0 {
+            for (num_live, ron_total) in live_wait_values(
+                &remaining_tiles,
+                &seen_tiles,
+                hand_state,
+                &ron_player_state,
+                rules,
+                |_, score_result| {
+                    compute_ron_total(base_points_of(score_result, rules), is_dealer, hand_state)
+                        as f64
+                },
+            ) {
+                expected_score += num_live as f64 * ron_total;
+            }
+        }
+        discard_values.push((discard_eval.tile, expected_score));
+    }
+    // (the sort is stable, so discards with the same expected score stay in order of acceptance)
+    discard_values.sort_by(|(_, value), (_, other_value)| other_value.total_cmp(value));
+    discard_values
+}
+
+/// For each wait of the 13-tile tenpai hand (see `shanten::get_waits()`) that has live copies (not in the hand or the
+/// visible tiles, see `shanten::live_count_of()`): the number of live copies, and the value of winning on the wait
+/// (by the `wait_value` of the wait tile and a score), using the most valuable way to win on it, i.e. each of its
+/// groupings (see `tile_grouping::all_winning_groupings()`), or kokushi musou (which can't be grouped).
+/// This is the shared expected value routine for choosing a discard by value and for the value of riichi.
+fn live_wait_values(
+    player_tiles: &Vec<tiles::Tile>,
+    visible_tiles: &[tiles::Tile],
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+    rules: &ScoringRules,
+    wait_value: impl Fn(&tiles::Tile, &ScoreResult) -> f64,
+) -> Vec<(u32, f64)> {
+    let hand_counts = shanten::count_tiles_by_kind(player_tiles);
+    let mut wait_values = Vec::new();
+    for wait_tile in shanten::get_waits(player_tiles, &vec![]) {
+        let num_live = shanten::live_count_of(&wait_tile, &hand_counts, visible_tiles);
+        if num_live == 0 {
+            continue;
+        }
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(wait_tile);
+        let mut score_results: Vec<ScoreResult> =
+            tile_grouping::all_winning_groupings(&winning_tiles, &vec![])
+                .iter()
+                .map(|grouping| {
+                    compute_score(
+                        player_tiles,
+                        &wait_tile,
+                        grouping,
+                        hand_state,
+                        player_state,
+                        rules,
+                    )
+                })
+                .collect();
+        score_results.extend(kokushi_score_result(
+            player_tiles,
+            &wait_tile,
+            hand_state,
+            player_state,
+        ));
+        let max_value = score_results
+            .iter()
+            .map(|score_result| wait_value(&wait_tile, score_result))
+            .fold(0.0, f64::max);
+        wait_values.push((num_live, max_value));
+    }
+    wait_values
+}
+
+/// The score of kokushi musou (see `yaku::han_from_kokushi_yaku`), which can't be grouped, or None if the player's
+/// tiles and the winning tile aren't kokushi musou
+fn kokushi_score_result(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Option<ScoreResult> {
+    let kokushi_han =
+        yaku::han_from_kokushi_yaku(player_tiles, winning_tile, hand_state, player_state)?;
+    Some(ScoreResult::Yakuman {
+        multiplier: (kokushi_han / yaku::Yaku::han_value(&yaku::Yaku::KazoeYakuman)) as u8,
+        yaku: vec![yaku::Yaku::KokushiMusou],
+    })
+}
+
+/// The base points of the score under the rules (see `ScoringRules::base_points`), or of its yakuman (see
+/// `yakuman_base_points`). A normal hand without a yaku can't win, so it has no base points.
+fn base_points_of(score_result: &ScoreResult, rules: &ScoringRules) -> u32 {
+    match score_result {
+        ScoreResult::Yakuman { multiplier, .. } => yakuman_base_points(*multiplier),
+        ScoreResult::Normal { han: 0, .. } => 0,
+        ScoreResult::Normal { han, fu } => rules.base_points(*han, *fu),
+    }
+}
+
+/// The base points of a yakuman hand: the limit of a single yakuman (see `compute_base_points`) times the multiplier
+fn yakuman_base_points(multiplier: u8) -> u32 {
+    compute_base_points(yaku::Yaku::han_value(&yaku::Yaku::KazoeYakuman), 0) * u32::from(multiplier)
+}
+
+fn seat_index(seat: state::WindDirection) -> usize {
+    seat as usize - 1
+}
+
+fn round_up_to_ten(fu: u32) -> u32 {
+    fu.div_ceil(10) * 10
+}
+
+fn round_up_to_hundred(points: u32) -> u32 {
+    points.div_ceil(100) * 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_hand_state() -> state::HandState {
+        state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1z")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        }
+    }
+
+    fn test_player_state() -> state::PlayerState {
+        state::PlayerState {
+            discards: vec![tiles::Tile::from_string("5p")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        }
+    }
+
+    fn compute_score_from_string(
+        hand_string: &str,
+        winning_tile_string: &str,
+        hand_state: &state::HandState,
+        player_state: &state::PlayerState,
+        rules: &ScoringRules,
+    ) -> ScoreResult {
+        let (player_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string(hand_string).unwrap();
+        let winning_tile = tiles::Tile::from_string(winning_tile_string);
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &declared_groups)
+            .expect("Should be a winning hand");
+        compute_score(
+            &player_tiles,
+            &winning_tile,
+            &groupings[0],
+            hand_state,
+            player_state,
+            rules,
+        )
+    }
+
+    #[test]
+    fn test_best_value_discards() {
+        let mut hand_state = test_hand_state();
+        let player_state = test_player_state();
+        let rules = ScoringRules::default();
+        // the dora is 5s, and the hand has a red 5s
+        hand_state.dora_indicators = vec![tiles::Tile::from_string("4s")];
+        // 234m 678m 345p 88p 23s 0s:
+        // - discarding the red 5s leaves a wide but cheap wait on 1s (pinfu) or 4s (tanyao, pinfu)
+        // - discarding 2s leaves a narrow wait on 4s, but with tanyao, the dora, and the red five
+        let tiles = tiles::tiles_from_string("234m678m345p88p230s");
+        let discard_evals = shanten::ranked_discards(&tiles, &vec![]);
+        assert_eq!(discard_evals[0].tile.to_string(), "0s");
+        assert_eq!(discard_evals[0].acceptance, 8);
+
+        let discard_values =
+            best_value_discards(&tiles, &vec![], &hand_state, &player_state, &rules);
+        let discard_strings: Vec<(String, f64)> = discard_values
+            .iter()
+            .map(|(tile, value)| (tile.to_string(), *value))
+            .collect();
+        assert_eq!(discard_strings.len(), 2);
+        assert_eq!(discard_strings[0].0, "2s");
+        // 1s: 4 live * 1000 (1 han 30 fu), 4s: 4 live * 2000 (2 han 30 fu)
+        assert_eq!(discard_strings[1], (String::from("0s"), 12000.0));
+        assert!(discard_strings[0].1 > discard_strings[1].1);
+
+        // a wait without a yaku can't win by ron, so it has no value:
+        // 111m 789m 345p 88p 23s 9s, discarding 9s leaves a wait on 1s or 4s without a yaku
+        let tiles = tiles::tiles_from_string("111789m345p88p239s");
+        let discard_values =
+            best_value_discards(&tiles, &vec![], &hand_state, &player_state, &rules);
+        let (_, nine_sou_value) = discard_values
+            .iter()
+            .find(|(tile, _)| tile.to_string() == "9s")
+            .unwrap();
+        assert_eq!(*nine_sou_value, 0.0);
+    }
+
+    #[test]
+    fn test_riichi_ev() {
+        let mut hand_state = test_hand_state();
+        let player_state = test_player_state();
+        let rules = ScoringRules::default();
+        // 123m 567p 99s 34567s: a wide wait on 2s, 5s, or 8s (11 live tiles), but only riichi and pinfu
+        let wide_cheap_tiles = tiles::tiles_from_string("123m567p99s34567s");
+        // 123m 456p 789s 222z 5p: a single wait on 5p (2 live tiles), but riichi and 3 dora (mangan)
+        let narrow_expensive_tiles = tiles::tiles_from_string("123m456p789s222z5p");
+
+        // early in the hand, riichi is worth it for both hands, but the wide wait wins much more often
+        hand_state.tiles_remaining = 60;
+        let wide_cheap_ev = riichi_ev(
+            &wide_cheap_tiles,
+            &vec![],
+            &hand_state,
+            &player_state,
+            &rules,
+        );
+        let narrow_expensive_ev = riichi_ev(
+            &narrow_expensive_tiles,
+            &vec![],
+            &hand_state,
+            &player_state,
+            &rules,
+        );
+        assert!(narrow_expensive_ev > 0.0);
+        assert!(wide_cheap_ev > narrow_expensive_ev);
+
+        // if the other copies of the wide wait's tiles are visible, the narrow wait is better
+        let visible_tiles = tiles::tiles_from_string("222555888s");
+        let wide_cheap_dead_ev = riichi_ev(
+            &wide_cheap_tiles,
+            &visible_tiles,
+            &hand_state,
+            &player_state,
+            &rules,
+        );
+        assert!(wide_cheap_dead_ev < narrow_expensive_ev);
+
+        // 11m 55m 22p 88p 33s 99s 4z: a seven pairs wait on 4z (3 live tiles) is also worth declaring riichi
+        let seven_pairs_tiles = tiles::tiles_from_string("1155m2288p3399s4z");
+        assert!(
+            riichi_ev(
+                &seven_pairs_tiles,
+                &vec![],
+                &hand_state,
+                &player_state,
+                &rules
+            ) > 0.0
+        );
+
+        // late in the hand, the riichi stick is likely lost
+        hand_state.tiles_remaining = 4;
+        assert!(
+            riichi_ev(
+                &wide_cheap_tiles,
+                &vec![],
+                &hand_state,
+                &player_state,
+                &rules
+            ) < 0.0
+        );
+        assert!(
+            riichi_ev(
+                &narrow_expensive_tiles,
+                &vec![],
+                &hand_state,
+                &player_state,
+                &rules
+            ) < 0.0
+        );
+
+        // no live waits
+        let visible_tiles = tiles::tiles_from_string("55p");
+        assert_eq!(
+            riichi_ev(
+                &narrow_expensive_tiles,
+                &visible_tiles,
+                &hand_state,
+                &player_state,
+                &rules
+            ),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_compute_score_stacks_yakuman() {
+        // 111m 999m 111p 999p 11s, winning on 1s (pair wait): suuankou (double yakuman) + chinroutou
+        assert_eq!(
+            compute_score_from_string(
+                "111999m111999p1s",
+                "1s",
+                &test_hand_state(),
+                &test_player_state(),
+                &ScoringRules::default()
+            ),
+            ScoreResult::Yakuman {
+                multiplier: 3,
+                yaku: vec![yaku::Yaku::Suuankou, yaku::Yaku::Chinroutou],
+            }
+        );
+
+        // 555z 666z 777z 234m 88p: a single yakuman
+        assert_eq!(
+            compute_score_from_string(
+                "234m8p555666777z",
+                "8p",
+                &test_hand_state(),
+                &test_player_state(),
+                &ScoringRules::default()
+            ),
+            ScoreResult::Yakuman {
+                multiplier: 1,
+                yaku: vec![yaku::Yaku::Daisangen],
+            }
+        );
+    }
+
+    #[test]
+    fn test_compute_score_counted_yakuman_stays_normal() {
+        // 234m 234m 567m 678m 88m, winning on 5m (ryanmen wait) by tsumo: riichi + menzen tsumo + tanyao + pinfu +
+        // iipeikou, and 9 dora (the 2m and 7m twice, and the 5m). 14 han is a counted yakuman, which is still a normal
+        // hand rather than a yakuman hand
+        let mut hand_state = test_hand_state();
+        hand_state.dora_indicators = tiles::tiles_from_string("11466m");
+        let mut player_state = test_player_state();
+        player_state.in_riichi = true;
+        player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+        let score_result = compute_score_from_string(
+            "2233446677888m",
+            "5m",
+            &hand_state,
+            &player_state,
+            &ScoringRules::default(),
+        );
+        assert_eq!(score_result, ScoreResult::Normal { han: 14, fu: 20 });
+        assert_eq!(score_result.limit_hand(), Some(LimitHand::KazoeYakuman));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_hand_score_to_json() {
+        // 123m 456p 789s 22s 67s, in riichi, winning on a red 5s by ron with the 4s dora indicator:
+        // riichi + pinfu + dora 1 + aka dora 1 = 4 han 30 fu = 7700
+        let player_tiles = tiles::tiles_from_string("123m456p789s2267s");
+        let winning_tile = tiles::Tile::from_string("0s");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &vec![])
+            .expect("Should be a winning hand");
+        let mut hand_state = test_hand_state();
+        hand_state.dora_indicators = tiles::tiles_from_string("4s");
+        let mut player_state = test_player_state();
+        player_state.in_riichi = true;
+        let hand_score = score_hand(
+            &player_tiles,
+            &winning_tile,
+            &groupings[0],
+            &hand_state,
+            &player_state,
+            &ScoringRules::default(),
+        );
+
+        let json = serde_json::to_string(&hand_score).unwrap();
+        for field in [
+            r#""winning_tile":"0s""#,
+            r#""han":4"#,
+            r#""fu":30"#,
+            r#""yaku":[["Riichi",1],["Pinfu",1],["Dora",1],["Aka Dora",1]]"#,
+            r#""dora":1"#,
+            r#""ura_dora":0"#,
+            r#""aka_dora":1"#,
+            r#""limit_name":null"#,
+            r#""ron":7700"#,
+            r#""tsumo_dealer_pays":3900"#,
+            r#""tsumo_others_pay":2000"#,
+        ] {
+            assert!(json.contains(field), "{} should contain {}", json, field);
+        }
+        assert_eq!(
+            serde_json::from_str::<HandScore>(&json).unwrap(),
+            hand_score
+        );
+
+        // a yakuman hand lists its yakuman
+        let yakuman_json = serde_json::to_string(&ScoreResult::Yakuman {
+            multiplier: 3,
+            yaku: vec![yaku::Yaku::Suuankou, yaku::Yaku::Chinroutou],
+        })
+        .unwrap();
+        assert_eq!(
+            yakuman_json,
+            r#"{"Yakuman":{"multiplier":3,"yaku":["Suuankou","Chinroutou"]}}"#
+        );
+    }
+
+    #[test]
+    fn test_compute_score_normal_hand() {
+        // 12345678m 234p 99p, winning on 9m by tsumo: menzen tsumo + pinfu + ittsu
+        let mut player_state = test_player_state();
+        player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+        assert!(matches!(
+            compute_score_from_string(
+                "12345678m23499p",
+                "9m",
+                &test_hand_state(),
+                &player_state,
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { han: 4, .. }
+        ));
+    }
+
+    #[test]
+    fn test_compute_base_points_limit_hands() {
+        // mangan, by han or by a smaller hand with high fu (e.g. 4 han 40 fu, 3 han 70 fu)
+        assert_eq!(compute_base_points(5, 30), 2000);
+        assert_eq!(compute_base_points(4, 40), 2000);
+        assert_eq!(compute_base_points(3, 70), 2000);
+        // haneman is 3000 base points, not 4000 (baiman)
+        assert_eq!(compute_base_points(6, 20), 3000);
+        assert_eq!(compute_base_points(7, 30), 3000);
+        assert_eq!(compute_base_points(8, 20), 4000);
+        assert_eq!(compute_base_points(10, 40), 4000);
+        assert_eq!(compute_base_points(11, 30), 6000);
+        assert_eq!(compute_base_points(13, 30), 8000);
+
+        // haneman ron
+        assert_eq!(
+            compute_ron_payment(compute_base_points(6, 20), false),
+            12000
+        );
+        assert_eq!(compute_ron_payment(compute_base_points(6, 20), true), 18000);
+        // baiman ron
+        assert_eq!(
+            compute_ron_payment(compute_base_points(8, 20), false),
+            16000
+        );
+        assert_eq!(compute_ron_payment(compute_base_points(8, 20), true), 24000);
+    }
+
+    #[test]
+    fn test_compute_base_points_below_mangan() {
+        // 1 han 30 fu: 240 base points, 1000 ron for a non-dealer, 1500 ron for the dealer
+        assert_eq!(compute_base_points(1, 30), 240);
+        assert_eq!(compute_ron_payment(240, false), 1000);
+        assert_eq!(compute_ron_payment(240, true), 1500);
+        // 3 han 30 fu: 960 base points, 3900 ron for a non-dealer, 5800 ron for the dealer
+        assert_eq!(compute_base_points(3, 30), 960);
+        assert_eq!(compute_ron_payment(960, false), 3900);
+        assert_eq!(compute_ron_payment(960, true), 5800);
+    }
+
+    #[test]
+    fn test_compute_score_ura_dora_only_with_riichi() {
+        let mut hand_state = test_hand_state();
+        hand_state.ura_dora_indicators = tiles::tiles_from_string("1m");
+        let mut player_state = test_player_state();
+        player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+
+        // 12345678m 234p 99p, winning on 9m by tsumo: menzen tsumo + pinfu + ittsu, and 2m is ura dora
+        assert!(matches!(
+            compute_score_from_string(
+                "12345678m23499p",
+                "9m",
+                &hand_state,
+                &player_state,
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { han: 4, .. }
+        ));
+        player_state.in_riichi = true;
+        assert!(matches!(
+            compute_score_from_string(
+                "12345678m23499p",
+                "9m",
+                &hand_state,
+                &player_state,
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { han: 6, .. }
+        ));
+    }
+
+    #[test]
+    fn test_compute_score_red_five() {
+        // 234m 340m 678p 345s 66s, winning on the red 5m: tanyao + pinfu + 1 han from the red five
+        assert!(matches!(
+            compute_score_from_string(
+                "23434m678p345s66s",
+                "0m",
+                &test_hand_state(),
+                &test_player_state(),
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { han: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_compute_fu_open_pinfu_shape() {
+        // 234m 567p 66p 78s + chi 345s, winning on 6s (open wait): tanyao (open), 30 fu by ron or by tsumo
+        for winning_tile_source in [
+            state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            },
+            state::WinningTileSource::SelfDraw {
+                is_first_go_around: false,
+            },
+        ] {
+            let mut player_state = test_player_state();
+            player_state.winning_tile_source = Some(winning_tile_source);
+            assert_eq!(
+                compute_score_from_string(
+                    "234m56766p78s + chi345s",
+                    "6s",
+                    &test_hand_state(),
+                    &player_state,
+                    &ScoringRules::default()
+                ),
+                ScoreResult::Normal { han: 1, fu: 30 }
+            );
+        }
+
+        // an open hand with other fu is scored normally: 20 + 8 (closed triplet of terminals) + 2 (tsumo) = 30 fu
+        let mut player_state = test_player_state();
+        player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+        assert_eq!(
+            compute_score_from_string(
+                "234m99956p66s + chi345s",
+                "7p",
+                &test_hand_state(),
+                &player_state,
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { han: 0, fu: 30 }
+        );
+    }
+
+    #[test]
+    fn test_compute_fu_maximum() {
+        // the dealer in the east round, so a 1z pair is a double wind pair (4 fu)
+        let mut player_state = test_player_state();
+        player_state.seat_wind = state::WindDirection::East;
+        let fu_from_string = |hand_string: &str, extra_group_string: Option<&str>| {
+            let (player_tiles, declared_groups) =
+                tiles::hand_and_melds_from_string(hand_string).unwrap();
+            let winning_tile = tiles::Tile::from_string("1z");
+            let mut winning_tiles = player_tiles.clone();
+            winning_tiles.push(winning_tile);
+            let mut grouping = tile_grouping::tile_grouping(&winning_tiles, &declared_groups)
+                .expect("Should be a winning hand")
+                .remove(0);
+            if let Some(extra_group_string) = extra_group_string {
+                let (_, extra_groups) =
+                    tiles::hand_and_melds_from_string(extra_group_string).unwrap();
+                grouping.extend(extra_groups);
+            }
+            try_compute_fu(
+                &player_tiles,
+                &winning_tile,
+                &grouping,
+                &test_hand_state(),
+                &player_state,
+            )
+        };
+
+        // 20 + 32 + 32 (closed kans of terminals) + 16 (open kan of terminals) + 4 (open triplet of terminals)
+        // + 4 (double wind pair) + 2 (tanki wait) = 110 fu
+        assert_eq!(
+            fu_from_string("1z + ankan1m + ankan9p + kan1s + pon9s", None),
+            Ok(110)
+        );
+        // 20 + 4 * 32 (closed kans of honors) + 4 (double wind pair) + 2 (tanki wait) + 10 (closed ron) = 164 -> 170 fu
+        assert_eq!(
+            fu_from_string("1z + ankan2z + ankan3z + ankan4z + ankan5z", None),
+            Ok(170)
+        );
+        // a typical hand is well under the maximum: 20 + 10 (closed ron) + 4 (honor triplet completed by ron) = 34 -> 40 fu
+        assert_eq!(fu_from_string("123m456p789s99s11z", None), Ok(40));
+        // a grouping with a fifth group isn't a winning hand
+        assert!(fu_from_string(
+            "1z + ankan2z + ankan3z + ankan4z + ankan5z",
+            Some("1m + ankan6z")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_compute_fu_seven_pairs() {
+        let (player_tiles, _) = tiles::hand_and_melds_from_string("1133m2288p4466s7z").unwrap();
+        let winning_tile = tiles::Tile::from_string("7z");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let grouping = tile_grouping::seven_pairs_tile_grouping(&winning_tiles, &Vec::new())
+            .expect("Should be a seven pairs hand");
+        assert_eq!(
+            compute_fu(
+                &player_tiles,
+                &winning_tile,
+                &grouping,
+                &test_hand_state(),
+                &test_player_state()
+            ),
+            25
+        );
+    }
+
+    #[test]
+    fn test_compute_fu_closed_pinfu() {
+        // 234m 234p 234s 56p 88m, winning on 7p (open wait): pinfu + tanyao + sanshoku doujun
+        let mut player_state = test_player_state();
+        // by ron: 20 + 10 (closed ron) = 30 fu
+        player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+            is_first_go_around: false,
+        });
+        assert_eq!(
+            compute_score_from_string(
+                "234m23456p234s88m",
+                "7p",
+                &test_hand_state(),
+                &player_state,
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { han: 4, fu: 30 }
+        );
+        // by tsumo: pinfu doesn't earn the 2 fu for tsumo, so it's always 20 fu (plus menzen tsumo)
+        player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+        assert_eq!(
+            compute_score_from_string(
+                "234m23456p234s88m",
+                "7p",
+                &test_hand_state(),
+                &player_state,
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { han: 5, fu: 20 }
+        );
+        // a closed wait (kanchan) on the same shape isn't pinfu, so tsumo earns 2 fu (and the wait earns 2 fu):
+        // 20 + 2 + 2 = 24 -> 30 fu, with menzen tsumo + tanyao + sanshoku doujun (no pinfu)
+        assert_eq!(
+            compute_score_from_string(
+                "234m23457p234s88m",
+                "6p",
+                &test_hand_state(),
+                &player_state,
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { han: 4, fu: 30 }
+        );
+    }
+
+    #[test]
+    fn test_classify_limit() {
+        assert_eq!(classify_limit(4, 30), None);
+        assert_eq!(classify_limit(4, 40), Some(LimitHand::Mangan));
+        assert_eq!(classify_limit(3, 60), None);
+        assert_eq!(classify_limit(3, 70), Some(LimitHand::Mangan));
+        assert_eq!(classify_limit(5, 30), Some(LimitHand::Mangan));
+        assert_eq!(classify_limit(7, 40), Some(LimitHand::Haneman));
+        assert_eq!(classify_limit(8, 30), Some(LimitHand::Baiman));
+        assert_eq!(classify_limit(12, 30), Some(LimitHand::Sanbaiman));
+        assert_eq!(classify_limit(14, 30), Some(LimitHand::KazoeYakuman));
+        assert_eq!(LimitHand::Mangan.name(), "mangan");
+
+        assert_eq!(
+            ScoreResult::Normal { han: 6, fu: 30 }.limit_hand(),
+            Some(LimitHand::Haneman)
+        );
+        assert_eq!(
+            ScoreResult::Yakuman {
+                multiplier: 1,
+                yaku: vec![yaku::Yaku::Daisangen]
+            }
+            .limit_hand(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_payments_with_honba_and_riichi_sticks() {
+        let mut hand_state = test_hand_state();
+
+        // dealer 2 han 30 fu ron (2900) with 3 honba: +900
+        hand_state.honba_sticks = 3;
+        assert_eq!(
+            compute_ron_total(compute_base_points(2, 30), true, &hand_state),
+            3800
+        );
+
+        // non-dealer 1 han 30 fu tsumo (500 from the dealer, 300 from each non-dealer) with 1 honba (+100 from each)
+        // and one riichi stick on the table (+1000)
+        hand_state.honba_sticks = 1;
+        hand_state.riichi_sticks = 1;
+        assert_eq!(
+            compute_tsumo_payments(compute_base_points(1, 30), false),
+            (500, 300)
+        );
+        assert_eq!(
+            compute_tsumo_total(compute_base_points(1, 30), false, &hand_state),
+            2400
+        );
+
+        // dealer mangan tsumo (4000 from each) with no sticks
+        hand_state.honba_sticks = 0;
+        hand_state.riichi_sticks = 0;
+        assert_eq!(
+            compute_tsumo_payments(compute_base_points(5, 30), true),
+            (0, 4000)
+        );
+        assert_eq!(
+            compute_tsumo_total(compute_base_points(5, 30), true, &hand_state),
+            12000
+        );
+    }
+
+    #[test]
+    fn test_base_points_table() {
+        for han in 1..=4 {
+            for fu in (20..=110).step_by(10) {
+                assert_eq!(
+                    compute_base_points(han, fu),
+                    (fu << (2 + han)).min(2000),
+                    "{} han {} fu",
+                    han,
+                    fu
+                );
+            }
+        }
+        // reference scores
+        assert_eq!(compute_ron_payment(compute_base_points(3, 40), false), 5200);
+        assert_eq!(compute_ron_payment(compute_base_points(3, 40), true), 7700);
+        assert_eq!(
+            compute_ron_payment(compute_base_points(1, 110), false),
+            3600
+        );
+        // seven pairs (25 fu) isn't in the table
+        assert_eq!(compute_ron_payment(compute_base_points(2, 25), false), 1600);
+        assert_eq!(compute_ron_payment(compute_base_points(4, 25), true), 9600);
+    }
+
+    #[test]
+    fn test_compute_nagashi_mangan_payments() {
+        let mut player_state = test_player_state();
+        player_state.winning_tile_source = None;
+        player_state.discards = tiles::tiles_from_string("19m9p1s1234567z");
+        assert_eq!(
+            compute_nagashi_mangan_payments(&player_state, false),
+            Some((4000, 2000))
+        );
+        assert_eq!(
+            compute_nagashi_mangan_payments(&player_state, true),
+            Some((0, 4000))
+        );
+
+        player_state.any_discards_called_by_others = true;
+        assert_eq!(compute_nagashi_mangan_payments(&player_state, false), None);
+    }
+
+    #[test]
+    fn test_aotenjou_base_points() {
+        let aotenjou_rules = ScoringRules {
+            aotenjou: true,
+            ..Default::default()
+        };
+        // 6 han 40 fu: haneman under the standard rules, but uncapped under aotenjou
+        assert_eq!(ScoringRules::default().base_points(6, 40), 3000);
+        assert_eq!(aotenjou_rules.base_points(6, 40), 40 * 256);
+        // the same as the standard rules below mangan, but a smaller hand over 2000 base points isn't capped
+        assert_eq!(aotenjou_rules.base_points(3, 30), 960);
+        assert_eq!(aotenjou_rules.base_points(3, 70), 70 * 32);
+        assert_eq!(aotenjou_rules.base_points(100, 30), u32::MAX);
+    }
+
+    #[test]
+    fn test_compute_score_renhou() {
+        // 123m 456p 789s 111z 99p, a non-dealer winning by ron before their first draw:
+        // yakuhai (round wind) is 1 han without renhou
+        let mut player_state = test_player_state();
+        player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+            is_first_go_around: true,
+        });
+        let renhou_score = |hand_state: &state::HandState,
+                            player_state: &state::PlayerState,
+                            renhou: RenhouValue| {
+            compute_score_from_string(
+                "123m456p99p78s111z",
+                "9s",
+                hand_state,
+                player_state,
+                &ScoringRules {
+                    renhou,
+                    ..Default::default()
+                },
+            )
+        };
+        assert!(matches!(
+            renhou_score(&test_hand_state(), &player_state, RenhouValue::Disabled),
+            ScoreResult::Normal { han: 1, .. }
+        ));
+        assert!(matches!(
+            renhou_score(&test_hand_state(), &player_state, RenhouValue::Mangan),
+            ScoreResult::Normal { han: 5, .. }
+        ));
+        assert_eq!(
+            renhou_score(&test_hand_state(), &player_state, RenhouValue::Yakuman),
+            ScoreResult::Yakuman {
+                multiplier: 1,
+                yaku: vec![yaku::Yaku::Renhou]
+            }
+        );
+
+        // not renhou after the first go-around, or after a call
+        let mut late_player_state = player_state.clone();
+        late_player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+            is_first_go_around: false,
+        });
+        assert!(matches!(
+            renhou_score(&test_hand_state(), &late_player_state, RenhouValue::Yakuman),
+            ScoreResult::Normal { han: 1, .. }
+        ));
+        let mut called_hand_state = test_hand_state();
+        called_hand_state.any_calls_made = true;
+        assert!(matches!(
+            renhou_score(&called_hand_state, &player_state, RenhouValue::Mangan),
+            ScoreResult::Normal { han: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_compute_score_daisharin() {
+        let local_yaku_rules = ScoringRules {
+            local_yaku: LocalYakuConfig {
+                daisharin: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // 2233445566778p + 8p: daisharin is only a yakuman with local yaku
+        assert_eq!(
+            compute_score_from_string(
+                "2233445566778p",
+                "8p",
+                &test_hand_state(),
+                &test_player_state(),
+                &local_yaku_rules
+            ),
+            ScoreResult::Yakuman {
+                multiplier: 1,
+                yaku: vec![yaku::Yaku::Daisharin]
+            }
+        );
+        assert!(matches!(
+            compute_score_from_string(
+                "2233445566778p",
+                "8p",
+                &test_hand_state(),
+                &test_player_state(),
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { .. }
+        ));
+
+        // 3344556677889p + 9p has two of each tile, but isn't 2-8
+        assert!(matches!(
+            compute_score_from_string(
+                "3344556677889p",
+                "9p",
+                &test_hand_state(),
+                &test_player_state(),
+                &local_yaku_rules
+            ),
+            ScoreResult::Normal { .. }
+        ));
+    }
+
+    #[test]
+    fn test_score_hand() {
+        let score =
+            |hand_string: &str, winning_tile_string: &str, player_state: &state::PlayerState| {
+                let (player_tiles, declared_groups) =
+                    tiles::hand_and_melds_from_string(hand_string).unwrap();
+                let winning_tile = tiles::Tile::from_string(winning_tile_string);
+                let mut winning_tiles = player_tiles.clone();
+                winning_tiles.push(winning_tile);
+                let groupings = tile_grouping::tile_grouping(&winning_tiles, &declared_groups)
+                    .expect("Should be a winning hand");
+                score_hand(
+                    &player_tiles,
+                    &winning_tile,
+                    &groupings[0],
+                    &test_hand_state(),
+                    player_state,
+                    &ScoringRules::default(),
+                )
+            };
+        let mut player_state = test_player_state();
+        player_state.in_riichi = true;
+
+        // non-dealer, riichi and pinfu by ron: 2 han 30 fu = 2000
+        assert_eq!(
+            score("123m456p789s2267s", "5s", &player_state),
+            HandScore {
+                winning_tile: String::from("5s"),
+                han: 2,
+                fu: 30,
+                yaku: vec![(String::from("Riichi"), 1), (String::from("Pinfu"), 1)],
+                dora: 0,
+                ura_dora: 0,
+                aka_dora: 0,
+                limit_name: None,
+                ron: 2000,
+                tsumo_dealer_pays: 1000,
+                tsumo_others_pay: 500
+            }
+        );
+        // the same hand by tsumo (riichi, pinfu, menzen tsumo): 3 han 20 fu = 700/1300
+        let mut tsumo_player_state = player_state.clone();
+        tsumo_player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+        let hand_score = score("123m456p789s2267s", "5s", &tsumo_player_state);
+        assert_eq!((hand_score.han, hand_score.fu), (3, 20));
+        assert_eq!(
+            (hand_score.tsumo_dealer_pays, hand_score.tsumo_others_pay),
+            (1300, 700)
+        );
+
+        // dealer, riichi and pinfu by ron: 2 han 30 fu = 2900, or 1000 all by tsumo
+        let mut dealer_player_state = player_state.clone();
+        dealer_player_state.seat_wind = state::WindDirection::East;
+        let hand_score = score("123m456p789s2267s", "5s", &dealer_player_state);
+        assert_eq!(hand_score.ron, 2900);
+        assert_eq!(
+            (hand_score.tsumo_dealer_pays, hand_score.tsumo_others_pay),
+            (0, 1000)
+        );
+
+        // non-dealer mangan: riichi, tanyao, pinfu, and 2 red fives (5 han) = 8000, or 2000/4000 by tsumo
+        let hand_score = score("234m406p678s2206s", "7s", &player_state);
+        assert_eq!(hand_score.limit_name, Some(String::from("mangan")));
+        assert_eq!(
+            (
+                hand_score.ron,
+                hand_score.tsumo_dealer_pays,
+                hand_score.tsumo_others_pay
+            ),
+            (8000, 4000, 2000)
+        );
+
+        // non-dealer daisangen = 32000, or 8000/16000 by tsumo
+        let hand_score = score("234m8p555666777z", "8p", &test_player_state());
+        assert_eq!(hand_score.limit_name, Some(String::from("yakuman")));
+        assert_eq!((hand_score.han, hand_score.fu), (13, 0));
+        assert_eq!(
+            (
+                hand_score.ron,
+                hand_score.tsumo_dealer_pays,
+                hand_score.tsumo_others_pay
+            ),
+            (32000, 16000, 8000)
+        );
+
+        // kokushi musou can't be grouped, so it's scored with an empty grouping
+        let kokushi_score = |hand_string: &str, winning_tile_string: &str| {
+            score_hand(
+                &tiles::tiles_from_string(hand_string),
+                &tiles::Tile::from_string(winning_tile_string),
+                &vec![],
+                &test_hand_state(),
+                &test_player_state(),
+                &ScoringRules::default(),
+            )
+        };
+        // waiting only on 7z (the pair is 11z) = 32000
+        let hand_score = kokushi_score("19m19p19s1123456z", "7z");
+        assert_eq!(hand_score.limit_name, Some(String::from("yakuman")));
+        assert_eq!((hand_score.han, hand_score.ron), (13, 32000));
+        // a 13-sided wait is a double yakuman = 64000, or 16000/32000 by tsumo
+        let hand_score = kokushi_score("19m19p19s1234567z", "9m");
+        assert_eq!(hand_score.limit_name, Some(String::from("2x yakuman")));
+        assert_eq!(
+            (
+                hand_score.ron,
+                hand_score.tsumo_dealer_pays,
+                hand_score.tsumo_others_pay
+            ),
+            (64000, 32000, 16000)
+        );
+    }
+
+    #[test]
+    fn test_compute_score_open_riichi() {
+        let mut player_state = test_player_state();
+        player_state.in_riichi = true;
+        player_state.in_open_riichi = true;
+        let open_riichi_rules = ScoringRules {
+            local_yaku: LocalYakuConfig {
+                open_riichi: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // 123m 456p 789s 22s 67s + 5s: riichi and pinfu
+        let score = |player_state: &state::PlayerState, rules: &ScoringRules| {
+            compute_score_from_string(
+                "123m456p789s2267s",
+                "5s",
+                &test_hand_state(),
+                player_state,
+                rules,
+            )
+        };
+        assert_eq!(
+            score(&player_state, &ScoringRules::default()),
+            ScoreResult::Normal { han: 2, fu: 30 }
+        );
+        // open riichi adds 1 han, and riichi is still only scored once
+        assert_eq!(
+            score(&player_state, &open_riichi_rules),
+            ScoreResult::Normal { han: 3, fu: 30 }
+        );
+        let (player_tiles, _) = tiles::hand_and_melds_from_string("123m456p789s2267s").unwrap();
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(tiles::Tile::from_string("5s"));
+        let grouping = &tile_grouping::tile_grouping(&winning_tiles, &vec![]).unwrap()[0];
+        let breakdown = compute_han_breakdown(
+            grouping,
+            &tiles::Tile::from_string("5s"),
+            &test_hand_state(),
+            &player_state,
+            &open_riichi_rules,
+        );
+        assert_eq!(
+            breakdown
+                .iter()
+                .filter(|(name, _)| name.contains("Riichi"))
+                .cloned()
+                .collect::<Vec<(String, u32)>>(),
+            vec![
+                (String::from("Riichi"), 1),
+                (String::from("Open Riichi"), 1)
+            ]
+        );
+
+        // a normal riichi doesn't score open riichi, and neither does an open riichi flag without riichi
+        let mut closed_riichi_state = player_state.clone();
+        closed_riichi_state.in_open_riichi = false;
+        assert_eq!(
+            score(&closed_riichi_state, &open_riichi_rules),
+            ScoreResult::Normal { han: 2, fu: 30 }
+        );
+        let mut no_riichi_state = player_state.clone();
+        no_riichi_state.in_riichi = false;
+        assert_eq!(
+            score(&no_riichi_state, &open_riichi_rules),
+            ScoreResult::Normal { han: 1, fu: 30 }
+        );
+    }
+
+    #[test]
+    fn test_compute_score_aotenjou_yakuman() {
+        // 555z 666z 777z 234m 88p: daisangen (13 han) + 3 han from yakuhai + sanankou (2 han) under aotenjou
+        assert!(matches!(
+            compute_score_from_string(
+                "234m8p555666777z",
+                "8p",
+                &test_hand_state(),
+                &test_player_state(),
+                &ScoringRules {
+                    aotenjou: true,
+                    ..Default::default()
+                }
+            ),
+            ScoreResult::Normal { han: 18, .. }
+        ));
+    }
+
+    #[test]
+    fn test_compute_yakuman_payments_pao() {
+        // a non-dealer (South) wins a single yakuman (daisangen) by tsumo
+        assert_eq!(
+            compute_yakuman_payments(1, state::WindDirection::South, None, None),
+            [16000, 0, 8000, 8000]
+        );
+        // West fed the third dragon group, so pays the entire tsumo
+        let pao = Some(PaoLiability {
+            liable_seat: state::WindDirection::West,
+        });
+        assert_eq!(
+            compute_yakuman_payments(1, state::WindDirection::South, None, pao),
+            [0, 0, 32000, 0]
+        );
+        // by ron from North, the payment is split between West and North
+        assert_eq!(
+            compute_yakuman_payments(
+                1,
+                state::WindDirection::South,
+                Some(state::WindDirection::North),
+                pao
+            ),
+            [0, 0, 16000, 16000]
+        );
+        // by ron from the liable player, who pays the entire payment
+        assert_eq!(
+            compute_yakuman_payments(
+                1,
+                state::WindDirection::South,
+                Some(state::WindDirection::West),
+                pao
+            ),
+            [0, 0, 32000, 0]
+        );
+        // the dealer wins by tsumo
+        assert_eq!(
+            compute_yakuman_payments(1, state::WindDirection::East, None, pao),
+            [0, 0, 48000, 0]
+        );
+        assert_eq!(
+            compute_yakuman_payments(1, state::WindDirection::East, None, None),
+            [0, 16000, 16000, 16000]
+        );
+    }
+
+    #[test]
+    fn test_kuitan() {
+        // 234m 567p 66p 78s + chi 345s, winning on 6s: open tanyao (and no dora)
+        let hand_state = test_hand_state();
+        let player_state = test_player_state();
+        assert!(matches!(
+            compute_score_from_string(
+                "234m56766p78s + chi345s",
+                "6s",
+                &hand_state,
+                &player_state,
+                &ScoringRules::default()
+            ),
+            ScoreResult::Normal { han: 1, .. }
+        ));
+        let no_kuitan_rules = ScoringRules {
+            kuitan: false,
+            ..Default::default()
+        };
+        assert!(matches!(
+            compute_score_from_string(
+                "234m56766p78s + chi345s",
+                "6s",
+                &hand_state,
+                &player_state,
+                &no_kuitan_rules
+            ),
+            ScoreResult::Normal { han: 0, .. }
+        ));
+        // closed tanyao is still scored: 234m 567p 66p 345s 678s, winning on 6s (tanyao + pinfu)
+        assert!(matches!(
+            compute_score_from_string(
+                "234m567p66p34578s",
+                "6s",
+                &hand_state,
+                &player_state,
+                &no_kuitan_rules
+            ),
+            ScoreResult::Normal { han: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_kiriage_mangan() {
+        let kiriage_rules = ScoringRules {
+            kiriage_mangan: true,
+            ..Default::default()
+        };
+        assert_eq!(ScoringRules::default().base_points(4, 30), 1920);
+        assert_eq!(kiriage_rules.base_points(4, 30), 2000);
+        assert_eq!(ScoringRules::default().base_points(3, 60), 1920);
+        assert_eq!(kiriage_rules.base_points(3, 60), 2000);
+        // other hands below mangan aren't rounded up
+        assert_eq!(kiriage_rules.base_points(3, 50), 1600);
+        assert_eq!(kiriage_rules.base_points(4, 25), 1600);
+    }
+
+    #[test]
+    fn test_compute_han_breakdown() {
+        // 2345666s 111z 777z (from the sample pro test question 1), winning on 1s by ron after riichi:
+        // riichi + yakuhai (round wind East and the red dragon) + 3 dora (6s)
+        let mut hand_state = test_hand_state();
+        hand_state.dora_indicators = tiles::tiles_from_string("5s");
+        let mut player_state = test_player_state();
+        player_state.in_riichi = true;
+
+        let (player_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("2345666s111777z").unwrap();
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(tiles::Tile::from_string("1s"));
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &declared_groups)
+            .expect("Should be a winning hand");
+        let breakdown = compute_han_breakdown(
+            &groupings[0],
+            &tiles::Tile::from_string("1s"),
+            &hand_state,
+            &player_state,
+            &ScoringRules::default(),
+        );
+        assert_eq!(
+            breakdown,
+            vec![
+                (String::from("Riichi"), 1),
+                (String::from("Yakuhai"), 2),
+                (String::from("Dora"), 3),
+            ]
+        );
+
+        let total_han: u32 = breakdown.iter().map(|(_, han)| han).sum();
+        assert_eq!(
+            compute_score(
+                &player_tiles,
+                &tiles::Tile::from_string("1s"),
+                &groupings[0],
+                &hand_state,
+                &player_state,
+                &ScoringRules::default(),
+            ),
+            ScoreResult::Normal {
+                han: total_han,
+                fu: 50
+            }
+        );
+
+        // 111m 222p 333s 45m 99p, winning on 6m by ron: sanankou (the ron completes the sequence, not a triplet)
+        let player_tiles = tiles::tiles_from_string("111m45m222p99p333s");
+        let winning_tile = tiles::Tile::from_string("6m");
+        let grouping =
+            &tile_grouping::decompose_winning_hand(&player_tiles, &vec![], &winning_tile)[0];
+        let breakdown = compute_han_breakdown(
+            grouping,
+            &winning_tile,
+            &test_hand_state(),
+            &test_player_state(),
+            &ScoringRules::default(),
+        );
+        assert_eq!(breakdown, vec![(String::from("Sanankou"), 2)]);
+        assert!(matches!(
+            compute_score(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &test_hand_state(),
+                &test_player_state(),
+                &ScoringRules::default(),
+            ),
+            ScoreResult::Normal { han: 2, .. }
+        ));
+    }
+}
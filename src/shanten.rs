@@ -0,0 +1,2570 @@
+use crate::{scoring, shanten_table, state, tile_grouping, tiles};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+// number of tiles of each tile kind in a standard riichi mahjong set
+const NUM_COPIES_PER_TILE_KIND: u8 = 4;
+
+/// Which hand shape(s) a tenpai hand is in tenpai for
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum TenpaiForm {
+    /// only tenpai for seven pairs (chiitoitsu), i.e. always a single-tile wait, scored as 25 fu
+    ChiitoiOnly,
+    /// only tenpai for the standard hand shape (4 groups and a pair)
+    StandardOnly,
+    /// tenpai for both seven pairs and the standard hand shape (e.g. 112233m445566p7s)
+    Both,
+}
+
+/// What a wait tile does for a tenpai hand when it's drawn
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum WaitCompletion {
+    /// the wait tile completes a group (triplet or sequence), e.g. 45 waiting on 3 or 6
+    Group,
+    /// the wait tile forms the pair, e.g. a single tile wait (tanki), or either end of 3456 (nobetan)
+    Pair,
+}
+
+/// A cache of shanten and ukiere results, keyed by the number of tiles of each tile kind (see `count_tiles_by_kind()`),
+/// for repeatedly evaluating related hands (e.g. every discard of a hand, then every draw after each discard).
+#[derive(Default)]
+pub struct ShantenCache {
+    shanten: HashMap<[u8; tiles::NUM_TILE_KINDS], i32>,
+    ukiere: HashMap<[u8; tiles::NUM_TILE_KINDS], Vec<tiles::Tile>>,
+}
+
+impl ShantenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The shanten number of the hand (see `get_shanten()`), computed only if it isn't already cached
+    pub fn get_shanten(&mut self, tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> i32 {
+        *self
+            .shanten
+            .entry(*tile_kind_counts)
+            .or_insert_with(|| shanten_from_counts(tile_kind_counts))
+    }
+
+    /// The ukiere of the 13-tile hand (see `get_ukiere()`), computed only if it isn't already cached
+    pub fn get_ukiere(
+        &mut self,
+        tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS],
+    ) -> Vec<tiles::Tile> {
+        if let Some(ukiere) = self.ukiere.get(tile_kind_counts) {
+            return ukiere.clone();
+        }
+        let shanten = self.get_shanten(tile_kind_counts);
+        let ukiere: Vec<tiles::Tile> = ukiere_kinds_from_counts(tile_kind_counts, shanten)
+            .into_iter()
+            .map(tiles::Tile::from_kind_index)
+            .collect();
+        self.ukiere.insert(*tile_kind_counts, ukiere.clone());
+        ukiere
+    }
+}
+
+/// The named shape of a tenpai hand's wait. A hand can have multiple wait shapes if its tiles can be read in different
+/// ways, e.g. 2345666s is both a nobetan (2345s, waiting on 2s or 5s) and a sanmenchan (23456s, waiting on 1s, 4s, or 7s).
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum WaitShape {
+    /// an open wait on either end of two consecutive tiles, e.g. 34 waiting on 2 or 5
+    Ryanmen,
+    /// a closed wait in the middle of two tiles, e.g. 35 waiting on 4
+    Kanchan,
+    /// an edge wait, i.e. 12 waiting on 3 or 89 waiting on 7
+    Penchan,
+    /// a single tile wait for the pair
+    Tanki,
+    /// a wait on either of two pairs to make a triplet
+    Shanpon,
+    /// a single tile wait on either end of four consecutive tiles, e.g. 3456 waiting on 3 or 6
+    Nobetan,
+    /// an open wait on three tiles from five consecutive tiles, e.g. 34567 waiting on 2, 5, or 8
+    Sanmenchan,
+    /// an open wait next to a pair, e.g. 3455 waiting on 2 or 5 (as 34-55, or as 345-5)
+    Aryanmen,
+    /// a triplet next to a single tile, e.g. 2223 waiting on 3 (as 222-3) or 1 and 4 (as 22-23)
+    Ryantan,
+    /// a triplet next to two consecutive tiles, with a separate pair, e.g. 44456 + 88 waiting on 4, 7, or 8
+    Entotsu,
+    /// a single tile wait on three tiles from seven consecutive tiles, e.g. 3456789 waiting on 3, 6, or 9
+    Sanmentan,
+}
+
+/// The evaluation of a discard from a 14-tile hand (see `ranked_discards()`)
+#[derive(Debug, Clone)]
+pub struct DiscardEval {
+    /// the tile in the hand to discard
+    pub tile: tiles::Tile,
+    /// the shanten of the hand after discarding the tile
+    pub shanten: i32,
+    /// the live ukiere tiles of the hand after discarding the tile (see `get_ukiere_with_counts()`)
+    pub ukiere_tiles: Vec<tiles::Tile>,
+    /// the total number of live copies of the ukiere tiles
+    pub acceptance: u32,
+}
+
+/// The comparison of two discards from the same 14-tile hand (see `compare_discards()`)
+#[derive(Debug, Clone)]
+pub struct DiscardComparison {
+    /// the evaluation of the first discard
+    pub a: DiscardEval,
+    /// the evaluation of the second discard
+    pub b: DiscardEval,
+    /// the live ukiere tiles after the first discard that aren't ukiere tiles after the second discard
+    pub only_a_ukiere: Vec<tiles::Tile>,
+    /// the live ukiere tiles after the second discard that aren't ukiere tiles after the first discard
+    pub only_b_ukiere: Vec<tiles::Tile>,
+    /// the live upgrade tiles after the first discard (see `compare_discards()`)
+    pub a_upgrade_tiles: Vec<tiles::Tile>,
+    /// the live upgrade tiles after the second discard (see `compare_discards()`)
+    pub b_upgrade_tiles: Vec<tiles::Tile>,
+    /// the upgrade tiles after the first discard that aren't upgrade tiles after the second discard
+    pub only_a_upgrades: Vec<tiles::Tile>,
+    /// the upgrade tiles after the second discard that aren't upgrade tiles after the first discard
+    pub only_b_upgrades: Vec<tiles::Tile>,
+}
+
+/// A yaku that the player is aiming for, which restricts which tiles are useful to draw (see `get_ukiere_for_yaku()`)
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum YakuTarget {
+    /// all simples: only simple tiles (2-8 of the numbered suits)
+    Tanyao,
+    /// half flush: only the tiles of the numbered suit and honor tiles
+    Honitsu(tiles::TileSuit),
+}
+
+impl YakuTarget {
+    /// Whether the tile can be in a hand with this yaku
+    pub fn allows_tile(&self, tile: &tiles::Tile) -> bool {
+        match self {
+            Self::Tanyao => tile.is_simple(),
+            Self::Honitsu(suit) => tile.suit() == *suit || tile.is_honor(),
+        }
+    }
+}
+
+/// Counts the number of tiles of each tile kind (see `tiles::Tile::kind_index()`).
+/// Red fives are counted as normal fives.
+pub fn count_tiles_by_kind(tiles: &Vec<tiles::Tile>) -> [u8; tiles::NUM_TILE_KINDS] {
+    let mut tile_kind_counts = [0u8; tiles::NUM_TILE_KINDS];
+    for tile in tiles.iter() {
+        tile_kind_counts[tile.kind_index()] += 1;
+    }
+    tile_kind_counts
+}
+
+/// Adds the tile to the counts of each tile kind (see `count_tiles_by_kind()`).
+/// Returns an Err (and leaves the counts unchanged) if there would be more than four copies of the tile kind.
+pub fn try_add_tile_to_counts(
+    tile_kind_counts: &mut [u8; tiles::NUM_TILE_KINDS],
+    tile: &tiles::Tile,
+) -> Result<(), &'static str> {
+    let count = &mut tile_kind_counts[tile.kind_index()];
+    if *count >= NUM_COPIES_PER_TILE_KIND {
+        return Err("Hand can't have more than four copies of a tile kind!");
+    }
+    *count += 1;
+    Ok(())
+}
+
+/// Removes the tile from the counts of each tile kind (see `count_tiles_by_kind()`).
+/// Returns an Err (and leaves the counts unchanged) if there are no copies of the tile kind to remove.
+pub fn try_remove_tile_from_counts(
+    tile_kind_counts: &mut [u8; tiles::NUM_TILE_KINDS],
+    tile: &tiles::Tile,
+) -> Result<(), &'static str> {
+    let count = &mut tile_kind_counts[tile.kind_index()];
+    if *count == 0 {
+        return Err("Hand must have a copy of the tile kind to remove!");
+    }
+    *count -= 1;
+    Ok(())
+}
+
+/// The total number of tiles in the counts of each tile kind
+pub fn total_tiles(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> u32 {
+    tile_kind_counts.iter().map(|count| *count as u32).sum()
+}
+
+/// The number of different tile kinds in the counts of each tile kind
+pub fn distinct_tiles(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> usize {
+    tile_kind_counts.iter().filter(|count| **count >= 1).count()
+}
+
+/// The number of live copies of the tile, i.e. 4 minus the copies in the hand (by tile kind, see `count_tiles_by_kind()`)
+/// and the copies in the other visible tiles (e.g. other players' discards and called groups, and dora indicators).
+pub fn live_count_of(
+    tile: &tiles::Tile,
+    hand_counts: &[u8; tiles::NUM_TILE_KINDS],
+    other_visible: &[tiles::Tile],
+) -> u32 {
+    let num_visible = other_visible
+        .iter()
+        .filter(|visible_tile| visible_tile.kind_index() == tile.kind_index())
+        .count() as u32;
+    (NUM_COPIES_PER_TILE_KIND as u32)
+        .saturating_sub(hand_counts[tile.kind_index()] as u32 + num_visible)
+}
+
+/// Counts the number of tiles of each tile kind in the hand from a tenhou.net hand analysis URL,
+/// e.g. "https://tenhou.net/2/?q=345m1156p4666778s", where the `q` parameter is the hand in MSPZ notation
+/// (see `tiles::tiles_from_string()`). Red fives (`0`) are counted as normal fives.
+pub fn counts_from_tenhou_url(url: &str) -> Result<[u8; tiles::NUM_TILE_KINDS], &'static str> {
+    let (_, query) = url.split_once('?').ok_or("URL has no query string!")?;
+    let hand_string = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("q="))
+        .ok_or("URL has no q parameter!")?;
+    let mut tile_kind_counts = [0; tiles::NUM_TILE_KINDS];
+    for tile in tiles::try_tiles_from_string(hand_string)?.iter() {
+        try_add_tile_to_counts(&mut tile_kind_counts, tile)?;
+    }
+    Ok(tile_kind_counts)
+}
+
+/// The tenhou.net hand analysis URL for the hand with the number of tiles of each tile kind
+/// (see `count_tiles_by_kind()`), with the tiles in MSPZ notation in order of suit (man, pin, sou, honors) and rank,
+/// e.g. "https://tenhou.net/2/?q=345m1156p4666778s"
+pub fn tenhou_url_from_counts(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> String {
+    let mut hand_string = String::new();
+    for ((start_index, end_index), suit_char) in
+        SUIT_KIND_RANGES.into_iter().zip(['m', 'p', 's', 'z'])
+    {
+        let rank_chars: String = (start_index..end_index)
+            .flat_map(|kind_index| {
+                let rank_char = char::from_digit((kind_index - start_index + 1) as u32, 10)
+                    .expect("Rank must be a single digit");
+                std::iter::repeat_n(rank_char, tile_kind_counts[kind_index] as usize)
+            })
+            .collect();
+        if !rank_chars.is_empty() {
+            hand_string.push_str(&rank_chars);
+            hand_string.push(suit_char);
+        }
+    }
+    format!("https://tenhou.net/2/?q={}", hand_string)
+}
+
+/// The shanten number of the hand: the minimum number of tiles that need to be exchanged to reach tenpai
+/// (i.e. 0 means the hand is in tenpai, and -1 means the hand is already a complete/winning hand).
+/// Considers the standard hand shape (4 groups and a pair), seven pairs (chiitoitsu), and thirteen orphans (kokushi musou).
+/// The hand must be 13 tiles (or 14 tiles, i.e. right after drawing a tile).
+pub fn get_shanten(tiles: &Vec<tiles::Tile>) -> i32 {
+    try_get_shanten(tiles).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Same as `get_shanten()`, but returns an Err instead of panicking if the hand isn't 13 or 14 tiles
+/// (e.g. an empty hand or an over-full hand), or has more than four copies of a tile kind.
+pub fn try_get_shanten(tiles: &Vec<tiles::Tile>) -> Result<i32, &'static str> {
+    Ok(shanten_from_counts(&try_count_hand_tiles(tiles)?))
+}
+
+/// The shanten number of the hand's concealed tiles, when the hand also has declared melds (i.e. called groups).
+/// Each declared meld is locked as a complete group, so the concealed tiles must be 13 (or 14) tiles minus 3 per meld.
+/// The tiles can also be all of the hand's tiles including the tiles of the declared melds, i.e. 13 (or 14) tiles
+/// plus 1 per quad (since a quad is a complete group with an extra tile).
+/// With any declared melds, only the standard hand shape (4 groups and a pair) is possible.
+pub fn get_shanten_with_melds(
+    tiles: &Vec<tiles::Tile>,
+    declared_groups: &Vec<tiles::TileGroup>,
+) -> i32 {
+    try_get_shanten_with_melds(tiles, declared_groups).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Same as `get_shanten_with_melds()`, but returns an Err instead of panicking if the hand has the wrong number of tiles
+/// for its declared melds, or the tiles include the declared melds but are missing some of the meld tiles.
+pub fn try_get_shanten_with_melds(
+    tiles: &Vec<tiles::Tile>,
+    declared_groups: &Vec<tiles::TileGroup>,
+) -> Result<i32, &'static str> {
+    let tile_kind_counts = try_concealed_tile_counts(tiles, declared_groups, &[13, 14])?;
+    Ok(shanten_from_counts(&tile_kind_counts))
+}
+
+/// The ukiere (see `get_ukiere()`) of the hand's concealed tiles, when the hand also has declared melds
+/// (see `get_shanten_with_melds()`). The hand must be 13 tiles (not counting the extra tile of each quad).
+pub fn get_ukiere_with_melds(
+    tiles: &Vec<tiles::Tile>,
+    declared_groups: &Vec<tiles::TileGroup>,
+) -> Vec<tiles::Tile> {
+    try_get_ukiere_with_melds(tiles, declared_groups).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Same as `get_ukiere_with_melds()`, but returns an Err instead of panicking if the hand has the wrong number of tiles
+/// for its declared melds (see `try_get_shanten_with_melds()`).
+pub fn try_get_ukiere_with_melds(
+    tiles: &Vec<tiles::Tile>,
+    declared_groups: &Vec<tiles::TileGroup>,
+) -> Result<Vec<tiles::Tile>, &'static str> {
+    let tile_kind_counts = try_concealed_tile_counts(tiles, declared_groups, &[13])?;
+    Ok(
+        ukiere_kinds_from_counts(&tile_kind_counts, shanten_from_counts(&tile_kind_counts))
+            .into_iter()
+            .map(tiles::Tile::from_kind_index)
+            .collect(),
+    )
+}
+
+/// Whether the 13-tile hand (with any declared melds, see `get_shanten_with_melds()`) is in tenpai,
+/// for the standard hand shape, seven pairs, or thirteen orphans. Any other number of tiles is never in tenpai.
+pub fn is_tenpai(tiles: &Vec<tiles::Tile>, declared_groups: &Vec<tiles::TileGroup>) -> bool {
+    is_valid_hand_size_with_melds(tiles, declared_groups, &[13])
+        && get_shanten_with_melds(tiles, declared_groups) == 0
+}
+
+/// Whether the 14-tile hand (with any declared melds, see `get_shanten_with_melds()`) is a complete hand shape (agari),
+/// i.e. the same as `is_winning_hand()` but for a hand that can have declared melds. Doesn't check for yaku.
+pub fn is_agari(tiles: &Vec<tiles::Tile>, declared_groups: &Vec<tiles::TileGroup>) -> bool {
+    is_valid_hand_size_with_melds(tiles, declared_groups, &[14])
+        && get_shanten_with_melds(tiles, declared_groups) == -1
+}
+
+/// The winning tiles (one tile per tile kind) of the 13-tile hand (with any declared melds, see
+/// `get_shanten_with_melds()`), i.e. the tiles that complete the hand. Returns an empty list if the hand isn't in tenpai.
+/// For a tenpai hand this is the same as the ukiere (see `get_ukiere()`), e.g. a tile kind that is already held
+/// in all four copies can't be a winning tile.
+pub fn get_waits(
+    tiles: &Vec<tiles::Tile>,
+    declared_groups: &Vec<tiles::TileGroup>,
+) -> Vec<tiles::Tile> {
+    let mut tile_kind_counts = concealed_tile_counts(tiles, declared_groups, &[13]);
+    let mut wait_tiles = Vec::new();
+    for kind_index in 0..tiles::NUM_TILE_KINDS {
+        if tile_kind_counts[kind_index] >= NUM_COPIES_PER_TILE_KIND {
+            continue;
+        }
+        tile_kind_counts[kind_index] += 1;
+        if shanten_from_counts(&tile_kind_counts) == -1 {
+            wait_tiles.push(tiles::Tile::from_kind_index(kind_index));
+        }
+        tile_kind_counts[kind_index] -= 1;
+    }
+    wait_tiles
+}
+
+/// Whether a tenpai hand with the waits (see `get_waits()`) is furiten, i.e. any of its wait tiles is in the player's
+/// own discards (red fives are the same as normal fives). A furiten hand can't win by ron, only by tsumo.
+pub fn is_furiten(wait_tiles: &Vec<tiles::Tile>, own_discards: &Vec<tiles::Tile>) -> bool {
+    wait_tiles.iter().any(|wait_tile| {
+        own_discards
+            .iter()
+            .any(|discard| discard.kind_index() == wait_tile.kind_index())
+    })
+}
+
+/// The wait shapes (see `WaitShape`) of the 13-tile tenpai hand, from each way to group the hand for tenpai
+/// (see `tile_grouping::tenpai_grouping()`). The readings that combine into a multi-sided wait (e.g. the two single tile
+/// waits of a nobetan) are only classified as the multi-sided wait. Returns an empty list if the hand isn't in tenpai
+/// (thirteen orphans isn't classified).
+pub fn classify_wait(tiles: &Vec<tiles::Tile>) -> Vec<WaitShape> {
+    assert_eq!(tiles.len(), 13, "Waits are only defined for a 13-tile hand");
+    let mut readings: Vec<(WaitShape, Vec<usize>)> = Vec::new();
+    for tile_groups in tile_grouping::tenpai_grouping(tiles, &vec![])
+        .unwrap_or_default()
+        .iter()
+    {
+        if let Some(reading) = basic_wait_shape(tile_groups) {
+            if !readings.contains(&reading) {
+                readings.push(reading);
+            }
+        }
+    }
+
+    let mut wait_shapes: Vec<WaitShape> = Vec::new();
+    let mut is_reading_used = vec![false; readings.len()];
+    let add_wait_shape = |wait_shape: WaitShape, wait_shapes: &mut Vec<WaitShape>| {
+        if !wait_shapes.contains(&wait_shape) {
+            wait_shapes.push(wait_shape);
+        }
+    };
+    for (compound_shape, first_shape, second_shape) in [
+        (WaitShape::Nobetan, WaitShape::Tanki, WaitShape::Tanki),
+        (
+            WaitShape::Sanmenchan,
+            WaitShape::Ryanmen,
+            WaitShape::Ryanmen,
+        ),
+        (WaitShape::Aryanmen, WaitShape::Tanki, WaitShape::Ryanmen),
+        (WaitShape::Ryantan, WaitShape::Tanki, WaitShape::Ryanmen),
+        (WaitShape::Entotsu, WaitShape::Shanpon, WaitShape::Ryanmen),
+    ] {
+        for first_index in 0..readings.len() {
+            for second_index in 0..readings.len() {
+                let (first_reading_shape, first_waits) = &readings[first_index];
+                let (second_reading_shape, second_waits) = &readings[second_index];
+                if first_index == second_index
+                    || (is_reading_used[first_index] && is_reading_used[second_index])
+                    || *first_reading_shape != first_shape
+                    || *second_reading_shape != second_shape
+                    || !is_compound_wait(compound_shape, first_waits, second_waits)
+                {
+                    continue;
+                }
+                // a sanmentan is three single tile waits, i.e. two overlapping nobetan
+                let third_index = (0..readings.len()).find(|third_index| {
+                    compound_shape == WaitShape::Nobetan
+                        && readings[*third_index].0 == WaitShape::Tanki
+                        && is_compound_wait(compound_shape, second_waits, &readings[*third_index].1)
+                });
+                if let Some(third_index) = third_index {
+                    is_reading_used[third_index] = true;
+                    add_wait_shape(WaitShape::Sanmentan, &mut wait_shapes);
+                } else {
+                    add_wait_shape(compound_shape, &mut wait_shapes);
+                }
+                is_reading_used[first_index] = true;
+                is_reading_used[second_index] = true;
+            }
+        }
+    }
+    for (reading_index, (wait_shape, _)) in readings.iter().enumerate() {
+        if !is_reading_used[reading_index] {
+            add_wait_shape(*wait_shape, &mut wait_shapes);
+        }
+    }
+    wait_shapes
+}
+
+/// Whether the 14-tile hand is a complete/winning hand shape (4 groups and a pair, seven pairs, or thirteen orphans).
+/// Doesn't check for yaku. Any other number of tiles (e.g. an empty hand) is never a winning hand.
+pub fn is_winning_hand(tiles: &Vec<tiles::Tile>) -> bool {
+    tiles.len() == 14 && try_get_shanten(tiles) == Ok(-1)
+}
+
+/// The shanten number of the hand, only considering the standard hand shape (4 groups and a pair)
+pub fn get_standard_shanten(tiles: &Vec<tiles::Tile>) -> i32 {
+    try_get_standard_shanten(tiles).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Same as `get_standard_shanten()`, but returns an Err instead of panicking if the hand isn't 13 or 14 tiles,
+/// or has more than four copies of a tile kind.
+pub fn try_get_standard_shanten(tiles: &Vec<tiles::Tile>) -> Result<i32, &'static str> {
+    Ok(standard_shanten_from_counts(&try_count_hand_tiles(tiles)?))
+}
+
+/// The shanten number of the hand, only considering the seven pairs (chiitoitsu) hand shape
+pub fn get_chiitoi_shanten(tiles: &Vec<tiles::Tile>) -> i32 {
+    let tile_kind_counts = try_count_hand_tiles(tiles).unwrap_or_else(|err| panic!("{}", err));
+    chiitoi_shanten_from_counts(&tile_kind_counts)
+}
+
+/// The shanten number of the hand, only considering the thirteen orphans (kokushi musou) hand shape
+pub fn get_kokushi_shanten(tiles: &Vec<tiles::Tile>) -> i32 {
+    let tile_kind_counts = try_count_hand_tiles(tiles).unwrap_or_else(|err| panic!("{}", err));
+    kokushi_shanten_from_counts(&tile_kind_counts)
+}
+
+/// Classifies the 13-tile hand by which hand shape(s) it's in tenpai for (seven pairs, the standard hand shape, or both).
+/// Returns None if the hand isn't in tenpai for either hand shape (thirteen orphans tenpai also returns None).
+pub fn get_tenpai_form(tiles: &Vec<tiles::Tile>) -> Option<TenpaiForm> {
+    assert_eq!(tiles.len(), 13, "Tenpai is only defined for a 13-tile hand");
+    let is_chiitoi_tenpai = get_chiitoi_shanten(tiles) == 0;
+    let is_standard_tenpai = get_standard_shanten(tiles) == 0;
+    match (is_chiitoi_tenpai, is_standard_tenpai) {
+        (true, true) => Some(TenpaiForm::Both),
+        (true, false) => Some(TenpaiForm::ChiitoiOnly),
+        (false, true) => Some(TenpaiForm::StandardOnly),
+        (false, false) => None,
+    }
+}
+
+/// The tiles (one tile per tile kind) that would reduce the shanten of the 13-tile hand if drawn (aka ukiere).
+/// Tile kinds that are already held in all four copies can't be drawn, so they are never included.
+/// For a thirteen orphans tenpai with no pair (all 13 different terminal/honor tiles), this is the 13-sided wait.
+pub fn get_ukiere(tiles: &Vec<tiles::Tile>) -> Vec<tiles::Tile> {
+    try_get_ukiere(tiles).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// The ukiere (see `get_ukiere()`) of the 13-tile hand that keep the hand on track for the yaku,
+/// e.g. only the simple tiles when aiming for tanyao
+pub fn get_ukiere_for_yaku(tiles: &Vec<tiles::Tile>, yaku_target: YakuTarget) -> Vec<tiles::Tile> {
+    get_ukiere(tiles)
+        .into_iter()
+        .filter(|tile| yaku_target.allows_tile(tile))
+        .collect()
+}
+
+/// Same as `get_ukiere()`, but returns an Err instead of panicking if the hand isn't 13 tiles
+/// (e.g. an empty hand or a hand right after drawing), or has more than four copies of a tile kind.
+pub fn try_get_ukiere(tiles: &Vec<tiles::Tile>) -> Result<Vec<tiles::Tile>, &'static str> {
+    if tiles.len() != 13 {
+        return Err("Ukiere is only defined for a 13-tile hand!");
+    }
+    let tile_kind_counts = try_count_tiles_by_kind(tiles)?;
+    Ok(
+        ukiere_kinds_from_counts(&tile_kind_counts, shanten_from_counts(&tile_kind_counts))
+            .into_iter()
+            .map(tiles::Tile::from_kind_index)
+            .collect(),
+    )
+}
+
+/// The ukiere tiles (see `get_ukiere()`) of the 13-tile hand that are still live, each with the number of its copies that
+/// are still live (i.e. not in the hand or in the visible tiles, e.g. other players' discards and called groups,
+/// and dora indicators). Ukiere tiles with all four copies visible can't be drawn, so they aren't included.
+pub fn get_ukiere_with_counts(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+) -> Vec<(tiles::Tile, u32)> {
+    assert_eq!(tiles.len(), 13, "Ukiere is only defined for a 13-tile hand");
+    let hand_counts = count_tiles_by_kind(tiles);
+    ukiere_kinds_from_counts(&hand_counts, shanten_from_counts(&hand_counts))
+        .into_iter()
+        .map(|kind_index| {
+            let tile = tiles::Tile::from_kind_index(kind_index);
+            (tile, live_count_of(&tile, &hand_counts, visible_tiles))
+        })
+        .filter(|(_, num_live)| *num_live > 0)
+        .collect()
+}
+
+/// The tiles (one tile per tile kind) with all four copies visible (kabe, i.e. a "wall"). A wait that can only be
+/// completed by these tiles is dead, e.g. if all four copies of 5p are visible, a 46p closed wait can't be completed.
+pub fn dead_waits_from_kabe(visible_tiles: &Vec<tiles::Tile>) -> Vec<tiles::Tile> {
+    let visible_counts = count_tiles_by_kind(visible_tiles);
+    (0..tiles::NUM_TILE_KINDS)
+        .filter(|kind_index| visible_counts[*kind_index] >= NUM_COPIES_PER_TILE_KIND)
+        .map(tiles::Tile::from_kind_index)
+        .collect()
+}
+
+/// The live ukiere tiles of the 13-tile hand, each with the number of its live copies (see `get_ukiere_with_counts()`),
+/// where incomplete groups that can only be completed by tiles with all four copies seen (in the hand or in the
+/// visible tiles, see `dead_waits_from_kabe()`) don't count towards the shanten. So a dead wait doesn't contribute
+/// any acceptance, and the tiles that would replace it are counted instead.
+pub fn get_ukiere_with_kabe(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+) -> Vec<(tiles::Tile, u32)> {
+    assert_eq!(tiles.len(), 13, "Ukiere is only defined for a 13-tile hand");
+    let mut counts = count_tiles_by_kind(tiles);
+    let mut seen_counts = counts;
+    for tile in visible_tiles.iter() {
+        seen_counts[tile.kind_index()] += 1;
+    }
+    let live_kinds = seen_counts.map(|count| count < NUM_COPIES_PER_TILE_KIND);
+    let kabe_shanten = |counts: &[u8; tiles::NUM_TILE_KINDS]| -> i32 {
+        standard_shanten_with_live_kinds(counts, &live_kinds)
+            .min(chiitoi_shanten_from_counts(counts))
+            .min(kokushi_shanten_from_counts(counts))
+    };
+
+    let shanten = kabe_shanten(&counts);
+    let mut ukiere_with_counts = Vec::new();
+    for kind_index in 0..tiles::NUM_TILE_KINDS {
+        if !live_kinds[kind_index] {
+            continue;
+        }
+        counts[kind_index] += 1;
+        if kabe_shanten(&counts) < shanten {
+            ukiere_with_counts.push((
+                tiles::Tile::from_kind_index(kind_index),
+                num_live_tiles(&[kind_index], &seen_counts),
+            ));
+        }
+        counts[kind_index] -= 1;
+    }
+    ukiere_with_counts
+}
+
+/// The acceptance of the 13-tile hand: its live ukiere tiles (see `get_ukiere_with_counts()`), and the total number of
+/// live copies of those tiles (i.e. how many of the unseen tiles would reduce the shanten if drawn)
+pub fn get_acceptance(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+) -> (Vec<tiles::Tile>, u32) {
+    let ukiere_with_counts = get_ukiere_with_counts(tiles, visible_tiles);
+    let num_live_ukiere = ukiere_with_counts
+        .iter()
+        .map(|(_, num_live)| *num_live)
+        .sum();
+    (
+        ukiere_with_counts
+            .into_iter()
+            .map(|(tile, _)| tile)
+            .collect(),
+        num_live_ukiere,
+    )
+}
+
+/// The two-step acceptance of the 13-tile hand: for each live ukiere tile (see `get_ukiere_with_counts()`), the number of
+/// live ukiere tiles after drawing it and making the best discard (see `get_ukiere_wait_widths()`), averaged over the
+/// ukiere tiles weighted by their number of live copies. e.g. for a 1-shanten hand, this is the average wait width
+/// of the tenpai hand it reaches. Returns 0 if the hand has no live ukiere tiles.
+pub fn get_two_step_acceptance(tiles: &Vec<tiles::Tile>, visible_tiles: &Vec<tiles::Tile>) -> f64 {
+    let ukiere_with_counts = get_ukiere_with_counts(tiles, visible_tiles);
+    let ukiere_wait_widths = get_ukiere_wait_widths(tiles, visible_tiles);
+    let mut total_num_live: u32 = 0;
+    let mut total_weighted_wait_width: u32 = 0;
+    for (ukiere_tile, num_live) in ukiere_with_counts.iter() {
+        let (_, wait_width) = ukiere_wait_widths
+            .iter()
+            .find(|(tile, _)| tile.kind_index() == ukiere_tile.kind_index())
+            .expect("Every ukiere tile has a wait width");
+        total_num_live += num_live;
+        total_weighted_wait_width += num_live * wait_width;
+    }
+    if total_num_live == 0 {
+        return 0.0;
+    }
+    total_weighted_wait_width as f64 / total_num_live as f64
+}
+
+/// For each ukiere tile of the 13-tile hand, the width of the hand's acceptance after drawing that tile and making the
+/// best discard (see `expected_draws_to_win` for how the discard is chosen), i.e. the number of live ukiere tiles.
+/// For a 1-shanten hand, this is the wait width (number of live wait tiles) of the tenpai that each ukiere tile leads to.
+/// Ukiere tiles with all four copies visible can't be drawn, so they aren't included.
+pub fn get_ukiere_wait_widths(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+) -> Vec<(tiles::Tile, u32)> {
+    assert_eq!(tiles.len(), 13, "Ukiere is only defined for a 13-tile hand");
+    let hand_counts = count_tiles_by_kind(tiles);
+    let mut seen_counts = hand_counts;
+    for tile in visible_tiles.iter() {
+        seen_counts[tile.kind_index()] += 1;
+    }
+
+    let mut ukiere_wait_widths = Vec::new();
+    for kind_index in ukiere_kinds_from_counts(&hand_counts, shanten_from_counts(&hand_counts)) {
+        if seen_counts[kind_index] >= NUM_COPIES_PER_TILE_KIND {
+            continue;
+        }
+        let mut next_hand_counts = hand_counts;
+        let mut next_seen_counts = seen_counts;
+        next_hand_counts[kind_index] += 1;
+        next_seen_counts[kind_index] += 1;
+        let (discard_kind_index, next_shanten) =
+            best_discard_from_counts(&next_hand_counts, &next_seen_counts);
+        next_hand_counts[discard_kind_index] -= 1;
+        let num_live_ukiere = num_live_tiles(
+            &ukiere_kinds_from_counts(&next_hand_counts, next_shanten),
+            &next_seen_counts,
+        );
+        ukiere_wait_widths.push((tiles::Tile::from_kind_index(kind_index), num_live_ukiere));
+    }
+    ukiere_wait_widths
+}
+
+/// The wait tiles of a hand that is locked after declaring riichi (i.e. can only discard the drawn tile).
+/// The 13-tile hand must be in tenpai, otherwise the riichi declaration was invalid and this returns an Err.
+pub fn get_riichi_wait_tiles(tiles: &Vec<tiles::Tile>) -> Result<Vec<tiles::Tile>, &'static str> {
+    if tiles.len() != 13 {
+        return Err("Riichi hand must have 13 tiles!");
+    }
+    if get_shanten(tiles) != 0 {
+        return Err("Riichi hand must be in tenpai!");
+    }
+    Ok(get_ukiere(tiles))
+}
+
+/// The wait tiles of the 13-tile tenpai hand, each tagged with whether it completes a group or forms the pair.
+/// If the wait tile can complete a group in any of the winning hand groupings, it's tagged as completing a group.
+/// Winning with seven pairs always forms a pair. Returns an empty Vec if the hand isn't in tenpai.
+pub fn get_wait_completions(tiles: &Vec<tiles::Tile>) -> Vec<(tiles::Tile, WaitCompletion)> {
+    if get_shanten(tiles) != 0 {
+        return Vec::new();
+    }
+    let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+    get_ukiere(tiles)
+        .into_iter()
+        .map(|wait_tile| {
+            let mut winning_tiles = tiles.clone();
+            winning_tiles.push(wait_tile);
+            let groupings =
+                tile_grouping::tile_grouping(&winning_tiles, &empty_groups).unwrap_or_default();
+            let completes_group = groupings.iter().any(|grouping| {
+                grouping.iter().any(|tile_group| match tile_group {
+                    tiles::TileGroup::Triplet { tiles, .. } => {
+                        tiles[0].kind_index() == wait_tile.kind_index()
+                    }
+                    tiles::TileGroup::Sequence { tiles, .. } => tiles
+                        .iter()
+                        .any(|tile| tile.kind_index() == wait_tile.kind_index()),
+                    _ => false,
+                })
+            });
+            if completes_group {
+                (wait_tile, WaitCompletion::Group)
+            } else {
+                (wait_tile, WaitCompletion::Pair)
+            }
+        })
+        .collect()
+}
+
+/// Estimates the expected number of draws for the 13-tile hand to reach a winning hand, assuming that:
+/// - every unseen tile (i.e. not in the hand and not in `visible_tiles`) is equally likely to be the next draw,
+///   and there's no limit on the number of draws (no other players, no exhaustive draw)
+/// - after each draw, the discard is chosen to minimize shanten, then to maximize the number of live ukiere tiles
+///
+/// If there are U live ukiere tiles out of W unseen tiles, the expected number of draws until the first ukiere tile
+/// is (W + 1) / (U + 1), i.e. the expected position of the first ukiere tile in a random ordering of the unseen tiles.
+/// The estimate adds this up over each shanten step, weighting each ukiere tile by how many live copies are left.
+/// Returns infinity if the hand can't make progress (no live ukiere tiles).
+pub fn expected_draws_to_win(tiles: &Vec<tiles::Tile>, visible_tiles: &Vec<tiles::Tile>) -> f64 {
+    assert_eq!(
+        tiles.len(),
+        13,
+        "Expected draws to win is only defined for a 13-tile hand"
+    );
+    let hand_counts = count_tiles_by_kind(tiles);
+    let mut seen_counts = hand_counts;
+    for tile in visible_tiles.iter() {
+        seen_counts[tile.kind_index()] += 1;
+    }
+    for seen_count in seen_counts.iter() {
+        assert!(
+            *seen_count <= NUM_COPIES_PER_TILE_KIND,
+            "Only four copies of each tile kind"
+        );
+    }
+    expected_draws_from_counts(
+        &hand_counts,
+        &seen_counts,
+        shanten_from_counts(&hand_counts),
+    )
+}
+
+/// Evaluates every discard (one per tile kind in the hand) from the 14-tile hand, sorted from best to worst for a player
+/// who prioritizes speed: by shanten after the discard (lowest first), then by acceptance (most live ukiere first).
+/// Discards that are tied are in order of tile kind.
+pub fn ranked_discards(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+) -> Vec<DiscardEval> {
+    let (discard_evals, _) = ranked_discards_with_budget(tiles, visible_tiles, usize::MAX);
+    discard_evals
+}
+
+/// Same as `ranked_discards()`, but stops searching for ukiere after the budget of shanten evaluations runs out,
+/// for interactive use on slow hands. The shanten after each discard is always computed (it's cheap), then the ukiere
+/// of each discard are computed from the lowest shanten discards first, so the best discards are evaluated first.
+/// The discards that weren't evaluated within the budget have no ukiere (and 0 acceptance).
+/// Returns the discard evaluations, and whether the search completed within the budget (if so, the evaluations are
+/// the same as `ranked_discards()`).
+pub fn ranked_discards_with_budget(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+    max_shanten_evaluations: usize,
+) -> (Vec<DiscardEval>, bool) {
+    assert_eq!(
+        tiles.len(),
+        14,
+        "Discarding is only defined for a 14-tile hand"
+    );
+    let mut counts = count_tiles_by_kind(tiles);
+    let mut seen_counts = counts;
+    for tile in visible_tiles.iter() {
+        seen_counts[tile.kind_index()] += 1;
+    }
+    let mut discard_shantens: Vec<(usize, i32)> = Vec::new();
+    for kind_index in 0..tiles::NUM_TILE_KINDS {
+        if counts[kind_index] == 0 {
+            continue;
+        }
+        counts[kind_index] -= 1;
+        discard_shantens.push((kind_index, shanten_from_counts(&counts)));
+        counts[kind_index] += 1;
+    }
+    // (the sort is stable, so discards with the same shanten stay in order of tile kind)
+    discard_shantens.sort_by_key(|(_, shanten)| *shanten);
+
+    let mut num_shanten_evaluations: usize = 0;
+    let mut completed = true;
+    let mut discard_evals = Vec::new();
+    for (kind_index, shanten) in discard_shantens {
+        counts[kind_index] -= 1;
+        // finding the ukiere evaluates the shanten after drawing each tile kind that isn't already 4 copies in the hand
+        let num_draw_evaluations = counts
+            .iter()
+            .filter(|count| **count < NUM_COPIES_PER_TILE_KIND)
+            .count();
+        let live_ukiere_kinds: Vec<usize> = if completed
+            && num_shanten_evaluations.saturating_add(num_draw_evaluations)
+                <= max_shanten_evaluations
+        {
+            num_shanten_evaluations += num_draw_evaluations;
+            ukiere_kinds_from_counts(&counts, shanten)
+                .into_iter()
+                .filter(|ukiere_kind| seen_counts[*ukiere_kind] < NUM_COPIES_PER_TILE_KIND)
+                .collect()
+        } else {
+            completed = false;
+            Vec::new()
+        };
+        counts[kind_index] += 1;
+        discard_evals.push(DiscardEval {
+            tile: *tiles
+                .iter()
+                .find(|tile| tile.kind_index() == kind_index)
+                .expect("Discarded tile kind must be in the hand"),
+            shanten,
+            acceptance: num_live_tiles(&live_ukiere_kinds, &seen_counts),
+            ukiere_tiles: live_ukiere_kinds
+                .into_iter()
+                .map(tiles::Tile::from_kind_index)
+                .collect(),
+        });
+    }
+    discard_evals
+        .sort_by_key(|discard_eval| (discard_eval.shanten, Reverse(discard_eval.acceptance)));
+    (discard_evals, completed)
+}
+
+/// Compares two discards from the 14-tile hand (by tile kind, see `count_tiles_by_kind()`): the shanten and acceptance
+/// after each discard (see `ranked_discards()`), and the ukiere tiles that only one of the discards accepts. To compare
+/// two discards with the same acceptance but different shapes, the upgrade tiles after each discard are the draws that
+/// don't reduce the shanten, but allow a discard that keeps the same shanten with more acceptance. The copies in the
+/// hand (including the discarded tile) and in the other visible tiles aren't live.
+pub fn compare_discards(
+    tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS],
+    a: &tiles::Tile,
+    b: &tiles::Tile,
+    other_visible: &[tiles::Tile],
+) -> DiscardComparison {
+    try_compare_discards(tile_kind_counts, a, b, other_visible)
+        .unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Same as `compare_discards()`, but returns an Err instead of panicking if the hand isn't 14 tiles,
+/// or either discard isn't in the hand.
+pub fn try_compare_discards(
+    tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS],
+    a: &tiles::Tile,
+    b: &tiles::Tile,
+    other_visible: &[tiles::Tile],
+) -> Result<DiscardComparison, &'static str> {
+    if total_tiles(tile_kind_counts) != 14 {
+        return Err("Discarding is only defined for a 14-tile hand!");
+    }
+    let a_eval = discard_eval_from_counts(tile_kind_counts, a, other_visible)?;
+    let b_eval = discard_eval_from_counts(tile_kind_counts, b, other_visible)?;
+    let a_upgrade_tiles = upgrade_tiles_from_counts(tile_kind_counts, &a_eval, other_visible);
+    let b_upgrade_tiles = upgrade_tiles_from_counts(tile_kind_counts, &b_eval, other_visible);
+    let tile_difference = |tiles: &[tiles::Tile], other_tiles: &[tiles::Tile]| {
+        tiles
+            .iter()
+            .filter(|tile| {
+                !other_tiles
+                    .iter()
+                    .any(|other_tile| other_tile.kind_index() == tile.kind_index())
+            })
+            .copied()
+            .collect()
+    };
+    Ok(DiscardComparison {
+        only_a_ukiere: tile_difference(&a_eval.ukiere_tiles, &b_eval.ukiere_tiles),
+        only_b_ukiere: tile_difference(&b_eval.ukiere_tiles, &a_eval.ukiere_tiles),
+        only_a_upgrades: tile_difference(&a_upgrade_tiles, &b_upgrade_tiles),
+        only_b_upgrades: tile_difference(&b_upgrade_tiles, &a_upgrade_tiles),
+        a: a_eval,
+        b: b_eval,
+        a_upgrade_tiles,
+        b_upgrade_tiles,
+    })
+}
+
+/// The live tiles that aren't ukiere tiles after the discard, but after drawing them, some other discard keeps the
+/// same shanten with more live ukiere tiles (the discarded tile and the drawn tile aren't live).
+fn upgrade_tiles_from_counts(
+    hand_counts: &[u8; tiles::NUM_TILE_KINDS],
+    discard_eval: &DiscardEval,
+    other_visible: &[tiles::Tile],
+) -> Vec<tiles::Tile> {
+    let mut counts = *hand_counts;
+    counts[discard_eval.tile.kind_index()] -= 1;
+    let mut visible_tiles = other_visible.to_vec();
+    visible_tiles.push(discard_eval.tile);
+    (0..tiles::NUM_TILE_KINDS)
+        .map(tiles::Tile::from_kind_index)
+        .filter(|draw_tile| {
+            live_count_of(draw_tile, hand_counts, other_visible) > 0
+                && !discard_eval
+                    .ukiere_tiles
+                    .iter()
+                    .any(|ukiere_tile| ukiere_tile.kind_index() == draw_tile.kind_index())
+        })
+        .filter(|draw_tile| {
+            let mut drawn_counts = counts;
+            drawn_counts[draw_tile.kind_index()] += 1;
+            (0..tiles::NUM_TILE_KINDS)
+                .filter(|kind_index| drawn_counts[*kind_index] > 0)
+                .any(|kind_index| {
+                    let mut upgraded_counts = drawn_counts;
+                    upgraded_counts[kind_index] -= 1;
+                    if shanten_from_counts(&upgraded_counts) != discard_eval.shanten {
+                        return false;
+                    }
+                    let acceptance: u32 =
+                        ukiere_kinds_from_counts(&upgraded_counts, discard_eval.shanten)
+                            .into_iter()
+                            .map(|ukiere_kind_index| {
+                                live_count_of(
+                                    &tiles::Tile::from_kind_index(ukiere_kind_index),
+                                    &drawn_counts,
+                                    &visible_tiles,
+                                )
+                            })
+                            .sum();
+                    acceptance > discard_eval.acceptance
+                })
+        })
+        .collect()
+}
+
+fn discard_eval_from_counts(
+    hand_counts: &[u8; tiles::NUM_TILE_KINDS],
+    discard_tile: &tiles::Tile,
+    other_visible: &[tiles::Tile],
+) -> Result<DiscardEval, &'static str> {
+    let mut counts = *hand_counts;
+    try_remove_tile_from_counts(&mut counts, discard_tile)?;
+    let shanten = shanten_from_counts(&counts);
+    let live_ukiere: Vec<(tiles::Tile, u32)> = ukiere_kinds_from_counts(&counts, shanten)
+        .into_iter()
+        .map(|kind_index| {
+            let tile = tiles::Tile::from_kind_index(kind_index);
+            (tile, live_count_of(&tile, hand_counts, other_visible))
+        })
+        .filter(|(_, num_live)| *num_live > 0)
+        .collect();
+    Ok(DiscardEval {
+        tile: *discard_tile,
+        shanten,
+        acceptance: live_ukiere.iter().map(|(_, num_live)| num_live).sum(),
+        ukiere_tiles: live_ukiere.into_iter().map(|(tile, _)| tile).collect(),
+    })
+}
+
+/// Recommends a discard from the 14-tile hand for a player who prioritizes speed: first minimizes shanten,
+/// then maximizes the number of live ukiere tiles (not counting the copies in the hand or the visible tiles).
+/// Returns the tile in the hand to discard.
+pub fn get_best_discard_by_ukiere(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+) -> tiles::Tile {
+    assert_eq!(
+        tiles.len(),
+        14,
+        "Discarding is only defined for a 14-tile hand"
+    );
+    let hand_counts = count_tiles_by_kind(tiles);
+    let mut seen_counts = hand_counts;
+    for tile in visible_tiles.iter() {
+        seen_counts[tile.kind_index()] += 1;
+    }
+    let (discard_kind_index, _) = best_discard_from_counts(&hand_counts, &seen_counts);
+    *tiles
+        .iter()
+        .find(|tile| tile.kind_index() == discard_kind_index)
+        .expect("Discarded tile kind must be in the hand")
+}
+
+/// Recommends a discard from the 14-tile hand for a player who prioritizes hand value over speed: first minimizes shanten,
+/// then maximizes the expected score of the waits (see `scoring::best_value_discards()`, under the default rules).
+/// Discards that don't reach tenpai don't have a wait value yet, so ties are broken by the number of live ukiere tiles.
+pub fn get_best_discard_by_value(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> tiles::Tile {
+    assert_eq!(
+        tiles.len(),
+        14,
+        "Discarding is only defined for a 14-tile hand"
+    );
+    let (best_discard, _) = *scoring::best_value_discards(
+        tiles,
+        visible_tiles,
+        hand_state,
+        player_state,
+        &scoring::ScoringRules::default(),
+    )
+    .first()
+    .expect("Hand must have a tile to discard");
+    best_discard
+}
+
+/// Counts the tiles of the 13- or 14-tile hand (see `count_tiles_by_kind()`), or returns an Err if the hand has
+/// a different number of tiles or more than four copies of a tile kind
+fn try_count_hand_tiles(
+    tiles: &Vec<tiles::Tile>,
+) -> Result<[u8; tiles::NUM_TILE_KINDS], &'static str> {
+    if tiles.len() != 13 && tiles.len() != 14 {
+        return Err("Hand must have 13 or 14 tiles!");
+    }
+    try_count_tiles_by_kind(tiles)
+}
+
+/// Counts the concealed tiles of a hand with declared melds (see `get_shanten_with_melds()`), where the hand (not counting
+/// the extra tile of each quad) must have one of the valid hand sizes. Panics if the number of tiles isn't valid.
+fn concealed_tile_counts(
+    tiles: &[tiles::Tile],
+    declared_groups: &[tiles::TileGroup],
+    valid_hand_sizes: &[usize],
+) -> [u8; tiles::NUM_TILE_KINDS] {
+    try_concealed_tile_counts(tiles, declared_groups, valid_hand_sizes).unwrap_or_else(|err| {
+        panic!(
+            "{} ({} tiles, with {} declared melds, {} of them quads)",
+            err,
+            tiles.len(),
+            declared_groups.len(),
+            num_quads(declared_groups)
+        )
+    })
+}
+
+/// Same as `concealed_tile_counts()`, but returns an Err instead of panicking if the number of tiles isn't valid,
+/// or the tiles include the declared melds but are missing some of the meld tiles
+fn try_concealed_tile_counts(
+    tiles: &[tiles::Tile],
+    declared_groups: &[tiles::TileGroup],
+    valid_hand_sizes: &[usize],
+) -> Result<[u8; tiles::NUM_TILE_KINDS], &'static str> {
+    if !is_valid_hand_size_with_melds(tiles, declared_groups, valid_hand_sizes) {
+        return Err("Wrong number of tiles in hand for its declared melds!");
+    }
+    let mut tile_kind_counts = try_count_tiles_by_kind(&tiles.to_vec())?;
+    if valid_hand_sizes.contains(&(tiles.len() + 3 * declared_groups.len())) {
+        return Ok(tile_kind_counts);
+    }
+    // the tiles include the declared melds, which are removed to leave only the concealed tiles
+    for tile_group in declared_groups.iter() {
+        for tile in tile_group.tiles() {
+            try_remove_tile_from_counts(&mut tile_kind_counts, &tile)
+                .map_err(|_| "Declared meld tiles must be in the hand!")?;
+        }
+    }
+    Ok(tile_kind_counts)
+}
+
+/// Whether the hand with declared melds has one of the valid hand sizes (not counting the extra tile of each quad),
+/// where the tiles are either only the concealed tiles, or include the tiles of the declared melds
+fn is_valid_hand_size_with_melds(
+    tiles: &[tiles::Tile],
+    declared_groups: &[tiles::TileGroup],
+    valid_hand_sizes: &[usize],
+) -> bool {
+    let num_quads = num_quads(declared_groups);
+    declared_groups.len() <= 4
+        && (valid_hand_sizes.contains(&(tiles.len() + 3 * declared_groups.len()))
+            || (tiles.len() >= num_quads && valid_hand_sizes.contains(&(tiles.len() - num_quads))))
+}
+
+fn num_quads(declared_groups: &[tiles::TileGroup]) -> usize {
+    declared_groups
+        .iter()
+        .filter(|tile_group| matches!(tile_group, tiles::TileGroup::Quad { .. }))
+        .count()
+}
+
+/// The basic wait shape of a tenpai grouping (from its incomplete groups), and its wait tile kinds in order
+fn basic_wait_shape(tile_groups: &[tiles::TileGroup]) -> Option<(WaitShape, Vec<usize>)> {
+    let mut wait_kinds: Vec<usize> =
+        tile_grouping::tenpai_wait_tiles_from_grouping(&tile_groups.to_vec())
+            .iter()
+            .map(|tile| tile.kind_index())
+            .collect();
+    wait_kinds.sort();
+    wait_kinds.dedup();
+    if tile_grouping::number_pair_groups(&tile_groups.to_vec()) == 2 {
+        return Some((WaitShape::Shanpon, wait_kinds));
+    }
+    let single_tile_groups = tile_grouping::number_single_tile_groups(&tile_groups.to_vec());
+    if single_tile_groups > 1 {
+        // thirteen orphans
+        return None;
+    }
+    let wait_shape = tile_groups.iter().find_map(|tile_group| match tile_group {
+        tiles::TileGroup::SingleTile { .. } => Some(WaitShape::Tanki),
+        tiles::TileGroup::OpenWait { .. } => Some(WaitShape::Ryanmen),
+        tiles::TileGroup::ClosedWait { .. } => Some(WaitShape::Kanchan),
+        tiles::TileGroup::EdgeWait { .. } => Some(WaitShape::Penchan),
+        _ => None,
+    })?;
+    Some((wait_shape, wait_kinds))
+}
+
+/// Whether the waits of two readings of a tenpai hand combine into the multi-sided wait shape
+/// (see `WaitShape` for the multi-sided wait shapes)
+fn is_compound_wait(
+    compound_shape: WaitShape,
+    first_waits: &[usize],
+    second_waits: &[usize],
+) -> bool {
+    // the offset from the first wait tile kind to the second, if both are numbered tiles in the same suit
+    let same_suit_offset = |first_kind: usize, second_kind: usize| -> Option<i32> {
+        if first_kind < 3 * 9 && second_kind < 3 * 9 && first_kind / 9 == second_kind / 9 {
+            Some(second_kind as i32 - first_kind as i32)
+        } else {
+            None
+        }
+    };
+    match (compound_shape, first_waits, second_waits) {
+        (WaitShape::Nobetan, [first_kind], [second_kind]) => {
+            same_suit_offset(*first_kind, *second_kind) == Some(3)
+        }
+        (WaitShape::Sanmenchan, [first_low, first_high], [second_low, second_high]) => {
+            first_high == second_low
+                && same_suit_offset(*first_low, *first_high) == Some(3)
+                && same_suit_offset(*second_low, *second_high) == Some(3)
+        }
+        (WaitShape::Aryanmen, [tanki_kind], ryanmen_waits) => ryanmen_waits.contains(tanki_kind),
+        (WaitShape::Ryantan, [tanki_kind], [ryanmen_low, ryanmen_high]) => {
+            same_suit_offset(*ryanmen_low, *ryanmen_high) == Some(3)
+                && matches!(same_suit_offset(*ryanmen_low, *tanki_kind), Some(1..=2))
+        }
+        (WaitShape::Entotsu, shanpon_waits, ryanmen_waits) => ryanmen_waits
+            .iter()
+            .any(|ryanmen_kind| shanpon_waits.contains(ryanmen_kind)),
+        _ => false,
+    }
+}
+
+fn try_count_tiles_by_kind(
+    tiles: &Vec<tiles::Tile>,
+) -> Result<[u8; tiles::NUM_TILE_KINDS], &'static str> {
+    let mut tile_kind_counts = [0u8; tiles::NUM_TILE_KINDS];
+    for tile in tiles.iter() {
+        try_add_tile_to_counts(&mut tile_kind_counts, tile)?;
+    }
+    Ok(tile_kind_counts)
+}
+
+fn shanten_from_counts(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> i32 {
+    let num_tiles = total_tiles(tile_kind_counts);
+    if num_tiles < 13 {
+        // the hand has declared melds, so it can only be the standard hand shape
+        return standard_shanten_from_counts(tile_kind_counts);
+    }
+    standard_shanten_from_counts(tile_kind_counts)
+        .min(chiitoi_shanten_from_counts(tile_kind_counts))
+        .min(kokushi_shanten_from_counts(tile_kind_counts))
+}
+
+pub(crate) fn chiitoi_shanten_from_counts(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> i32 {
+    let num_pairs = tile_kind_counts.iter().filter(|count| **count >= 2).count() as i32;
+    let num_kinds = distinct_tiles(tile_kind_counts) as i32;
+    // the seven pairs must all be different tile kinds
+    6 - num_pairs + (7 - num_kinds).max(0)
+}
+
+pub(crate) fn kokushi_shanten_from_counts(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> i32 {
+    let mut num_kinds = 0;
+    let mut has_pair = false;
+    for (kind_index, count) in tile_kind_counts.iter().enumerate() {
+        let is_terminal_or_honor =
+            kind_index >= 3 * 9 || kind_index % 9 == 0 || kind_index % 9 == 8;
+        if is_terminal_or_honor && *count >= 1 {
+            num_kinds += 1;
+            if *count >= 2 {
+                has_pair = true;
+            }
+        }
+    }
+    13 - num_kinds - if has_pair { 1 } else { 0 }
+}
+
+// the range of tile kinds (see `tiles::Tile::kind_index()`) of each numbered suit, and of the honor tiles
+const SUIT_KIND_RANGES: [(usize, usize); 4] =
+    [(0, 9), (9, 18), (18, 27), (27, tiles::NUM_TILE_KINDS)];
+
+fn standard_shanten_from_counts(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> i32 {
+    standard_shanten_with_live_kinds(tile_kind_counts, &[true; tiles::NUM_TILE_KINDS])
+}
+
+/// The standard shanten (see `standard_shanten_from_counts()`), where an incomplete group (a pair, or a two-sided/closed/edge
+/// wait) is only counted if at least one of the tile kinds that would complete it is live
+fn standard_shanten_with_live_kinds(
+    tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS],
+    live_kinds: &[bool; tiles::NUM_TILE_KINDS],
+) -> i32 {
+    let num_tiles = total_tiles(tile_kind_counts);
+    let num_groups = (num_tiles / 3) as usize;
+    let mut counts = *tile_kind_counts;
+
+    // groups never span suits, so each suit (and the honor tiles) is searched separately, then the best scores
+    // of the suits are combined
+    let mut hand_scores = shanten_table::empty_scores();
+    for (start_index, end_index) in SUIT_KIND_RANGES {
+        let suit_scores = suit_group_scores(&mut counts, live_kinds, start_index, end_index);
+        hand_scores = shanten_table::merge_scores(&hand_scores, &suit_scores);
+    }
+    // each complete group is worth 2, each incomplete group (that still fits in the hand) is worth 1, and the pair is worth 1
+    let best_score = (hand_scores[num_groups][0] as i32).max(hand_scores[num_groups][1] as i32 + 1);
+    2 * num_groups as i32 - best_score
+}
+
+/// The best scores of the ways to split the tiles of one suit (between `start_index` and `end_index`) into groups,
+/// for each maximum number of groups, with or without the pair (see `shanten_table::GroupScores`)
+fn suit_group_scores(
+    counts: &mut [u8; tiles::NUM_TILE_KINDS],
+    live_kinds: &[bool; tiles::NUM_TILE_KINDS],
+    start_index: usize,
+    end_index: usize,
+) -> shanten_table::GroupScores {
+    let mut suit_scores = shanten_table::empty_scores();
+    for (num_groups, max_groups_scores) in suit_scores.iter_mut().enumerate() {
+        let num_groups = num_groups as i32;
+        max_groups_scores[0] =
+            max_group_score(counts, live_kinds, start_index, end_index, num_groups) as i8;
+        for kind_index in start_index..end_index {
+            if counts[kind_index] >= 2 {
+                counts[kind_index] -= 2;
+                let score =
+                    max_group_score(counts, live_kinds, start_index, end_index, num_groups) as i8;
+                counts[kind_index] += 2;
+                max_groups_scores[1] = max_groups_scores[1].max(score);
+            }
+        }
+    }
+    suit_scores
+}
+
+fn max_group_score(
+    counts: &mut [u8; tiles::NUM_TILE_KINDS],
+    live_kinds: &[bool; tiles::NUM_TILE_KINDS],
+    start_index: usize,
+    end_index: usize,
+    num_groups: i32,
+) -> i32 {
+    let mut best_score = 0;
+    search_groups(
+        counts,
+        live_kinds,
+        start_index,
+        end_index,
+        0,
+        0,
+        num_groups,
+        &mut best_score,
+    );
+    best_score
+}
+
+/// Searches over the ways to split the remaining tiles (from `kind_index` up to `end_index`) into complete groups
+/// (triplets and sequences), incomplete groups (pairs and two-sided/closed/edge waits), and isolated tiles.
+/// Incomplete groups can only be completed by the live tile kinds.
+#[allow(clippy::too_many_arguments)]
+fn search_groups(
+    counts: &mut [u8; tiles::NUM_TILE_KINDS],
+    live_kinds: &[bool; tiles::NUM_TILE_KINDS],
+    kind_index: usize,
+    end_index: usize,
+    num_complete: i32,
+    num_incomplete: i32,
+    num_groups: i32,
+    best_score: &mut i32,
+) {
+    let mut kind_index = kind_index;
+    while kind_index < end_index && counts[kind_index] == 0 {
+        kind_index += 1;
+    }
+    if kind_index >= end_index {
+        let num_incomplete = num_incomplete.min(num_groups - num_complete);
+        *best_score = (*best_score).max(2 * num_complete + num_incomplete);
+        return;
+    }
+
+    // stop early if the remaining tiles can't beat the best split found so far
+    // (each remaining group slot is worth at most 2, and every point needs at least 1.5 tiles)
+    let num_remaining_tiles: i32 = counts[kind_index..end_index]
+        .iter()
+        .map(|count| *count as i32)
+        .sum();
+    let max_remaining_score =
+        (2 * (num_groups - num_complete - num_incomplete)).min(2 * num_remaining_tiles / 3);
+    if 2 * num_complete + num_incomplete + max_remaining_score <= *best_score {
+        return;
+    }
+
+    // sequences can't wrap around between suits, and honor tiles can't form sequences
+    let is_number_kind = kind_index < 3 * 9;
+    let has_next = is_number_kind && kind_index % 9 <= 7 && counts[kind_index + 1] > 0;
+    let has_next_next = is_number_kind && kind_index % 9 <= 6 && counts[kind_index + 2] > 0;
+    // whether each incomplete group starting at this tile kind can be completed by a live tile kind
+    let is_pair_live = live_kinds[kind_index];
+    let is_adjacent_live = has_next
+        && ((kind_index % 9 >= 1 && live_kinds[kind_index - 1])
+            || (kind_index % 9 <= 6 && live_kinds[kind_index + 2]));
+    let is_gap_live = has_next_next && live_kinds[kind_index + 1];
+
+    if num_complete < num_groups {
+        if counts[kind_index] >= 3 {
+            counts[kind_index] -= 3;
+            search_groups(
+                counts,
+                live_kinds,
+                kind_index,
+                end_index,
+                num_complete + 1,
+                num_incomplete,
+                num_groups,
+                best_score,
+            );
+            counts[kind_index] += 3;
+        }
+        if has_next && has_next_next {
+            counts[kind_index] -= 1;
+            counts[kind_index + 1] -= 1;
+            counts[kind_index + 2] -= 1;
+            search_groups(
+                counts,
+                live_kinds,
+                kind_index,
+                end_index,
+                num_complete + 1,
+                num_incomplete,
+                num_groups,
+                best_score,
+            );
+            counts[kind_index] += 1;
+            counts[kind_index + 1] += 1;
+            counts[kind_index + 2] += 1;
+        }
+    }
+
+    if num_complete + num_incomplete < num_groups {
+        if counts[kind_index] >= 2 && is_pair_live {
+            counts[kind_index] -= 2;
+            search_groups(
+                counts,
+                live_kinds,
+                kind_index,
+                end_index,
+                num_complete,
+                num_incomplete + 1,
+                num_groups,
+                best_score,
+            );
+            counts[kind_index] += 2;
+        }
+        if is_adjacent_live {
+            counts[kind_index] -= 1;
+            counts[kind_index + 1] -= 1;
+            search_groups(
+                counts,
+                live_kinds,
+                kind_index,
+                end_index,
+                num_complete,
+                num_incomplete + 1,
+                num_groups,
+                best_score,
+            );
+            counts[kind_index] += 1;
+            counts[kind_index + 1] += 1;
+        }
+        if is_gap_live {
+            counts[kind_index] -= 1;
+            counts[kind_index + 2] -= 1;
+            search_groups(
+                counts,
+                live_kinds,
+                kind_index,
+                end_index,
+                num_complete,
+                num_incomplete + 1,
+                num_groups,
+                best_score,
+            );
+            counts[kind_index] += 1;
+            counts[kind_index + 2] += 1;
+        }
+    }
+
+    // leave one copy of this tile isolated (not part of any group)
+    counts[kind_index] -= 1;
+    search_groups(
+        counts,
+        live_kinds,
+        kind_index,
+        end_index,
+        num_complete,
+        num_incomplete,
+        num_groups,
+        best_score,
+    );
+    counts[kind_index] += 1;
+}
+
+fn ukiere_kinds_from_counts(hand_counts: &[u8; tiles::NUM_TILE_KINDS], shanten: i32) -> Vec<usize> {
+    let mut counts = *hand_counts;
+    let mut ukiere_kinds = Vec::new();
+    for kind_index in 0..tiles::NUM_TILE_KINDS {
+        if counts[kind_index] >= NUM_COPIES_PER_TILE_KIND {
+            continue;
+        }
+        counts[kind_index] += 1;
+        if shanten_from_counts(&counts) < shanten {
+            ukiere_kinds.push(kind_index);
+        }
+        counts[kind_index] -= 1;
+    }
+    ukiere_kinds
+}
+
+fn num_live_tiles(kinds: &[usize], seen_counts: &[u8; tiles::NUM_TILE_KINDS]) -> u32 {
+    kinds
+        .iter()
+        .map(|kind_index| (NUM_COPIES_PER_TILE_KIND - seen_counts[*kind_index]) as u32)
+        .sum()
+}
+
+/// Chooses the discard from the 14-tile hand that minimizes shanten, then maximizes the number of live ukiere tiles.
+/// Returns the tile kind to discard and the shanten after discarding.
+fn best_discard_from_counts(
+    hand_counts: &[u8; tiles::NUM_TILE_KINDS],
+    seen_counts: &[u8; tiles::NUM_TILE_KINDS],
+) -> (usize, i32) {
+    let mut counts = *hand_counts;
+    let mut discard_shantens: Vec<(usize, i32)> = Vec::new();
+    for kind_index in 0..tiles::NUM_TILE_KINDS {
+        if counts[kind_index] == 0 {
+            continue;
+        }
+        counts[kind_index] -= 1;
+        discard_shantens.push((kind_index, shanten_from_counts(&counts)));
+        counts[kind_index] += 1;
+    }
+    let min_shanten = discard_shantens
+        .iter()
+        .map(|(_, shanten)| *shanten)
+        .min()
+        .expect("Hand must have at least one tile to discard");
+
+    let mut best_discard: Option<(usize, u32)> = None;
+    for (kind_index, shanten) in discard_shantens.into_iter() {
+        if shanten != min_shanten {
+            continue;
+        }
+        counts[kind_index] -= 1;
+        let num_live_ukiere =
+            num_live_tiles(&ukiere_kinds_from_counts(&counts, shanten), seen_counts);
+        counts[kind_index] += 1;
+        if best_discard.is_none_or(|(_, best_num_live)| num_live_ukiere > best_num_live) {
+            best_discard = Some((kind_index, num_live_ukiere));
+        }
+    }
+    (best_discard.unwrap().0, min_shanten)
+}
+
+fn expected_draws_from_counts(
+    hand_counts: &[u8; tiles::NUM_TILE_KINDS],
+    seen_counts: &[u8; tiles::NUM_TILE_KINDS],
+    shanten: i32,
+) -> f64 {
+    if shanten < 0 {
+        return 0.0;
+    }
+    let ukiere_kinds = ukiere_kinds_from_counts(hand_counts, shanten);
+    let num_live_ukiere = num_live_tiles(&ukiere_kinds, seen_counts);
+    if num_live_ukiere == 0 {
+        return f64::INFINITY;
+    }
+    let num_unseen: u32 = seen_counts
+        .iter()
+        .map(|count| (NUM_COPIES_PER_TILE_KIND - count) as u32)
+        .sum();
+    let expected_draws_to_ukiere = (num_unseen + 1) as f64 / (num_live_ukiere + 1) as f64;
+    if shanten == 0 {
+        return expected_draws_to_ukiere;
+    }
+
+    let mut expected_draws_after_ukiere = 0.0;
+    for kind_index in ukiere_kinds.into_iter() {
+        let num_live = NUM_COPIES_PER_TILE_KIND - seen_counts[kind_index];
+        if num_live == 0 {
+            continue;
+        }
+        let mut next_hand_counts = *hand_counts;
+        let mut next_seen_counts = *seen_counts;
+        next_hand_counts[kind_index] += 1;
+        next_seen_counts[kind_index] += 1;
+        // the discarded tile is still seen (it's in the discard pile)
+        let (discard_kind_index, next_shanten) =
+            best_discard_from_counts(&next_hand_counts, &next_seen_counts);
+        next_hand_counts[discard_kind_index] -= 1;
+        expected_draws_after_ukiere += (num_live as f64 / num_live_ukiere as f64)
+            * expected_draws_from_counts(&next_hand_counts, &next_seen_counts, next_shanten);
+    }
+    expected_draws_to_ukiere + expected_draws_after_ukiere
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    fn tile_strings(tiles: &Vec<tiles::Tile>) -> Vec<String> {
+        tiles.iter().map(|tile| tile.to_string()).collect()
+    }
+
+    #[test]
+    fn test_degenerate_hands() {
+        // hand sizes other than 13 or 14 tiles
+        for hand_string in ["", "1m", "1234m", "123456789m123p", "123456789m123456p"] {
+            let tiles = tiles::try_tiles_from_string(hand_string).unwrap();
+            assert!(try_get_shanten(&tiles).is_err());
+            assert!(try_get_ukiere(&tiles).is_err());
+            assert!(!is_winning_hand(&tiles));
+        }
+        // ukiere isn't defined for a 14-tile hand
+        let tiles = tiles::tiles_from_string("123456789m12344p");
+        assert_eq!(try_get_shanten(&tiles), Ok(-1));
+        assert!(try_get_ukiere(&tiles).is_err());
+        assert!(is_winning_hand(&tiles));
+
+        // five copies of a tile kind
+        let tiles = tiles::tiles_from_string("11111m23456789p");
+        assert!(try_get_shanten(&tiles).is_err());
+        assert!(try_get_ukiere(&tiles).is_err());
+
+        let tiles = tiles::tiles_from_string("123456789m1234p");
+        assert_eq!(try_get_shanten(&tiles), Ok(0));
+        assert_eq!(
+            tile_strings(&try_get_ukiere(&tiles).unwrap()),
+            vec!["1p", "4p"]
+        );
+        assert!(!is_winning_hand(&tiles));
+    }
+
+    #[test]
+    fn test_shanten_complete_and_tenpai() {
+        // complete hand: 123m 456p 789s 111z 55z
+        let tiles = tiles::tiles_from_string("123m456p789s11155z");
+        assert_eq!(get_shanten(&tiles), -1);
+
+        // tenpai (waiting on 4s or 7s): 123m 456p 56s 111z 55z
+        let tiles = tiles::tiles_from_string("123m456p56s11155z");
+        assert_eq!(get_shanten(&tiles), 0);
+        assert_eq!(tile_strings(&get_ukiere(&tiles)), vec!["4s", "7s"]);
+    }
+
+    #[test]
+    fn test_shanten_standard() {
+        // 123m 456p 34s 78s 55z 9m: two complete groups, two incomplete groups, a pair -> 1-shanten
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        assert_eq!(get_standard_shanten(&tiles), 1);
+        assert_eq!(get_shanten(&tiles), 1);
+        assert_eq!(
+            tile_strings(&get_ukiere(&tiles)),
+            vec!["2s", "5s", "6s", "9s"]
+        );
+
+        // no groups at all: 147m 258p 369s 1234z -> 8-shanten for the standard shape
+        let tiles = tiles::tiles_from_string("147m258p369s1234z");
+        assert_eq!(get_standard_shanten(&tiles), 8);
+    }
+
+    #[test]
+    fn test_shanten_chiitoi_and_kokushi() {
+        // six pairs and a single tile: tenpai for seven pairs
+        let tiles = tiles::tiles_from_string("1199m2288p3377s1z");
+        assert_eq!(get_chiitoi_shanten(&tiles), 0);
+        assert_eq!(get_shanten(&tiles), 0);
+
+        // twelve different terminal/honor tiles and a pair: tenpai for thirteen orphans (waiting on 7z)
+        let tiles = tiles::tiles_from_string("19m19p19s1123456z");
+        assert_eq!(get_kokushi_shanten(&tiles), 0);
+        assert_eq!(get_shanten(&tiles), 0);
+        assert_eq!(tile_strings(&get_ukiere(&tiles)), vec!["7z"]);
+    }
+
+    #[test]
+    fn test_shanten_with_melds() {
+        // 234m 567p 123s 1z + pon 999s: waiting on 1z (tanki)
+        let (tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p123s1z + pon999s").unwrap();
+        assert_eq!(tiles.len(), 10);
+        assert_eq!(get_shanten_with_melds(&tiles, &declared_groups), 0);
+        assert_eq!(
+            tile_strings(&get_ukiere_with_melds(&tiles, &declared_groups)),
+            vec!["1z"]
+        );
+
+        // 234m 56p 123s 1z 7z + pon 999s: 1-shanten
+        let (tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m56p123s17z + pon999s").unwrap();
+        assert_eq!(get_shanten_with_melds(&tiles, &declared_groups), 1);
+
+        // 123p 123p 4z + two pons: waiting on 4z (the pairs 11p 22p 33p don't count towards seven pairs)
+        let (tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("112233p4z + pon999s + pon777z").unwrap();
+        assert_eq!(get_shanten_with_melds(&tiles, &declared_groups), 0);
+        assert_eq!(
+            tile_strings(&get_ukiere_with_melds(&tiles, &declared_groups)),
+            vec!["4z"]
+        );
+
+        // the complete hand after winning on 1z
+        let (tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p123s11z + pon999s").unwrap();
+        assert_eq!(get_shanten_with_melds(&tiles, &declared_groups), -1);
+    }
+
+    #[test]
+    fn test_shanten_with_quads() {
+        // 234m 567p 11s 55z + closed kan 2222z: 14 tiles (13 + the extra tile of the quad), waiting on 1s or 5z
+        let (concealed_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p11s55z + ankan2z").unwrap();
+        let mut tiles = concealed_tiles.clone();
+        for tile_group in declared_groups.iter() {
+            tiles.extend(tile_group.tiles());
+        }
+        assert_eq!(tiles.len(), 14);
+        assert_eq!(get_shanten_with_melds(&tiles, &declared_groups), 0);
+        assert_eq!(
+            tile_strings(&get_ukiere_with_melds(&tiles, &declared_groups)),
+            vec!["1s", "5z"]
+        );
+        // the same as only passing the concealed tiles
+        assert_eq!(
+            get_shanten_with_melds(&concealed_tiles, &declared_groups),
+            0
+        );
+
+        // after drawing a tile: 15 tiles
+        tiles.push(tiles::Tile::from_string("9p"));
+        assert_eq!(get_shanten_with_melds(&tiles, &declared_groups), 0);
+        tiles.pop();
+        tiles.push(tiles::Tile::from_string("5z"));
+        assert_eq!(get_shanten_with_melds(&tiles, &declared_groups), -1);
+    }
+
+    #[test]
+    fn test_shanten_cache() {
+        let mut cache = ShantenCache::new();
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z9p");
+        // every discard from the 14-tile hand, twice (the second time is cached)
+        for _ in 0..2 {
+            for discard_index in 0..tiles.len() {
+                let mut hand_tiles = tiles.clone();
+                hand_tiles.remove(discard_index);
+                let tile_kind_counts = count_tiles_by_kind(&hand_tiles);
+                assert_eq!(
+                    cache.get_shanten(&tile_kind_counts),
+                    get_shanten(&hand_tiles)
+                );
+                assert_eq!(
+                    tile_strings(&cache.get_ukiere(&tile_kind_counts)),
+                    tile_strings(&get_ukiere(&hand_tiles))
+                );
+            }
+        }
+        // each hand is only computed once (i.e. once per tile kind discarded)
+        assert_eq!(cache.shanten.len(), 13);
+        assert_eq!(cache.ukiere.len(), 13);
+    }
+
+    #[test]
+    fn test_is_tenpai_and_is_agari() {
+        // thirteen orphans
+        let tiles = tiles::tiles_from_string("19m19p19s11234567z");
+        assert!(is_agari(&tiles, &vec![]));
+        assert!(!is_tenpai(&tiles, &vec![]));
+
+        // seven pairs tenpai (waiting on 7p)
+        let tiles = tiles::tiles_from_string("1166m4499s667p55z");
+        assert!(is_tenpai(&tiles, &vec![]));
+        assert!(!is_agari(&tiles, &vec![]));
+
+        // 1-shanten
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        assert!(!is_tenpai(&tiles, &vec![]));
+        assert!(!is_agari(&tiles, &vec![]));
+
+        // with a declared meld
+        let (tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p123s1z + pon999s").unwrap();
+        assert!(is_tenpai(&tiles, &declared_groups));
+        assert!(!is_agari(&tiles, &declared_groups));
+        // the number of tiles doesn't match the declared melds
+        assert!(!is_tenpai(&tiles, &vec![]));
+    }
+
+    #[test]
+    fn test_get_waits() {
+        // nobetan: 3456p waiting on 3p or 6p
+        let tiles = tiles::tiles_from_string("123456m789s3456p");
+        assert_eq!(tile_strings(&get_waits(&tiles, &vec![])), vec!["3p", "6p"]);
+
+        // sanmenchan: 34567s waiting on 2s, 5s, or 8s
+        let tiles = tiles::tiles_from_string("123456m34567s11z");
+        assert_eq!(
+            tile_strings(&get_waits(&tiles, &vec![])),
+            vec!["2s", "5s", "8s"]
+        );
+
+        // thirteen orphans 13-sided wait
+        let tiles = tiles::tiles_from_string("19m19p19s1234567z");
+        assert_eq!(
+            tile_strings(&get_waits(&tiles, &vec![])),
+            vec!["1m", "9m", "1p", "9p", "1s", "9s", "1z", "2z", "3z", "4z", "5z", "6z", "7z"]
+        );
+
+        // with a declared meld: 234m 567p 123s 1z + pon 999s waiting on 1z
+        let (tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p123s1z + pon999s").unwrap();
+        assert_eq!(
+            tile_strings(&get_waits(&tiles, &declared_groups)),
+            vec!["1z"]
+        );
+
+        // not in tenpai (1-shanten), even though the hand has ukiere
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        assert!(get_waits(&tiles, &vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_is_furiten() {
+        // 123456m 567s 11s 78p: waiting on 6p or 9p
+        let tiles = tiles::tiles_from_string("123456m78p11567s");
+        let wait_tiles = get_waits(&tiles, &vec![]);
+        assert_eq!(tile_strings(&wait_tiles), vec!["6p", "9p"]);
+
+        assert!(is_furiten(&wait_tiles, &tiles::tiles_from_string("9p1z")));
+        assert!(!is_furiten(&wait_tiles, &tiles::tiles_from_string("58p1z")));
+        assert!(!is_furiten(&wait_tiles, &vec![]));
+        // a red five is the same as a normal five
+        let wait_tiles = tiles::tiles_from_string("25m");
+        assert!(is_furiten(&wait_tiles, &tiles::tiles_from_string("0m")));
+    }
+
+    #[test]
+    fn test_live_count_of() {
+        // 123m 456p 789s 55m 11z: a shanpon wait on 5m and 1z, with 2 copies of each wait already in the hand
+        let tiles = tiles::tiles_from_string("12355m456p789s11z");
+        let hand_counts = count_tiles_by_kind(&tiles);
+        let five_man = tiles::Tile::from_string("5m");
+        assert_eq!(live_count_of(&five_man, &hand_counts, &[]), 2);
+        // a red five is the same tile kind
+        let visible_tiles = tiles::tiles_from_string("0m1z");
+        assert_eq!(live_count_of(&five_man, &hand_counts, &visible_tiles), 1);
+        assert_eq!(
+            live_count_of(
+                &tiles::Tile::from_string("1z"),
+                &hand_counts,
+                &visible_tiles
+            ),
+            1
+        );
+        assert_eq!(
+            live_count_of(
+                &tiles::Tile::from_string("9m"),
+                &hand_counts,
+                &visible_tiles
+            ),
+            4
+        );
+        assert_eq!(
+            get_ukiere_with_counts(&tiles, &vec![])
+                .iter()
+                .map(|(tile, num_live)| (tile.to_string(), *num_live))
+                .collect::<Vec<(String, u32)>>(),
+            vec![(String::from("5m"), 2), (String::from("1z"), 2)]
+        );
+    }
+
+    #[test]
+    fn test_ukiere_with_counts() {
+        // 123m 456p 34s 78s 55z 9m: ukiere 2s, 5s, 6s, 9s
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        let ukiere_strings = |ukiere_with_counts: Vec<(tiles::Tile, u32)>| -> Vec<(String, u32)> {
+            ukiere_with_counts
+                .iter()
+                .map(|(tile, num_live)| (tile.to_string(), *num_live))
+                .collect()
+        };
+        assert_eq!(
+            ukiere_strings(get_ukiere_with_counts(&tiles, &vec![])),
+            vec![
+                (String::from("2s"), 4),
+                (String::from("5s"), 4),
+                (String::from("6s"), 4),
+                (String::from("9s"), 4)
+            ]
+        );
+
+        // all four copies of 2s are visible, so 2s is excluded, and one 9s is visible
+        let visible_tiles = tiles::tiles_from_string("22229s");
+        assert_eq!(
+            ukiere_strings(get_ukiere_with_counts(&tiles, &visible_tiles)),
+            vec![
+                (String::from("5s"), 4),
+                (String::from("6s"), 4),
+                (String::from("9s"), 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_acceptance() {
+        // 123456m 11z 23456p: waiting on 1p, 4p, or 7p, with 1p and 7p visible
+        let tiles = tiles::tiles_from_string("123456m23456p11z");
+        let (ukiere_tiles, num_live_ukiere) =
+            get_acceptance(&tiles, &tiles::tiles_from_string("17p"));
+        assert_eq!(tile_strings(&ukiere_tiles), vec!["1p", "4p", "7p"]);
+        assert_eq!(num_live_ukiere, 9);
+
+        // with no visible tiles, every ukiere tile has 4 live copies (2s, 5s, 6s, 9s)
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        let (ukiere_tiles, num_live_ukiere) = get_acceptance(&tiles, &vec![]);
+        assert_eq!(
+            tile_strings(&ukiere_tiles),
+            tile_strings(&get_ukiere(&tiles))
+        );
+        assert_eq!(num_live_ukiere, 16);
+    }
+
+    #[test]
+    fn test_ranked_discards() {
+        // 123m 66m 7m 6p 889p 12s 78s: discarding 6p or 9p keeps the most acceptance at 2-shanten
+        let tiles = tiles::tiles_from_string("123667m6889p1278s");
+        let discard_evals = ranked_discards(&tiles, &vec![]);
+        assert_eq!(discard_evals.len(), 12);
+        assert_eq!(discard_evals[0].tile.to_string(), "6p");
+        assert_eq!(discard_evals[0].shanten, 2);
+        assert_eq!(
+            tile_strings(&discard_evals[0].ukiere_tiles),
+            vec!["5m", "6m", "8m", "7p", "8p", "3s", "6s", "9s"]
+        );
+        assert_eq!(discard_evals[0].acceptance, 28);
+        // sorted by shanten, then by acceptance (ties in order of tile kind)
+        let ranking: Vec<(String, i32, u32)> = discard_evals
+            .iter()
+            .map(|eval| (eval.tile.to_string(), eval.shanten, eval.acceptance))
+            .collect();
+        assert_eq!(
+            ranking[1..5],
+            [
+                (String::from("9p"), 2, 28),
+                (String::from("6m"), 2, 20),
+                (String::from("7m"), 2, 20),
+                (String::from("8p"), 2, 16)
+            ]
+        );
+        assert_eq!(ranking[9], (String::from("1m"), 3, 35));
+        assert_eq!(
+            discard_evals[0].tile.to_string(),
+            get_best_discard_by_ukiere(&tiles, &vec![]).to_string()
+        );
+
+        // with all of the 8m visible, 8m is no longer ukiere
+        let discard_evals = ranked_discards(&tiles, &tiles::tiles_from_string("8888m"));
+        assert_eq!(discard_evals[0].tile.to_string(), "6p");
+        assert_eq!(discard_evals[0].acceptance, 24);
+        assert!(!tile_strings(&discard_evals[0].ukiere_tiles).contains(&String::from("8m")));
+    }
+
+    #[test]
+    fn test_compare_discards() {
+        let tile_strings = |tiles: &[tiles::Tile]| -> Vec<String> {
+            tiles.iter().map(|tile| tile.to_string()).collect()
+        };
+        let hand_counts = count_tiles_by_kind(&tiles::tiles_from_string("4557m2357p23567s6p"));
+
+        // cut 4m (leaving 557m) vs cut 7m (leaving 455m): both are 1-shanten with the same ukiere (16 tiles), so the
+        // difference between them is in the draws that improve the shape, not in the ukiere
+        let comparison = compare_discards(
+            &hand_counts,
+            &tiles::Tile::from_string("4m"),
+            &tiles::Tile::from_string("7m"),
+            &[],
+        );
+        assert_eq!((comparison.a.shanten, comparison.a.acceptance), (1, 16));
+        assert_eq!((comparison.b.shanten, comparison.b.acceptance), (1, 16));
+        assert_eq!(
+            tile_strings(&comparison.a.ukiere_tiles),
+            vec!["1p", "4p", "1s", "4s"]
+        );
+        assert!(comparison.only_a_ukiere.is_empty());
+        assert!(comparison.only_b_ukiere.is_empty());
+        // e.g. drawing 3m after cut 7m makes 345m (then cutting a 5m widens the acceptance), but 3m doesn't connect to 557m
+        assert_eq!(
+            tile_strings(&comparison.a_upgrade_tiles),
+            vec!["5m", "6m", "2p", "3p", "2s", "3s"]
+        );
+        assert_eq!(
+            tile_strings(&comparison.b_upgrade_tiles),
+            vec!["3m", "5m", "6m", "2p", "3p", "2s", "3s"]
+        );
+        assert!(comparison.only_a_upgrades.is_empty());
+        assert_eq!(tile_strings(&comparison.only_b_upgrades), vec!["3m"]);
+
+        // cut 7m vs cut 7p (2-shanten), with a copy of 1p visible
+        let comparison = compare_discards(
+            &hand_counts,
+            &tiles::Tile::from_string("7m"),
+            &tiles::Tile::from_string("7p"),
+            &tiles::tiles_from_string("1p"),
+        );
+        assert_eq!((comparison.a.shanten, comparison.a.acceptance), (1, 15));
+        assert_eq!((comparison.b.shanten, comparison.b.acceptance), (2, 18));
+        assert!(comparison.only_a_ukiere.is_empty());
+        assert_eq!(tile_strings(&comparison.only_b_ukiere), vec!["7p"]);
+
+        // the discard must be in the hand, and the hand must be 14 tiles
+        assert!(try_compare_discards(
+            &hand_counts,
+            &tiles::Tile::from_string("4m"),
+            &tiles::Tile::from_string("1z"),
+            &[]
+        )
+        .is_err());
+        let thirteen_tile_counts =
+            count_tiles_by_kind(&tiles::tiles_from_string("4557m2357p23567s"));
+        assert!(try_compare_discards(
+            &thirteen_tile_counts,
+            &tiles::Tile::from_string("4m"),
+            &tiles::Tile::from_string("7m"),
+            &[]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_ranked_discards_with_budget() {
+        let tiles = tiles::tiles_from_string("123667m6889p1278s");
+        let exact_evals = ranked_discards(&tiles, &vec![]);
+        let eval_summary = |discard_evals: &Vec<DiscardEval>| -> Vec<(String, i32, u32)> {
+            discard_evals
+                .iter()
+                .map(|eval| (eval.tile.to_string(), eval.shanten, eval.acceptance))
+                .collect()
+        };
+
+        // an unbounded search is the same as ranked_discards
+        let (discard_evals, completed) = ranked_discards_with_budget(&tiles, &vec![], usize::MAX);
+        assert!(completed);
+        assert_eq!(eval_summary(&discard_evals), eval_summary(&exact_evals));
+
+        // without any budget, only the shanten after each discard is computed, and it's still exact
+        let (discard_evals, completed) = ranked_discards_with_budget(&tiles, &vec![], 0);
+        assert!(!completed);
+        assert_eq!(discard_evals.len(), exact_evals.len());
+        for discard_eval in discard_evals.iter() {
+            let exact_eval = exact_evals
+                .iter()
+                .find(|eval| eval.tile.to_string() == discard_eval.tile.to_string())
+                .unwrap();
+            assert_eq!(discard_eval.shanten, exact_eval.shanten);
+            assert_eq!(discard_eval.acceptance, 0);
+        }
+        assert_eq!(discard_evals[0].shanten, 2);
+
+        // with a budget for only three discards (each evaluates drawing every tile kind), the 2-shanten discards are
+        // evaluated first (in order of tile kind: 6m, 7m, 6p), which already finds the best discard
+        let (discard_evals, completed) =
+            ranked_discards_with_budget(&tiles, &vec![], 3 * tiles::NUM_TILE_KINDS);
+        assert!(!completed);
+        let evaluated: Vec<(String, i32, u32)> = eval_summary(&discard_evals)
+            .into_iter()
+            .filter(|(_, _, acceptance)| *acceptance > 0)
+            .collect();
+        assert_eq!(
+            evaluated,
+            vec![
+                (String::from("6p"), 2, 28),
+                (String::from("6m"), 2, 20),
+                (String::from("7m"), 2, 20)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ukiere_with_kabe() {
+        assert_eq!(
+            tile_strings(&dead_waits_from_kabe(&tiles::tiles_from_string(
+                "55559p1111z"
+            ))),
+            vec!["5p", "1z"]
+        );
+        assert!(dead_waits_from_kabe(&tiles::tiles_from_string("555p")).is_empty());
+
+        // 123m 456s 789s 46p 11z: tenpai on 5p, but all four copies of 5p are visible
+        let tiles = tiles::tiles_from_string("123m456s789s46p11z");
+        let visible_tiles = tiles::tiles_from_string("5555p");
+        assert!(get_ukiere_with_counts(&tiles, &visible_tiles).is_empty());
+
+        // the 46p closed wait is dead, so the hand is effectively 1-shanten, and the ukiere are the tiles that
+        // replace the dead wait (e.g. 3p for a 34p two-sided wait), and none of them are 5p
+        assert_eq!(
+            get_ukiere_with_kabe(&tiles, &visible_tiles)
+                .iter()
+                .map(|(tile, num_live)| (tile.to_string(), *num_live))
+                .collect::<Vec<(String, u32)>>(),
+            vec![
+                (String::from("2p"), 4),
+                (String::from("3p"), 4),
+                (String::from("4p"), 3),
+                (String::from("6p"), 3),
+                (String::from("7p"), 4),
+                (String::from("8p"), 4),
+                (String::from("1z"), 2)
+            ]
+        );
+
+        // without any dead waits, it's the same as the ukiere with counts
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        let visible_tiles = tiles::tiles_from_string("22229s");
+        assert_eq!(
+            get_ukiere_with_kabe(&tiles, &visible_tiles)
+                .iter()
+                .map(|(tile, num_live)| (tile.to_string(), *num_live))
+                .collect::<Vec<(String, u32)>>(),
+            get_ukiere_with_counts(&tiles, &visible_tiles)
+                .iter()
+                .map(|(tile, num_live)| (tile.to_string(), *num_live))
+                .collect::<Vec<(String, u32)>>()
+        );
+    }
+
+    #[test]
+    fn test_two_step_acceptance() {
+        // both hands are 1-shanten with 16 live ukiere tiles, but the second hand reaches wider waits on average
+        let shallow_tiles = tiles::tiles_from_string("234456m1135p344s");
+        let deep_tiles = tiles::tiles_from_string("678m4577p135567s");
+        assert_eq!(get_shanten(&shallow_tiles), 1);
+        assert_eq!(get_shanten(&deep_tiles), 1);
+        assert_eq!(get_acceptance(&shallow_tiles, &vec![]).1, 16);
+        assert_eq!(get_acceptance(&deep_tiles, &vec![]).1, 16);
+        assert!((get_two_step_acceptance(&shallow_tiles, &vec![]) - 5.0).abs() < 1e-9);
+        assert!((get_two_step_acceptance(&deep_tiles, &vec![]) - 6.0).abs() < 1e-9);
+
+        // a hand with no live ukiere tiles
+        let tiles = tiles::tiles_from_string("123m456p56s11155z");
+        assert_eq!(
+            get_two_step_acceptance(&tiles, &tiles::tiles_from_string("44447777s")),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_standard_shanten_by_suit_matches_whole_hand() {
+        // searching the whole hand at once (the pair can be any tile kind)
+        let whole_hand_standard_shanten = |tiles: &Vec<tiles::Tile>| -> i32 {
+            let mut counts = count_tiles_by_kind(tiles);
+            let live_kinds = [true; tiles::NUM_TILE_KINDS];
+            let num_groups = (tiles.len() / 3) as i32;
+            let mut best_shanten = 2 * num_groups
+                - max_group_score(
+                    &mut counts,
+                    &live_kinds,
+                    0,
+                    tiles::NUM_TILE_KINDS,
+                    num_groups,
+                );
+            for kind_index in 0..tiles::NUM_TILE_KINDS {
+                if counts[kind_index] >= 2 {
+                    counts[kind_index] -= 2;
+                    let score = max_group_score(
+                        &mut counts,
+                        &live_kinds,
+                        0,
+                        tiles::NUM_TILE_KINDS,
+                        num_groups,
+                    );
+                    counts[kind_index] += 2;
+                    best_shanten = best_shanten.min(2 * num_groups - 1 - score);
+                }
+            }
+            best_shanten
+        };
+
+        for hand_string in [
+            "123m456p789s11155z",
+            "123m456p56s11155z",
+            "1239m456p3478s55z",
+            "147m258p369s1234z",
+            "1199m2288p3377s1z",
+            "19m19p19s1123456z",
+            "345m1156p666778s",
+            "1111m234p567s777z",
+            "112233m445566p7s",
+            "2345666s111777z",
+            "234456m1135p344s",
+            "678m4577p135567s",
+            "234m567p123s1z",
+            "112233p4z",
+        ] {
+            let tiles = tiles::tiles_from_string(hand_string);
+            assert_eq!(
+                standard_shanten_from_counts(&count_tiles_by_kind(&tiles)),
+                whole_hand_standard_shanten(&tiles),
+                "{}",
+                hand_string
+            );
+        }
+    }
+
+    #[test]
+    fn test_ukiere_sequence_or_closed_wait() {
+        // 2344s can be read as 234s + 4s, 23s + 44s, or 24s + 34s: the search tries a closed wait (kanchan) even when
+        // the same tile could start a sequence, so none of the readings are skipped
+        // 123456m 234s 46s 77z: the 4s is used in both the sequence and the closed wait, waiting on 5s
+        let tiles = tiles::tiles_from_string("123456m2344s6s77z");
+        assert_eq!(get_shanten(&tiles), 0);
+        assert_eq!(tile_strings(&get_ukiere(&tiles)), vec!["5s"]);
+
+        // 111m 23m 456p 2344s 9p (or 11m 123m 456p 2344s 9p)
+        let tiles = tiles::tiles_from_string("11123m456p2344s9p");
+        assert_eq!(get_shanten(&tiles), 1);
+        assert_eq!(
+            tile_strings(&get_ukiere(&tiles)),
+            vec!["1m", "4m", "7p", "8p", "9p", "1s", "2s", "3s", "4s", "5s", "6s"]
+        );
+
+        // 123m 456m 2344s 88p 1z: 3s completes the closed wait of the 24s + 34s reading (234s + 34s)
+        let tiles = tiles::tiles_from_string("123456m88p2344s1z");
+        assert_eq!(get_shanten(&tiles), 1);
+        assert!(tile_strings(&get_ukiere(&tiles)).contains(&String::from("3s")));
+    }
+
+    #[test]
+    fn test_classify_wait() {
+        let wait_shapes = |hand_string: &str| classify_wait(&tiles::tiles_from_string(hand_string));
+        // 123m 456p 789s 55z 34s
+        assert_eq!(wait_shapes("123m456p34789s55z"), vec![WaitShape::Ryanmen]);
+        assert_eq!(wait_shapes("123m456p35789s55z"), vec![WaitShape::Kanchan]);
+        assert_eq!(wait_shapes("123m456p12789s55z"), vec![WaitShape::Penchan]);
+        assert_eq!(wait_shapes("123m456p789s1115z"), vec![WaitShape::Tanki]);
+        assert_eq!(wait_shapes("123m456p789s1155z"), vec![WaitShape::Shanpon]);
+        assert_eq!(wait_shapes("123456m789s3456p"), vec![WaitShape::Nobetan]);
+        assert_eq!(wait_shapes("123456m34567s11z"), vec![WaitShape::Sanmenchan]);
+        assert_eq!(wait_shapes("123m456p3456789s"), vec![WaitShape::Sanmentan]);
+        // from the aryanmen tenpai grouping test: 678p 123p 789s 5567m
+        assert_eq!(wait_shapes("678p123p789s6755m"), vec![WaitShape::Aryanmen]);
+        assert_eq!(wait_shapes("123m456p789s2223z"), vec![WaitShape::Tanki]);
+        assert_eq!(wait_shapes("123m456p789m2223s"), vec![WaitShape::Ryantan]);
+        assert_eq!(wait_shapes("123m789p44456s77z"), vec![WaitShape::Entotsu]);
+        // from the sample pro test question 1: 2345666s 111z 777z
+        assert_eq!(
+            wait_shapes("2345666s111777z"),
+            vec![WaitShape::Nobetan, WaitShape::Sanmenchan]
+        );
+        // 1-shanten
+        assert!(wait_shapes("1239m456p3478s55z").is_empty());
+    }
+
+    #[test]
+    fn test_shanpon_waits() {
+        // 123m 999m 44p 99s 777z: both pairs are the wait (neither is locked in as the head)
+        let tiles = tiles::tiles_from_string("123999m44p99s777z");
+        assert_eq!(classify_wait(&tiles), vec![WaitShape::Shanpon]);
+        assert_eq!(tile_strings(&get_waits(&tiles, &vec![])), vec!["4p", "9s"]);
+        assert_eq!(tile_strings(&get_ukiere(&tiles)), vec!["4p", "9s"]);
+        // winning on either pair tile makes it a triplet, and the other pair is the head
+        for (winning_tile_string, pair_tile_string) in [("4p", "9s"), ("9s", "4p")] {
+            let decompositions = tile_grouping::decompose_winning_hand(
+                &tiles,
+                &vec![],
+                &tiles::Tile::from_string(winning_tile_string),
+            );
+            assert_eq!(decompositions.len(), 1);
+            assert_eq!(
+                tiles::get_head_tile(&decompositions[0])
+                    .unwrap()
+                    .to_string(),
+                pair_tile_string
+            );
+        }
+
+        // with a called triplet: 123m 44p 99s 777z + pon 999m is the same wait
+        let (tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("123m44p99s777z + pon9m").unwrap();
+        assert_eq!(
+            tile_strings(&get_waits(&tiles, &declared_groups)),
+            vec!["4p", "9s"]
+        );
+    }
+
+    #[test]
+    fn test_tile_counts_arithmetic() {
+        let mut counts = count_tiles_by_kind(&tiles::tiles_from_string("1112m55z"));
+        assert_eq!(total_tiles(&counts), 6);
+        assert_eq!(distinct_tiles(&counts), 3);
+
+        // a fourth copy can be added, but not a fifth
+        let one_man = tiles::Tile::from_string("1m");
+        assert_eq!(try_add_tile_to_counts(&mut counts, &one_man), Ok(()));
+        assert_eq!(counts[one_man.kind_index()], 4);
+        assert_eq!(
+            try_add_tile_to_counts(&mut counts, &one_man),
+            Err("Hand can't have more than four copies of a tile kind!")
+        );
+        assert_eq!(counts[one_man.kind_index()], 4);
+        assert_eq!(total_tiles(&counts), 7);
+
+        // a tile kind that isn't in the hand can't be removed
+        let two_man = tiles::Tile::from_string("2m");
+        assert_eq!(try_remove_tile_from_counts(&mut counts, &two_man), Ok(()));
+        assert_eq!(
+            try_remove_tile_from_counts(&mut counts, &two_man),
+            Err("Hand must have a copy of the tile kind to remove!")
+        );
+        assert_eq!(counts[two_man.kind_index()], 0);
+        assert_eq!(total_tiles(&counts), 6);
+        assert_eq!(distinct_tiles(&counts), 2);
+    }
+
+    #[test]
+    fn test_invalid_hand_errors() {
+        // 12 and 15 tiles
+        for hand_string in ["123456789m123p", "123456789m123456p"] {
+            let tiles = tiles::tiles_from_string(hand_string);
+            assert_eq!(
+                try_get_standard_shanten(&tiles),
+                Err("Hand must have 13 or 14 tiles!")
+            );
+            assert_eq!(
+                try_get_shanten_with_melds(&tiles, &vec![]),
+                Err("Wrong number of tiles in hand for its declared melds!")
+            );
+            assert_eq!(
+                try_get_ukiere_with_melds(&tiles, &vec![]).err(),
+                Some("Wrong number of tiles in hand for its declared melds!")
+            );
+        }
+
+        // five copies of a tile kind
+        let tiles = tiles::tiles_from_string("11111m23456789p");
+        assert_eq!(
+            try_get_standard_shanten(&tiles),
+            Err("Hand can't have more than four copies of a tile kind!")
+        );
+
+        // the concealed tiles must be 13 (or 14) tiles minus 3 per declared meld
+        let (tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p123s1z + pon999s").unwrap();
+        assert_eq!(try_get_shanten_with_melds(&tiles, &declared_groups), Ok(0));
+        assert!(try_get_ukiere_with_melds(&tiles, &declared_groups).is_ok());
+        let (tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p123s1155z + pon999s").unwrap();
+        assert!(try_get_shanten_with_melds(&tiles, &declared_groups).is_err());
+
+        // the full tile list (including the meld tiles) doesn't have the declared meld's tiles
+        let (_, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p123s11z + pon999s").unwrap();
+        let tiles = tiles::tiles_from_string("234m567p123s11z888s");
+        assert_eq!(
+            try_get_shanten_with_melds(&tiles, &declared_groups),
+            Err("Declared meld tiles must be in the hand!")
+        );
+    }
+
+    #[test]
+    fn test_counts_from_tenhou_url() {
+        for hand_string in ["345m1156p4666778s", "123456789m1134p", "2345666s111777z"] {
+            let url = format!("https://tenhou.net/2/?q={}", hand_string);
+            assert_eq!(
+                counts_from_tenhou_url(&url),
+                Ok(count_tiles_by_kind(&tiles::tiles_from_string(hand_string)))
+            );
+        }
+
+        // red fives are counted as normal fives, and other parameters are ignored
+        assert_eq!(
+            counts_from_tenhou_url("https://tenhou.net/2/?q=340m1106p4666778s&x=1"),
+            counts_from_tenhou_url("https://tenhou.net/2/?q=345m1156p4666778s")
+        );
+
+        assert!(counts_from_tenhou_url("https://tenhou.net/2/").is_err());
+        assert!(counts_from_tenhou_url("https://tenhou.net/2/?x=1").is_err());
+        assert!(counts_from_tenhou_url("https://tenhou.net/2/?q=345m1156p4666778").is_err());
+        assert!(counts_from_tenhou_url("https://tenhou.net/2/?q=345m1156p89z").is_err());
+        assert!(counts_from_tenhou_url("https://tenhou.net/2/?q=11111m").is_err());
+        // too many copies is an Err, even if the count would overflow
+        assert!(
+            counts_from_tenhou_url(&format!("https://tenhou.net/2/?q={}m", "1".repeat(256)))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_tenhou_url_from_counts() {
+        for url in [
+            "https://tenhou.net/2/?q=345m1156p4666778s",
+            "https://tenhou.net/2/?q=123456789m1134p",
+            "https://tenhou.net/2/?q=2345666s111777z",
+            "https://tenhou.net/2/?q=19m19p19s1234567z",
+        ] {
+            let tile_kind_counts = counts_from_tenhou_url(url).unwrap();
+            assert_eq!(tenhou_url_from_counts(&tile_kind_counts), url);
+        }
+
+        // the tiles are sorted by suit and rank, and red fives are written as normal fives
+        let tiles = tiles::tiles_from_string("7z05s1m9p1m");
+        assert_eq!(
+            tenhou_url_from_counts(&count_tiles_by_kind(&tiles)),
+            "https://tenhou.net/2/?q=11m9p55s7z"
+        );
+    }
+
+    #[test]
+    fn test_ukiere_for_yaku() {
+        // 234m 55m 345p 345s 78s: waiting on 6s or 9s
+        let tiles = tiles::tiles_from_string("23455m345p345s78s");
+        assert_eq!(tile_strings(&get_ukiere(&tiles)), vec!["6s", "9s"]);
+        // aiming for tanyao, drawing 9s would add a terminal
+        assert_eq!(
+            tile_strings(&get_ukiere_for_yaku(&tiles, YakuTarget::Tanyao)),
+            vec!["6s"]
+        );
+
+        // 1234m 678m 55m 9m 12p 1z: aiming for a manzu honitsu
+        let tiles = tiles::tiles_from_string("1234678m55m9m12p1z");
+        let honitsu_ukiere = get_ukiere_for_yaku(&tiles, YakuTarget::Honitsu(tiles::TileSuit::Man));
+        assert!(!honitsu_ukiere.is_empty());
+        assert!(honitsu_ukiere
+            .iter()
+            .all(|tile| tile.suit() == tiles::TileSuit::Man || tile.is_honor()));
+        assert!(get_ukiere(&tiles)
+            .iter()
+            .any(|tile| tile.suit() == tiles::TileSuit::Pin));
+    }
+
+    #[test]
+    fn test_tenpai_form() {
+        // six pairs and 7p: only tenpai for seven pairs (the standard hand shape is 3-shanten)
+        let tiles = tiles::tiles_from_string("1166m667p4499s55z");
+        assert_eq!(get_tenpai_form(&tiles), Some(TenpaiForm::ChiitoiOnly));
+        assert_eq!(tile_strings(&get_ukiere(&tiles)), vec!["7p"]);
+
+        // 123m 123m 456p 456p 7s is also six pairs and 7s: tenpai for both, waiting on 7s
+        let tiles = tiles::tiles_from_string("112233m445566p7s");
+        assert_eq!(get_tenpai_form(&tiles), Some(TenpaiForm::Both));
+        assert_eq!(tile_strings(&get_ukiere(&tiles)), vec!["7s"]);
+
+        let tiles = tiles::tiles_from_string("123m456p56s11155z");
+        assert_eq!(get_tenpai_form(&tiles), Some(TenpaiForm::StandardOnly));
+
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        assert_eq!(get_tenpai_form(&tiles), None);
+    }
+
+    #[test]
+    fn test_ukiere_kokushi_waits() {
+        // all thirteen different terminal/honor tiles (no pair): the 13-sided wait on any terminal/honor tile
+        let tiles = tiles::tiles_from_string("19m19p19s1234567z");
+        assert_eq!(get_kokushi_shanten(&tiles), 0);
+        assert_eq!(
+            tile_strings(&get_ukiere(&tiles)),
+            vec!["1m", "9m", "1p", "9p", "1s", "9s", "1z", "2z", "3z", "4z", "5z", "6z", "7z"]
+        );
+
+        // has a pair (9p) but is missing 1s: only waiting on the missing 1s
+        let tiles = tiles::tiles_from_string("19m199p9s1234567z");
+        assert_eq!(get_kokushi_shanten(&tiles), 0);
+        assert_eq!(tile_strings(&get_ukiere(&tiles)), vec!["1s"]);
+    }
+
+    #[test]
+    fn test_ukiere_wait_widths() {
+        // 1-shanten: 123m 456p 35s 78s 55z 9m
+        let tiles = tiles::tiles_from_string("1239m456p3578s55z");
+        let wait_widths: Vec<(String, u32)> = get_ukiere_wait_widths(&tiles, &vec![])
+            .iter()
+            .map(|(tile, wait_width)| (tile.to_string(), *wait_width))
+            .collect();
+        assert_eq!(
+            wait_widths,
+            vec![
+                // 345s leaves the 78s two-sided wait on 6s or 9s
+                (String::from("4s"), 8),
+                // 678s or 789s leaves the 35s closed wait on 4s
+                (String::from("6s"), 4),
+                (String::from("9s"), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wait_completions() {
+        // nobetan: 123m 456p 789p 3456s, winning on 3s or 6s forms the pair
+        let tiles = tiles::tiles_from_string("123m456789p3456s");
+        let wait_completions: Vec<(String, WaitCompletion)> = get_wait_completions(&tiles)
+            .iter()
+            .map(|(tile, completion)| (tile.to_string(), *completion))
+            .collect();
+        assert_eq!(
+            wait_completions,
+            vec![
+                (String::from("3s"), WaitCompletion::Pair),
+                (String::from("6s"), WaitCompletion::Pair),
+            ]
+        );
+
+        // ryanmen: 123m 456p 789p 33s 45s, winning on 3s or 6s completes the 45s sequence
+        let tiles = tiles::tiles_from_string("123m456789p3345s");
+        let wait_completions: Vec<(String, WaitCompletion)> = get_wait_completions(&tiles)
+            .iter()
+            .map(|(tile, completion)| (tile.to_string(), *completion))
+            .collect();
+        assert_eq!(
+            wait_completions,
+            vec![
+                (String::from("3s"), WaitCompletion::Group),
+                (String::from("6s"), WaitCompletion::Group),
+            ]
+        );
+
+        // not in tenpai
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        assert!(get_wait_completions(&tiles).is_empty());
+    }
+
+    #[test]
+    fn test_riichi_wait_tiles() {
+        // 123m 456p 789s 11z 35s: closed wait on 4s
+        let tiles = tiles::tiles_from_string("123m456p35789s11z");
+        let wait_tiles = get_riichi_wait_tiles(&tiles).expect("Hand should be in tenpai");
+        assert_eq!(tile_strings(&wait_tiles), vec!["4s"]);
+
+        // 1-shanten hand: not a valid riichi hand
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        assert!(get_riichi_wait_tiles(&tiles).is_err());
+
+        // 14 tiles: not a valid riichi hand
+        let tiles = tiles::tiles_from_string("123m456p789s11155z");
+        assert!(get_riichi_wait_tiles(&tiles).is_err());
+    }
+
+    #[test]
+    fn test_ukiere_excludes_fully_held_tiles() {
+        // 111m 234p 567s 777z and a single 1m: waiting on 1m, but all four copies of 1m are in the hand
+        let tiles = tiles::tiles_from_string("1111m234p567s777z");
+        assert_eq!(get_shanten(&tiles), 0);
+        assert!(get_ukiere(&tiles).is_empty());
+    }
+
+    #[test]
+    fn test_ukiere_three_copies_split_across_shapes() {
+        // 345m 1156p 666778s (after discarding 4s from 345m1156p4666778s): the 666s have to be split as
+        // 678s + 67s + 6s (or 66s + 678s + 7s) to find all of the ukiere, including 5s and 8s
+        let tiles = tiles::tiles_from_string("345m1156p666778s");
+        assert_eq!(get_shanten(&tiles), 1);
+        assert_eq!(
+            tile_strings(&get_ukiere(&tiles)),
+            vec!["1p", "4p", "7p", "5s", "6s", "7s", "8s", "9s"]
+        );
+    }
+
+    #[test]
+    fn test_expected_draws_to_win_tenpai() {
+        // ryanmen wait (4s, 7s) with 8 live tiles out of 123 unseen tiles
+        let tiles = tiles::tiles_from_string("123m456p56s11155z");
+        let expected_draws = expected_draws_to_win(&tiles, &vec![]);
+        assert!((expected_draws - 124.0 / 9.0).abs() < 1e-9);
+
+        // if all copies of the waits are visible, the hand can't win
+        let visible_tiles = tiles::tiles_from_string("44447777s");
+        assert!(expected_draws_to_win(&tiles, &visible_tiles).is_infinite());
+    }
+
+    #[test]
+    fn test_best_discard_by_value() {
+        // 123m 456m 789p 66s 7s 55z: discarding 6s or 7s both reach tenpai
+        let tiles = tiles::tiles_from_string("123456m789p667s55z");
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 60,
+            dora_indicators: vec![tiles::Tile::from_string("1m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: None,
+        };
+
+        // discarding 6s leaves a 67s two-sided wait (5s, 8s: 8 live tiles) but no yaku,
+        // discarding 7s leaves a 6s/5z shanpon wait (4 live tiles), where winning on 5z gives yakuhai
+        let discard_6s = tiles::tiles_from_string("123456m789p67s55z");
+        assert_eq!(tile_strings(&get_ukiere(&discard_6s)), vec!["5s", "8s"]);
+        let discard_7s = tiles::tiles_from_string("123456m789p66s55z");
+        assert_eq!(tile_strings(&get_ukiere(&discard_7s)), vec!["6s", "5z"]);
+
+        let best_discard = get_best_discard_by_value(&tiles, &vec![], &hand_state, &player_state);
+        assert_eq!(best_discard.to_string(), "7s");
+
+        // seven pairs: discarding 5p or 4z both leave a single wait (3 live tiles), but with the 4p dora indicator,
+        // waiting on 5p (chiitoitsu + dora) is worth more than waiting on 4z (chiitoitsu)
+        let tiles = tiles::tiles_from_string("1155m22588p3399s4z");
+        let mut dora_hand_state = hand_state.clone();
+        dora_hand_state.dora_indicators = tiles::tiles_from_string("4p");
+        let best_discard =
+            get_best_discard_by_value(&tiles, &vec![], &dora_hand_state, &player_state);
+        assert_eq!(best_discard.to_string(), "4z");
+    }
+
+    /// Plays out the hand (from a seeded shuffled wall of all unseen tiles) with the same discard strategy as
+    /// the estimate, and returns the number of draws needed to win
+    fn simulate_draws_to_win(tiles: &Vec<tiles::Tile>, rng: &mut StdRng) -> u32 {
+        let mut hand_counts = count_tiles_by_kind(tiles);
+        let mut seen_counts = hand_counts;
+        let mut wall: Vec<usize> = Vec::new();
+        for (kind_index, count) in hand_counts.iter().enumerate() {
+            for _ in 0..(NUM_COPIES_PER_TILE_KIND - count) {
+                wall.push(kind_index);
+            }
+        }
+        wall.shuffle(rng);
+
+        let mut num_draws = 0;
+        for drawn_kind_index in wall.into_iter() {
+            num_draws += 1;
+            hand_counts[drawn_kind_index] += 1;
+            seen_counts[drawn_kind_index] += 1;
+            if shanten_from_counts(&hand_counts) < 0 {
+                return num_draws;
+            }
+            let (discard_kind_index, _) = best_discard_from_counts(&hand_counts, &seen_counts);
+            hand_counts[discard_kind_index] -= 1;
+        }
+        panic!("Hand never reached a win");
+    }
+
+    #[test]
+    fn test_expected_draws_to_win_vs_monte_carlo() {
+        // 1-shanten: 123m 456p 34s 78s 55z 9m
+        let tiles = tiles::tiles_from_string("1239m456p3478s55z");
+        assert_eq!(get_shanten(&tiles), 1);
+        let expected_draws = expected_draws_to_win(&tiles, &vec![]);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let num_trials = 200;
+        let total_draws: u32 = (0..num_trials)
+            .map(|_| simulate_draws_to_win(&tiles, &mut rng))
+            .sum();
+        let avg_draws = total_draws as f64 / num_trials as f64;
+        // the simulation can improve the hand's shape while drawing non-ukiere tiles (which the estimate ignores),
+        // so it shouldn't take longer than the estimate (with a seeded rng), and allow some tolerance below it
+        assert!(
+            avg_draws <= expected_draws,
+            "expected draws (estimate) = {}, avg draws (simulated) = {}",
+            expected_draws,
+            avg_draws
+        );
+        assert!(
+            (expected_draws - avg_draws).abs() / expected_draws < 0.2,
+            "expected draws (estimate) = {}, avg draws (simulated) = {}",
+            expected_draws,
+            avg_draws
+        );
+    }
+}
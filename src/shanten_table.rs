@@ -0,0 +1,262 @@
+use crate::{shanten, tiles};
+use std::sync::OnceLock;
+
+// the number of tile kinds in each numbered suit
+const NUM_SUIT_KINDS: usize = 9;
+// the number of honor tile kinds
+const NUM_HONOR_KINDS: usize = 7;
+// the number of count vectors for a single suit (each tile kind has 0 to 4 copies)
+const NUM_SUIT_COUNT_VECTORS: usize = 5usize.pow(NUM_SUIT_KINDS as u32);
+// the maximum number of groups (complete or incomplete) in a hand
+const MAX_GROUPS: usize = 4;
+// the score of a split that isn't possible (e.g. taking the pair from a suit with no pairs)
+const IMPOSSIBLE_SCORE: i8 = -100;
+
+/// The best scores of the ways to split the tiles of a suit (or the honor tiles) into groups, indexed by
+/// [the maximum number of groups][1 if the pair is taken from these tiles, otherwise 0]. Same as in `shanten`,
+/// each complete group (triplet or sequence) is worth 2 and each incomplete group (pair, or two-sided/closed/edge wait)
+/// is worth 1, and the pair isn't counted in the score.
+pub(crate) type GroupScores = [[i8; 2]; MAX_GROUPS + 1];
+
+/// The shanten number of the hand from the number of tiles of each tile kind (see `shanten::count_tiles_by_kind()`),
+/// the same as `shanten::get_shanten()`, but the standard hand shape is scored by looking up each suit in a precomputed
+/// table (which is built on first use) instead of searching over all of the ways to split the hand into groups.
+/// The hand can have fewer than 13 tiles if it has declared melds (see `shanten::get_shanten_with_melds()`).
+pub fn get_shanten_fast(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> i32 {
+    let standard_shanten = get_standard_shanten_fast(tile_kind_counts);
+    let num_tiles: u32 = tile_kind_counts.iter().map(|count| *count as u32).sum();
+    if num_tiles < 13 {
+        return standard_shanten;
+    }
+    standard_shanten
+        .min(shanten::chiitoi_shanten_from_counts(tile_kind_counts))
+        .min(shanten::kokushi_shanten_from_counts(tile_kind_counts))
+}
+
+/// The shanten number of the hand, only considering the standard hand shape (4 groups and a pair),
+/// using the precomputed table (see `get_shanten_fast()`)
+pub fn get_standard_shanten_fast(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> i32 {
+    let num_tiles: u32 = tile_kind_counts.iter().map(|count| *count as u32).sum();
+    let num_groups = (num_tiles / 3) as usize;
+
+    let table = suit_table();
+    let mut hand_scores = honor_scores(&tile_kind_counts[3 * NUM_SUIT_KINDS..]);
+    for suit in 0..3 {
+        let suit_counts = &tile_kind_counts[suit * NUM_SUIT_KINDS..(suit + 1) * NUM_SUIT_KINDS];
+        hand_scores = merge_scores(&hand_scores, &table[suit_index(suit_counts)]);
+    }
+    let best_score = (hand_scores[num_groups][0] as i32).max(hand_scores[num_groups][1] as i32 + 1);
+    2 * num_groups as i32 - best_score
+}
+
+fn suit_table() -> &'static Vec<GroupScores> {
+    static SUIT_TABLE: OnceLock<Vec<GroupScores>> = OnceLock::new();
+    SUIT_TABLE.get_or_init(build_suit_table)
+}
+
+/// The index of the suit's count vector in the table (i.e. the counts as a base-5 number, with the count of 1 as the
+/// most significant digit)
+fn suit_index(suit_counts: &[u8]) -> usize {
+    suit_counts
+        .iter()
+        .fold(0, |index, count| 5 * index + *count as usize)
+}
+
+/// Builds the table of the group scores for every count vector of a single suit. Every way to split the tiles removes
+/// tiles from the count vector, which always results in a smaller index, so the table is built in order of the index.
+fn build_suit_table() -> Vec<GroupScores> {
+    let mut table: Vec<GroupScores> = Vec::with_capacity(NUM_SUIT_COUNT_VECTORS);
+    table.push(empty_scores());
+    let mut counts = [0u8; NUM_SUIT_KINDS];
+    for index in 1..NUM_SUIT_COUNT_VECTORS {
+        // the count vector for this index (i.e. increment the base-5 number)
+        let mut digit = NUM_SUIT_KINDS - 1;
+        while counts[digit] == 4 {
+            counts[digit] = 0;
+            digit -= 1;
+        }
+        counts[digit] += 1;
+        table.push(suit_scores(&mut counts, &table));
+        debug_assert_eq!(table.len(), index + 1);
+    }
+    table
+}
+
+/// The group scores of the suit's tiles, from the scores of the smaller count vectors that are already in the table.
+/// Same as `shanten::search_groups()`, the first tile kind with any tiles is either used in a group, or left isolated.
+fn suit_scores(counts: &mut [u8; NUM_SUIT_KINDS], table: &[GroupScores]) -> GroupScores {
+    let kind_index = counts
+        .iter()
+        .position(|count| *count > 0)
+        .expect("Count vector must have at least one tile");
+    let has_next = kind_index + 1 < NUM_SUIT_KINDS && counts[kind_index + 1] > 0;
+    let has_next_next = kind_index + 2 < NUM_SUIT_KINDS && counts[kind_index + 2] > 0;
+    let num_copies = counts[kind_index];
+
+    // leave one copy of this tile isolated (not part of any group)
+    counts[kind_index] -= 1;
+    let mut scores = table[suit_index(counts)];
+    counts[kind_index] += 1;
+
+    // remove the tiles of a group, and add its score (or take the tiles as the pair, if the group score is None)
+    let mut try_split = |removed_kinds: &[usize], group_score: Option<i8>| {
+        for removed_kind in removed_kinds {
+            counts[*removed_kind] -= 1;
+        }
+        let rest_scores = &table[suit_index(counts)];
+        for removed_kind in removed_kinds {
+            counts[*removed_kind] += 1;
+        }
+        for max_groups in 0..=MAX_GROUPS {
+            match group_score {
+                None => {
+                    scores[max_groups][1] = scores[max_groups][1].max(rest_scores[max_groups][0]);
+                }
+                Some(group_score) if max_groups > 0 => {
+                    for has_pair in 0..2 {
+                        let score =
+                            rest_scores[max_groups - 1][has_pair].saturating_add(group_score);
+                        scores[max_groups][has_pair] = scores[max_groups][has_pair].max(score);
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+    };
+
+    if num_copies >= 3 {
+        try_split(&[kind_index, kind_index, kind_index], Some(2));
+    }
+    if has_next && has_next_next {
+        try_split(&[kind_index, kind_index + 1, kind_index + 2], Some(2));
+    }
+    if num_copies >= 2 {
+        try_split(&[kind_index, kind_index], None);
+        try_split(&[kind_index, kind_index], Some(1));
+    }
+    if has_next {
+        try_split(&[kind_index, kind_index + 1], Some(1));
+    }
+    if has_next_next {
+        try_split(&[kind_index, kind_index + 2], Some(1));
+    }
+    scores
+}
+
+/// The group scores of the honor tiles. Honor tiles can't form sequences, so each tile kind is scored separately.
+fn honor_scores(honor_counts: &[u8]) -> GroupScores {
+    debug_assert_eq!(honor_counts.len(), NUM_HONOR_KINDS);
+    let mut scores = empty_scores();
+    for count in honor_counts.iter() {
+        let group_score = match count {
+            3.. => 2,
+            2 => 1,
+            _ => 0,
+        };
+        let mut kind_scores = empty_scores();
+        for (max_groups, kind_max_groups_scores) in kind_scores.iter_mut().enumerate() {
+            if max_groups > 0 {
+                kind_max_groups_scores[0] = group_score;
+            }
+            if *count >= 2 {
+                // with 4 copies, the other 2 copies can be an incomplete group (waiting on the 5th copy, same as
+                // `shanten::search_groups()`), otherwise the rest of the tiles are isolated
+                kind_max_groups_scores[1] = if *count == 4 && max_groups > 0 { 1 } else { 0 };
+            }
+        }
+        scores = merge_scores(&scores, &kind_scores);
+    }
+    scores
+}
+
+/// The group scores of two disjoint sets of tiles combined: the groups are split between the two sets,
+/// and the pair is taken from at most one of them
+pub(crate) fn merge_scores(first: &GroupScores, second: &GroupScores) -> GroupScores {
+    let mut scores = [[IMPOSSIBLE_SCORE; 2]; MAX_GROUPS + 1];
+    for (first_groups, first_scores) in first.iter().enumerate() {
+        for (second_groups, second_scores) in second
+            .iter()
+            .take(MAX_GROUPS + 1 - first_groups)
+            .enumerate()
+        {
+            let max_groups = first_groups + second_groups;
+            // the pair is taken from the first set, the second set, or neither
+            for (first_pair, second_pair) in [(0, 0), (1, 0), (0, 1)] {
+                let score = first_scores[first_pair].saturating_add(second_scores[second_pair]);
+                let has_pair = first_pair + second_pair;
+                scores[max_groups][has_pair] = scores[max_groups][has_pair].max(score);
+            }
+        }
+    }
+    scores
+}
+
+/// The group scores of no tiles (or only isolated tiles): no groups, and no pair
+pub(crate) fn empty_scores() -> GroupScores {
+    [[0, IMPOSSIBLE_SCORE]; MAX_GROUPS + 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn test_shanten_fast_matches_shanten() {
+        for hand_string in [
+            "123m456p789s11155z",
+            "123m456p56s11155z",
+            "1239m456p3478s55z",
+            "147m258p369s1234z",
+            "1199m2288p3377s1z",
+            "19m19p19s1123456z",
+            "345m1156p666778s",
+            "1111m234p567s777z",
+            "112233m445566p7s",
+            "2345666s111777z",
+            "123456789m1234p",
+        ] {
+            let tiles = tiles::tiles_from_string(hand_string);
+            assert_eq!(
+                get_shanten_fast(&shanten::count_tiles_by_kind(&tiles)),
+                shanten::get_shanten(&tiles),
+                "{}",
+                hand_string
+            );
+        }
+
+        // hands with declared melds
+        for hand_string in [
+            "234m567p123s1z + pon999s",
+            "234m56p123s17z + pon999s",
+            "112233p4z + pon999s + pon777z",
+            "234m567p123s11z + pon999s",
+        ] {
+            let (tiles, declared_groups) = tiles::hand_and_melds_from_string(hand_string).unwrap();
+            assert_eq!(
+                get_shanten_fast(&shanten::count_tiles_by_kind(&tiles)),
+                shanten::get_shanten_with_melds(&tiles, &declared_groups),
+                "{}",
+                hand_string
+            );
+        }
+    }
+
+    #[test]
+    fn test_shanten_fast_matches_shanten_random_hands() {
+        let mut rng = StdRng::seed_from_u64(529);
+        let mut wall: Vec<tiles::Tile> = (0..tiles::NUM_TILE_KINDS)
+            .flat_map(|kind_index| [tiles::Tile::from_kind_index(kind_index); 4])
+            .collect();
+        for num_tiles in [13, 14] {
+            for _ in 0..200 {
+                wall.shuffle(&mut rng);
+                let tiles = wall[..num_tiles].to_vec();
+                assert_eq!(
+                    get_shanten_fast(&shanten::count_tiles_by_kind(&tiles)),
+                    shanten::get_shanten(&tiles)
+                );
+            }
+        }
+    }
+}
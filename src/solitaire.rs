@@ -0,0 +1,150 @@
+use crate::{shanten, tiles};
+use rand::prelude::*;
+
+/// The outcome of a single-player game (see `play_game()`): the number of draws until the hand first reached tenpai,
+/// and until the hand won, or None if the wall ran out first
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct SolitaireResult {
+    pub draws_to_tenpai: Option<u32>,
+    pub draws_to_win: Option<u32>,
+}
+
+/// The totals over many single-player games (see `run_trials()`)
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct SolitaireStats {
+    pub num_trials: u32,
+    pub num_tenpai: u32,
+    pub total_draws_to_tenpai: u32,
+    pub num_wins: u32,
+    pub total_draws_to_win: u32,
+}
+
+impl SolitaireStats {
+    /// The average number of draws to reach tenpai, over the games that reached tenpai
+    pub fn avg_draws_to_tenpai(&self) -> Option<f64> {
+        (self.num_tenpai > 0).then(|| self.total_draws_to_tenpai as f64 / self.num_tenpai as f64)
+    }
+
+    /// The average number of draws to win, over the games that won
+    pub fn avg_draws_to_win(&self) -> Option<f64> {
+        (self.num_wins > 0).then(|| self.total_draws_to_win as f64 / self.num_wins as f64)
+    }
+}
+
+/// All 136 tiles of a standard riichi mahjong set (including the red fives), in a random order
+pub fn shuffled_wall(rng: &mut StdRng) -> Vec<tiles::Tile> {
+    let mut wall: Vec<tiles::Tile> = (0..tiles::NUM_TILES)
+        .map(|serial| tiles::Tile { serial })
+        .collect();
+    wall.shuffle(rng);
+    wall
+}
+
+/// Plays a single-player game: deals 13 tiles from the wall, then draws one tile at a time (tiles are taken from the
+/// end of the wall) and discards the tile that minimizes shanten then maximizes the live ukiere
+/// (see `shanten::get_best_discard_by_ukiere()`), until the hand wins or the wall runs out.
+pub fn play_game(wall: &Vec<tiles::Tile>) -> SolitaireResult {
+    let mut tile_wall = wall.clone();
+    let mut hand_tiles: Vec<tiles::Tile> = Vec::new();
+    while hand_tiles.len() < 13 {
+        hand_tiles.push(tile_wall.pop().expect("Wall must have at least 13 tiles"));
+    }
+
+    let mut discards: Vec<tiles::Tile> = Vec::new();
+    let mut draws_to_tenpai = (shanten::get_shanten(&hand_tiles) == 0).then_some(0);
+    let mut draws = 0;
+    while let Some(drawn_tile) = tile_wall.pop() {
+        hand_tiles.push(drawn_tile);
+        draws += 1;
+        if shanten::is_winning_hand(&hand_tiles) {
+            return SolitaireResult {
+                draws_to_tenpai: draws_to_tenpai.or(Some(draws)),
+                draws_to_win: Some(draws),
+            };
+        }
+
+        let discard_tile = shanten::get_best_discard_by_ukiere(&hand_tiles, &discards);
+        let discard_index = hand_tiles
+            .iter()
+            .position(|tile| tile.serial == discard_tile.serial)
+            .expect("Discarded tile must be in the hand");
+        discards.push(hand_tiles.swap_remove(discard_index));
+        if draws_to_tenpai.is_none() && shanten::get_shanten(&hand_tiles) == 0 {
+            draws_to_tenpai = Some(draws);
+        }
+    }
+    SolitaireResult {
+        draws_to_tenpai,
+        draws_to_win: None,
+    }
+}
+
+/// Plays many single-player games, each on a newly shuffled wall, and totals the draws to reach tenpai and to win
+pub fn run_trials(num_trials: u32, rng: &mut StdRng) -> SolitaireStats {
+    let mut stats = SolitaireStats {
+        num_trials,
+        ..Default::default()
+    };
+    for _ in 0..num_trials {
+        let result = play_game(&shuffled_wall(rng));
+        if let Some(draws_to_tenpai) = result.draws_to_tenpai {
+            stats.num_tenpai += 1;
+            stats.total_draws_to_tenpai += draws_to_tenpai;
+        }
+        if let Some(draws_to_win) = result.draws_to_win {
+            stats.num_wins += 1;
+            stats.total_draws_to_win += draws_to_win;
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A wall that deals the hand, then draws the tiles in order (the wall is drawn from the end)
+    fn wall_from_strings(hand_string: &str, draws_string: &str) -> Vec<tiles::Tile> {
+        let mut wall = tiles::tiles_from_string(hand_string);
+        wall.extend(tiles::tiles_from_string(draws_string));
+        wall.reverse();
+        wall
+    }
+
+    #[test]
+    fn test_play_game_favorable_wall() {
+        // 1-shanten: 123m 456p 34s 78s 55z 9m
+        let wall = wall_from_strings("1239m456p3478s55z", "1z2s7z5z2p6s");
+        let result = play_game(&wall);
+        // drawing 2s reaches tenpai on 6s-9s (123m 456p 234s 78s 55z), and drawing 6s wins
+        assert_eq!(result.draws_to_tenpai, Some(2));
+        assert_eq!(result.draws_to_win, Some(6));
+        assert!(result.draws_to_win.is_some_and(|draws| draws <= 18));
+    }
+
+    #[test]
+    fn test_play_game_wall_runs_out() {
+        // 13 orphans tiles, and no useful draws
+        let wall = wall_from_strings("19m19p19s1234567z", "");
+        assert_eq!(
+            play_game(&wall),
+            SolitaireResult {
+                draws_to_tenpai: Some(0),
+                draws_to_win: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_run_trials() {
+        let mut rng = StdRng::seed_from_u64(552);
+        let stats = run_trials(5, &mut rng);
+        assert_eq!(stats.num_trials, 5);
+        assert!(stats.num_wins <= stats.num_tenpai);
+        // with the whole wall to draw from, every game reaches tenpai
+        assert_eq!(stats.num_tenpai, 5);
+        assert!(
+            stats.avg_draws_to_tenpai().unwrap() <= stats.avg_draws_to_win().unwrap_or(f64::MAX)
+        );
+    }
+}
@@ -38,10 +38,10 @@ impl Dragon {
 
 #[derive(Copy, Clone)]
 pub enum WinningTileSource {
-    // i.e. ron
-    Discard,
-    // i.e. tsumo
-    SelfDraw,
+    // i.e. ron. A discard in the first go-around (before the player's first draw, with no calls made) can be renhou
+    Discard { is_first_go_around: bool },
+    // i.e. tsumo. A draw in the first go-around (the player's first draw, with no calls made) can be tenhou or chiihou
+    SelfDraw { is_first_go_around: bool },
     // i.e. after the player calls a kan
     DeadWall,
     // i.e. winning off of another player's added kan (i.e. they had an open triplet and drew the fourth copy)
@@ -49,9 +49,99 @@ pub enum WinningTileSource {
     RobbingKan,
 }
 
-/// A new hand begins with a new set of initial tiles (haipai). Multiple hands make up a wind round, 
+/// The type of kan (quad) declared by a player
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum KanType {
+    /// a closed kan, with all four tiles drawn by the player
+    Ankan,
+    /// an open kan, called from another player's discard when holding a concealed triplet (aka daiminkan)
+    Minkan,
+    /// an open kan, adding a drawn tile to an open triplet (aka kakan)
+    Shouminkan,
+}
+
+/// The type of call used to declare a meld
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MeldType {
+    Chi,
+    Pon,
+    Kan(KanType),
+}
+
+/// A meld declared by a player, including which player the called tile came from.
+/// Used for fu (a closed kan earns more fu than an open kan) and for pao (liability for a yakuman).
+#[derive(Clone)]
+pub struct CalledMeld {
+    pub meld_type: MeldType,
+    /// The tiles of the meld (3 tiles, or 4 tiles for a kan)
+    pub tiles: Vec<tiles::Tile>,
+    /// The seat of the player whose discard was called (for a shouminkan, the discard called for the original pon).
+    /// None for an ankan, since no tile was called.
+    pub called_from: Option<WindDirection>,
+}
+
+impl CalledMeld {
+    /// Constructs a meld, or returns an Err if the tiles don't form the meld, or only an ankan is missing `called_from`
+    pub fn new(
+        meld_type: MeldType,
+        tiles: Vec<tiles::Tile>,
+        called_from: Option<WindDirection>,
+    ) -> Result<Self, &'static str> {
+        let called_meld = Self {
+            meld_type,
+            tiles,
+            called_from,
+        };
+        let num_meld_tiles = if matches!(meld_type, MeldType::Kan(_)) {
+            4
+        } else {
+            3
+        };
+        if called_meld.tiles.len() != num_meld_tiles {
+            return Err("Wrong number of tiles in meld!");
+        }
+        if called_meld.is_closed() == called_meld.called_from.is_some() {
+            return Err("Only an ankan has no player that the tile was called from!");
+        }
+        if !called_meld.tile_group().is_valid() {
+            return Err("Invalid tiles for meld!");
+        }
+        Ok(called_meld)
+    }
+
+    /// Whether the meld is closed (only an ankan): a hand with only closed melds is still a closed hand
+    pub fn is_closed(&self) -> bool {
+        self.meld_type == MeldType::Kan(KanType::Ankan)
+    }
+
+    /// The meld as a tile group (e.g. for hand grouping, yaku, and fu)
+    pub fn tile_group(&self) -> tiles::TileGroup {
+        match self.meld_type {
+            MeldType::Chi => {
+                let mut sequence_tiles = [self.tiles[0], self.tiles[1], self.tiles[2]];
+                sequence_tiles.sort_by_key(|tile| tile.sequence_rank_num());
+                tiles::TileGroup::Sequence {
+                    open: true,
+                    tiles: sequence_tiles,
+                }
+            }
+            MeldType::Pon => tiles::TileGroup::Triplet {
+                open: true,
+                tiles: [self.tiles[0], self.tiles[1], self.tiles[2]],
+            },
+            MeldType::Kan(kan_type) => tiles::TileGroup::Quad {
+                open: kan_type != KanType::Ankan,
+                added: kan_type == KanType::Shouminkan,
+                tiles: [self.tiles[0], self.tiles[1], self.tiles[2], self.tiles[3]],
+            },
+        }
+    }
+}
+
+/// A new hand begins with a new set of initial tiles (haipai). Multiple hands make up a wind round,
 /// and an entire game may consist of multiple wind rounds.
 /// Not to be confused with a player's hand, which is a set of tiles that belong to a specific player.
+#[derive(Clone)]
 pub struct HandState {
     /// The round wind direction. used for yaku (yakuhai)
     pub round_wind: WindDirection,
@@ -61,12 +151,15 @@ pub struct HandState {
     pub tiles_remaining: u32,
     /// The dora indicator tiles in the wall
     pub dora_indicators: Vec<tiles::Tile>,
+    /// The ura dora indicator tiles (under the dora indicators), only revealed for a player that wins after declaring riichi
+    pub ura_dora_indicators: Vec<tiles::Tile>,
     /// The number of riichi sticks that have been placed so far in this hand. used for scoring
     pub riichi_sticks: u32,
     /// The number of honba sticks for this hand. used for scoring
     pub honba_sticks: u32,
 }
 
+#[derive(Clone)]
 pub struct PlayerState {
     /// The ordered list of this player's discards. Includes tiles called by other players.
     /// Used for furiten, as well as nagashi mangan
@@ -78,6 +171,9 @@ pub struct PlayerState {
     pub in_riichi: bool,
     /// Whether the player's hand is in double riichi. used for scoring
     pub in_double_riichi: bool,
+    /// Whether the player declared riichi as an open riichi (revealing their hand). used for scoring,
+    /// as a local yaku (see `scoring::LocalYakuConfig`)
+    pub in_open_riichi: bool,
     /// Whether the player's hand is eligible for ippatsu (i.e. in riichi or double riichi,
     /// and no tile calls have been made and the player has not discarded a tile). used for scoring
     pub in_ippatsu_turn: bool,
@@ -88,3 +184,457 @@ pub struct PlayerState {
     /// Set to the player's winning tile source (if any). used for scoring, and certain yaku
     pub winning_tile_source: Option<WinningTileSource>,
 }
+
+/// Whether declaring riichi is a double riichi: riichi must be declared with the player's first discard,
+/// and no calls can have been made by any player before that discard
+pub fn is_double_riichi(is_first_discard: bool, any_calls_made: bool) -> bool {
+    is_first_discard && !any_calls_made
+}
+
+impl HandState {
+    /// The dora tiles indicated by this hand's dora indicators
+    pub fn dora_tiles(&self) -> Vec<tiles::Tile> {
+        tiles::dora_tiles_from_indicators(&self.dora_indicators)
+    }
+}
+
+impl PlayerState {
+    /// Whether declaring riichi with this player's next discard would be a double riichi
+    pub fn can_declare_double_riichi(&self, hand_state: &HandState) -> bool {
+        is_double_riichi(self.discards.is_empty(), hand_state.any_calls_made)
+    }
+}
+
+/// The number of players in a game
+pub const NUM_PLAYERS: usize = 4;
+/// The number of tiles in the live wall at the start of a hand: 136 tiles, minus the 14 tiles of the dead wall
+/// and the 13 tiles dealt to each player
+pub const NUM_LIVE_WALL_TILES: u32 = 136 - 14 - 13 * NUM_PLAYERS as u32;
+/// The maximum number of dora indicators (the first indicator, and one for each of up to four kans)
+pub const MAX_DORA_INDICATORS: usize = 5;
+
+/// The public state of a hand in progress: the tiles left in the live wall, the revealed dora indicators,
+/// and each player's discards and called melds. Players are indexed by seat (East is 0, South is 1, etc.).
+#[derive(Clone)]
+pub struct GameState {
+    /// The player whose turn it is (i.e. the next player to draw or discard)
+    pub current_player: usize,
+    /// how many tiles are left in the live wall
+    pub tiles_remaining: u32,
+    /// The revealed dora indicator tiles
+    pub dora_indicators: Vec<tiles::Tile>,
+    /// The ordered list of each player's discards. Includes tiles called by other players (same as `PlayerState`).
+    pub discards: [Vec<tiles::Tile>; NUM_PLAYERS],
+    /// The melds declared by each player
+    pub called_melds: [Vec<CalledMeld>; NUM_PLAYERS],
+    /// The discards that were called by other players (these tiles are in both the discards and the called melds)
+    called_discards: Vec<tiles::Tile>,
+    /// The last discard and the player that discarded it, if it can still be called
+    last_discard: Option<(usize, tiles::Tile)>,
+}
+
+impl GameState {
+    /// The state at the start of a hand (after the tiles are dealt), with the first dora indicator revealed
+    pub fn new(dora_indicator: tiles::Tile) -> Self {
+        Self {
+            current_player: 0,
+            tiles_remaining: NUM_LIVE_WALL_TILES,
+            dora_indicators: vec![dora_indicator],
+            discards: Default::default(),
+            called_melds: Default::default(),
+            called_discards: Vec::new(),
+            last_discard: None,
+        }
+    }
+
+    /// The current player draws a tile from the live wall. Returns an Err if the live wall is empty.
+    pub fn draw(&mut self) -> Result<(), &'static str> {
+        if self.tiles_remaining == 0 {
+            return Err("The live wall is empty!");
+        }
+        self.tiles_remaining -= 1;
+        self.last_discard = None;
+        Ok(())
+    }
+
+    /// The current player discards the tile, then it's the next player's turn
+    pub fn discard(&mut self, tile: tiles::Tile) {
+        self.discards[self.current_player].push(tile);
+        self.last_discard = Some((self.current_player, tile));
+        self.current_player = (self.current_player + 1) % NUM_PLAYERS;
+    }
+
+    /// Reveals a new dora indicator (after a kan). Returns an Err if all of the dora indicators are already revealed.
+    pub fn reveal_dora(&mut self, dora_indicator: tiles::Tile) -> Result<(), &'static str> {
+        if self.dora_indicators.len() >= MAX_DORA_INDICATORS {
+            return Err("All of the dora indicators are already revealed!");
+        }
+        self.dora_indicators.push(dora_indicator);
+        Ok(())
+    }
+
+    /// How many tiles are left in the live wall
+    pub fn remaining_wall(&self) -> u32 {
+        self.tiles_remaining
+    }
+
+    /// The player declares the meld, then it's their turn. A chi, pon, or minkan must call the last discard
+    /// (from the player in `called_from`), and a shouminkan replaces the player's pon of the same tile.
+    /// After a kan, the player draws a replacement tile from the dead wall, which takes a tile from the live wall.
+    pub fn call_meld(
+        &mut self,
+        player: usize,
+        called_meld: CalledMeld,
+    ) -> Result<(), &'static str> {
+        match called_meld.meld_type {
+            MeldType::Chi | MeldType::Pon | MeldType::Kan(KanType::Minkan) => {
+                let (discarder, called_tile) =
+                    self.last_discard.ok_or("There is no discard to call!")?;
+                let is_called_tile_in_meld = called_meld
+                    .tiles
+                    .iter()
+                    .any(|tile| tile.to_string() == called_tile.to_string());
+                let called_from = called_meld.called_from.map(|seat| seat as usize - 1);
+                if !is_called_tile_in_meld || called_from != Some(discarder) || discarder == player
+                {
+                    return Err("Called tile must be another player's last discard!");
+                }
+                self.called_discards.push(called_tile);
+                self.last_discard = None;
+            }
+            MeldType::Kan(KanType::Shouminkan) => {
+                if player != self.current_player {
+                    return Err("Only the current player can declare a closed or added kan!");
+                }
+                let kind_index = called_meld.tiles[0].kind_index();
+                let pon_index = self.called_melds[player]
+                    .iter()
+                    .position(|meld| {
+                        meld.meld_type == MeldType::Pon && meld.tiles[0].kind_index() == kind_index
+                    })
+                    .ok_or("Shouminkan must add to the player's pon!")?;
+                self.called_melds[player].remove(pon_index);
+            }
+            MeldType::Kan(KanType::Ankan) => {
+                if player != self.current_player {
+                    return Err("Only the current player can declare a closed or added kan!");
+                }
+            }
+        }
+        if matches!(called_meld.meld_type, MeldType::Kan(_)) {
+            self.tiles_remaining = self
+                .tiles_remaining
+                .checked_sub(1)
+                .ok_or("The live wall is empty!")?;
+        }
+        self.called_melds[player].push(called_meld);
+        self.current_player = player;
+        Ok(())
+    }
+
+    /// All of the tiles that are visible to every player: the dora indicators, the discards, and the tiles of
+    /// the called melds (each called discard is only counted once). Can be used as the visible tiles for
+    /// the shanten functions (e.g. `shanten::get_ukiere_with_counts()`).
+    pub fn visible_tiles(&self) -> Vec<tiles::Tile> {
+        let mut visible_tiles: Vec<tiles::Tile> = self.dora_indicators.clone();
+        for player in 0..NUM_PLAYERS {
+            visible_tiles.extend(self.discards[player].iter());
+            for called_meld in self.called_melds[player].iter() {
+                visible_tiles.extend(called_meld.tiles.iter());
+            }
+        }
+        for called_tile in self.called_discards.iter() {
+            let visible_index = visible_tiles
+                .iter()
+                .position(|tile| tile.to_string() == called_tile.to_string())
+                .expect("Called discard must be visible");
+            visible_tiles.remove(visible_index);
+        }
+        visible_tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_double_riichi() {
+        assert!(is_double_riichi(true, false));
+        // not the player's first discard
+        assert!(!is_double_riichi(false, false));
+        // a call was made before the player's first discard
+        assert!(!is_double_riichi(true, true));
+        assert!(!is_double_riichi(false, true));
+    }
+
+    #[test]
+    fn test_can_declare_double_riichi() {
+        let mut hand_state = HandState {
+            round_wind: WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 69,
+            dora_indicators: vec![tiles::Tile::from_string("3p")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let mut player_state = PlayerState {
+            discards: vec![],
+            seat_wind: WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: None,
+        };
+        assert!(player_state.can_declare_double_riichi(&hand_state));
+
+        hand_state.any_calls_made = true;
+        assert!(!player_state.can_declare_double_riichi(&hand_state));
+
+        hand_state.any_calls_made = false;
+        player_state.discards.push(tiles::Tile::from_string("9m"));
+        assert!(!player_state.can_declare_double_riichi(&hand_state));
+    }
+
+    #[test]
+    fn test_called_meld() {
+        let chi = CalledMeld::new(
+            MeldType::Chi,
+            tiles::tiles_from_string("435s"),
+            Some(WindDirection::North),
+        )
+        .expect("Valid chi");
+        assert!(!chi.is_closed());
+        assert!(matches!(
+            chi.tile_group(),
+            tiles::TileGroup::Sequence { open: true, .. }
+        ));
+        assert_eq!(chi.tile_group().to_tiles_string(), "3s,4s,5s");
+
+        let pon = CalledMeld::new(
+            MeldType::Pon,
+            tiles::tiles_from_string("777z"),
+            Some(WindDirection::East),
+        )
+        .expect("Valid pon");
+        assert!(!pon.is_closed());
+        assert!(matches!(
+            pon.tile_group(),
+            tiles::TileGroup::Triplet { open: true, .. }
+        ));
+
+        let ankan = CalledMeld::new(
+            MeldType::Kan(KanType::Ankan),
+            tiles::tiles_from_string("1111m"),
+            None,
+        )
+        .expect("Valid ankan");
+        assert!(ankan.is_closed());
+        assert!(matches!(
+            ankan.tile_group(),
+            tiles::TileGroup::Quad {
+                open: false,
+                added: false,
+                ..
+            }
+        ));
+
+        let minkan = CalledMeld::new(
+            MeldType::Kan(KanType::Minkan),
+            tiles::tiles_from_string("5055p"),
+            Some(WindDirection::West),
+        )
+        .expect("Valid minkan");
+        assert!(!minkan.is_closed());
+        assert!(matches!(
+            minkan.tile_group(),
+            tiles::TileGroup::Quad {
+                open: true,
+                added: false,
+                ..
+            }
+        ));
+
+        let shouminkan = CalledMeld::new(
+            MeldType::Kan(KanType::Shouminkan),
+            tiles::tiles_from_string("9999s"),
+            Some(WindDirection::South),
+        )
+        .expect("Valid shouminkan");
+        assert!(!shouminkan.is_closed());
+        assert!(matches!(
+            shouminkan.tile_group(),
+            tiles::TileGroup::Quad {
+                open: true,
+                added: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_called_meld_invalid() {
+        // wrong number of tiles
+        assert!(CalledMeld::new(
+            MeldType::Pon,
+            tiles::tiles_from_string("7777z"),
+            Some(WindDirection::East)
+        )
+        .is_err());
+        // tiles don't form the meld
+        assert!(CalledMeld::new(
+            MeldType::Chi,
+            tiles::tiles_from_string("357s"),
+            Some(WindDirection::North)
+        )
+        .is_err());
+        assert!(CalledMeld::new(
+            MeldType::Chi,
+            tiles::tiles_from_string("123z"),
+            Some(WindDirection::North)
+        )
+        .is_err());
+        // an ankan isn't called from anyone, and every other meld is
+        assert!(CalledMeld::new(
+            MeldType::Kan(KanType::Ankan),
+            tiles::tiles_from_string("1111m"),
+            Some(WindDirection::East)
+        )
+        .is_err());
+        assert!(CalledMeld::new(MeldType::Pon, tiles::tiles_from_string("777z"), None).is_err());
+    }
+
+    #[test]
+    fn test_hand_state_dora_tiles() {
+        let hand_state = HandState {
+            round_wind: WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 50,
+            dora_indicators: tiles::tiles_from_string("3p9s"),
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let dora_strings: Vec<String> = hand_state
+            .dora_tiles()
+            .iter()
+            .map(|tile| tile.to_string())
+            .collect();
+        assert_eq!(dora_strings, vec!["4p", "1s"]);
+    }
+
+    #[test]
+    fn test_game_state_turns() {
+        let tile_strings = |tiles: Vec<tiles::Tile>| -> Vec<String> {
+            tiles.iter().map(|tile| tile.to_string()).collect()
+        };
+        let mut game_state = GameState::new(tiles::Tile::from_string("3p"));
+        assert_eq!(game_state.remaining_wall(), 70);
+
+        // each player draws and discards once
+        for discard_string in ["1z", "9m", "5s", "2z"] {
+            game_state.draw().unwrap();
+            game_state.discard(tiles::Tile::from_string(discard_string));
+        }
+        assert_eq!(game_state.remaining_wall(), 66);
+        assert_eq!(game_state.current_player, 0);
+        assert_eq!(tile_strings(game_state.discards[1].clone()), vec!["9m"]);
+
+        // East discards 7z, and West pons it, then discards 4m
+        game_state.draw().unwrap();
+        game_state.discard(tiles::Tile::from_string("7z"));
+        let pon = CalledMeld::new(
+            MeldType::Pon,
+            tiles::tiles_from_string("777z"),
+            Some(WindDirection::East),
+        )
+        .unwrap();
+        game_state.call_meld(2, pon).unwrap();
+        assert_eq!(game_state.current_player, 2);
+        game_state.discard(tiles::Tile::from_string("4m"));
+        assert_eq!(game_state.remaining_wall(), 65);
+
+        // the called 7z is only counted once
+        assert_eq!(
+            tile_strings(game_state.visible_tiles()),
+            vec!["3p", "1z", "9m", "5s", "4m", "7z", "7z", "7z", "2z"]
+        );
+
+        // North can't pon a discard from before West's discard
+        let late_pon = CalledMeld::new(
+            MeldType::Pon,
+            tiles::tiles_from_string("999m"),
+            Some(WindDirection::South),
+        )
+        .unwrap();
+        assert!(game_state.call_meld(3, late_pon).is_err());
+
+        // West draws the fourth 7z and adds it to the pon, which takes a replacement tile and reveals a new dora
+        game_state.draw().unwrap();
+        game_state.discard(tiles::Tile::from_string("8p"));
+        game_state.draw().unwrap();
+        game_state.discard(tiles::Tile::from_string("6s"));
+        game_state.draw().unwrap();
+        game_state.discard(tiles::Tile::from_string("3s"));
+        game_state.draw().unwrap();
+        let shouminkan = CalledMeld::new(
+            MeldType::Kan(KanType::Shouminkan),
+            tiles::tiles_from_string("7777z"),
+            Some(WindDirection::East),
+        )
+        .unwrap();
+        game_state.call_meld(2, shouminkan).unwrap();
+        game_state
+            .reveal_dora(tiles::Tile::from_string("1s"))
+            .unwrap();
+        assert_eq!(game_state.remaining_wall(), 60);
+        assert_eq!(game_state.called_melds[2].len(), 1);
+        let visible_tiles = game_state.visible_tiles();
+        assert_eq!(visible_tiles.len(), 14);
+        assert_eq!(
+            visible_tiles
+                .iter()
+                .filter(|tile| tile.to_string() == "7z")
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_game_state_limits() {
+        let mut game_state = GameState::new(tiles::Tile::from_string("1m"));
+        for _ in 0..4 {
+            game_state
+                .reveal_dora(tiles::Tile::from_string("2m"))
+                .unwrap();
+        }
+        assert!(game_state
+            .reveal_dora(tiles::Tile::from_string("3m"))
+            .is_err());
+
+        for _ in 0..NUM_LIVE_WALL_TILES {
+            game_state.draw().unwrap();
+        }
+        assert_eq!(game_state.remaining_wall(), 0);
+        assert!(game_state.draw().is_err());
+        // there's no discard to call
+        let chi = CalledMeld::new(
+            MeldType::Chi,
+            tiles::tiles_from_string("123m"),
+            Some(WindDirection::North),
+        )
+        .unwrap();
+        assert!(game_state.call_meld(0, chi).is_err());
+        // it's East's turn, so South can't declare an ankan
+        let ankan = CalledMeld::new(
+            MeldType::Kan(KanType::Ankan),
+            tiles::tiles_from_string("1111z"),
+            None,
+        )
+        .unwrap();
+        assert!(game_state.call_meld(1, ankan).is_err());
+    }
+}
@@ -1,5 +1,22 @@
 use crate::tiles;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Whether to trace the tile grouping search (set the RIICHI_BOT_TRACE environment variable to enable)
+fn trace_enabled() -> bool {
+    static TRACE_ENABLED: OnceLock<bool> = OnceLock::new();
+    *TRACE_ENABLED.get_or_init(|| std::env::var_os("RIICHI_BOT_TRACE").is_some())
+}
+
+/// Prints a trace of the tile grouping search to stderr, if enabled (see `trace_enabled()`), so that it doesn't mix
+/// with the output of the command line interface
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if trace_enabled() {
+            eprintln!($($arg)*);
+        }
+    };
+}
 
 pub fn count_tiles_by_suit_rank(
     tiles: &Vec<tiles::Tile>,
@@ -32,6 +49,62 @@ pub fn count_tiles_by_suit_rank(
     tile_counts_by_suit
 }
 
+/// A compact fingerprint of the hand's shape, which is invariant under relabeling the three numbered suits
+/// (e.g. a shape in man and the same shape in sou map to the same fingerprint), since the shape of a hand
+/// (shanten, waits, etc.) doesn't depend on which numbered suit the tiles are in.
+/// Red fives are treated the same as normal fives. Honor tiles are not relabeled.
+pub fn hand_fingerprint(tiles: &Vec<tiles::Tile>) -> String {
+    let tile_counts_by_suit = count_tiles_by_suit_rank(tiles, true);
+
+    // the count of each rank (1-9) within each numbered suit, as a string of digits
+    let mut number_suit_fingerprints: Vec<String> = Vec::new();
+    for tile_suit in [
+        tiles::TileSuit::Man,
+        tiles::TileSuit::Pin,
+        tiles::TileSuit::Sou,
+    ] {
+        let mut suit_fingerprint = String::new();
+        for rank in 1..=9 {
+            let tile_rank = tiles::TileRank::Number(
+                tiles::NumberTileRank::try_from(
+                    char::from_digit(rank, 10).expect("Valid rank integer for char"),
+                )
+                .expect("valid tile rank"),
+            );
+            let tile_count = tile_counts_by_suit
+                .get(&tile_suit)
+                .and_then(|tile_counts| tile_counts.get(&tile_rank))
+                .unwrap_or(&0);
+            suit_fingerprint.push(
+                char::from_digit(*tile_count, 10).expect("Valid tile count integer for char"),
+            );
+        }
+        number_suit_fingerprints.push(suit_fingerprint);
+    }
+    // sorting the numbered suits is what makes the fingerprint invariant under relabeling the suits
+    number_suit_fingerprints.sort();
+
+    // the count of each honor tile (east, south, west, north, white, green, red)
+    let mut honor_fingerprint = String::new();
+    for rank in 1..=7 {
+        let tile_rank = tiles::TileRank::Honor(
+            tiles::HonorTileRank::try_from(
+                char::from_digit(rank, 10).expect("Valid rank integer for char"),
+            )
+            .expect("valid tile rank"),
+        );
+        let tile_count = tile_counts_by_suit
+            .get(&tiles::TileSuit::Honor)
+            .and_then(|tile_counts| tile_counts.get(&tile_rank))
+            .unwrap_or(&0);
+        honor_fingerprint
+            .push(char::from_digit(*tile_count, 10).expect("Valid tile count integer for char"));
+    }
+
+    number_suit_fingerprints.push(honor_fingerprint);
+    number_suit_fingerprints.join("|")
+}
+
 pub fn first_copy_index(
     tiles: &Vec<tiles::Tile>,
     tile_rank: tiles::TileRank,
@@ -49,6 +122,7 @@ pub fn first_copy_index(
             if tile.rank() == tile_rank {
                 return Some(tile_idx);
             } else if ignore_red_fives
+                && tile_rank == tiles::TileRank::Number(tiles::NumberTileRank::Five)
                 && tile.rank() == tiles::TileRank::Number(tiles::NumberTileRank::RedFive)
             {
                 return Some(tile_idx);
@@ -130,6 +204,42 @@ pub fn seven_pairs_tile_grouping(
     Some(new_groups)
 }
 
+/// All of the ways to group the winning hand: its standard groupings (see `tile_grouping()`), and its seven pairs grouping
+/// (see `seven_pairs_tile_grouping()`) if it has one. Some hands (e.g. 223344m556677p88s) have both.
+/// Returns an empty list if the tiles aren't a winning hand (or can only be thirteen orphans).
+pub fn all_winning_groupings(
+    tiles: &Vec<tiles::Tile>,
+    tile_groups: &Vec<tiles::TileGroup>,
+) -> Vec<Vec<tiles::TileGroup>> {
+    let mut groupings = tile_grouping(tiles, tile_groups).unwrap_or_default();
+    if let Some(seven_pairs_grouping) = seven_pairs_tile_grouping(tiles, tile_groups) {
+        groupings.push(seven_pairs_grouping);
+    }
+    groupings
+}
+
+/// All of the ways to decompose the winning hand into groups: the player's tiles (not including the declared groups,
+/// i.e. called groups and kans) plus the winning tile, with the declared groups. Each decomposition is listed once
+/// (see `all_winning_groupings()`), so that callers can score every interpretation of the hand, e.g. 111222333m
+/// can be grouped as three triplets or as three identical sequences (iipeikou).
+/// Returns an empty list if the tiles aren't a winning hand (or can only be thirteen orphans).
+pub fn decompose_winning_hand(
+    hand_tiles: &Vec<tiles::Tile>,
+    declared_groups: &Vec<tiles::TileGroup>,
+    winning_tile: &tiles::Tile,
+) -> Vec<Vec<tiles::TileGroup>> {
+    let mut winning_tiles = hand_tiles.clone();
+    winning_tiles.push(*winning_tile);
+    let mut decompositions: Vec<Vec<tiles::TileGroup>> = Vec::new();
+    let mut decomposition_strings: HashSet<String> = HashSet::new();
+    for grouping in all_winning_groupings(&winning_tiles, declared_groups) {
+        if decomposition_strings.insert(get_tile_groups_string(&grouping)) {
+            decompositions.push(grouping);
+        }
+    }
+    decompositions
+}
+
 pub fn tile_grouping(
     tiles: &Vec<tiles::Tile>,
     tile_groups: &Vec<tiles::TileGroup>,
@@ -140,17 +250,17 @@ pub fn tile_grouping(
 
     // example: if the parameter partial_hand contains a pair already, and the only way to use a tile is in a pair, then this function would return none
 
-    println!("{} remaining tiles: {:?}", tiles.len(), tiles);
+    trace!("{} remaining tiles: {:?}", tiles.len(), tiles);
 
     if tiles.is_empty() {
         // println!("partial hand so far:");
         // println!("pair tile: {:?}", tiles::get_pair_group(hand_groups));
         // println!("melds: {:?}", hand_groups);
         if tiles::get_pair_group(tile_groups).is_some() && tile_groups.len() == 5 {
-            println!("found winning hand: {:?}", tile_groups);
+            trace!("found winning hand: {:?}", tile_groups);
             return Some(vec![tile_groups.to_vec()]);
         } else {
-            println!(
+            trace!(
                 "invalid hand with no tiles remaining, num melds = {}, pair_tile = {:?}",
                 tile_groups.len(),
                 tiles::get_pair_group(tile_groups)
@@ -173,17 +283,17 @@ pub fn tile_grouping(
         };
         if !candidate_pair.is_valid() {
             // invalid pair (i.e. not the same tile rank and suit) -> not winning hand
-            println!("invalid grouping: last two tiles aren't a pair");
+            trace!("invalid grouping: last two tiles aren't a pair");
             return None;
         }
         if tiles::get_pair_group(tile_groups).is_some() {
             // already has a pair -> not winning hand
-            println!("invalid grouping: already have one pair");
+            trace!("invalid grouping: already have one pair");
             return None;
         }
         let mut new_groups = tile_groups.to_vec();
         new_groups.push(candidate_pair);
-        println!("found winning hand: {:?}", new_groups);
+        trace!("found winning hand: {:?}", new_groups);
         return Some(vec![new_groups]);
     }
 
@@ -208,12 +318,12 @@ pub fn tile_grouping(
             }
             if tile_count == &1 {
                 // isolated honor tile -> not winning hand
-                println!("isolated honor tile {considered_tile_str}");
+                trace!("isolated honor tile {considered_tile_str}");
                 return None;
             } else if tile_count == &2 {
                 if tiles::get_pair_group(tile_groups).is_some() {
                     // honor tile must be the pair, but can only have one pair in the winning hand
-                    println!("pair of honor tile {considered_tile_str} but already have a pair");
+                    trace!("pair of honor tile {considered_tile_str} but already have a pair");
                     return None;
                 } else {
                     // remove all copies of this tile, honor tiles can't be used in sequences, so there's
@@ -327,7 +437,7 @@ pub fn tile_grouping(
                     panic!("Should have only three or four tiles!");
                 }
             } else {
-                println!("impossible, cannot be more than 4 tiles!");
+                trace!("impossible, cannot be more than 4 tiles!");
                 return None;
             }
         }
@@ -429,7 +539,7 @@ pub fn tile_grouping(
                         // recursive call
                         let mut new_groups = tile_groups.clone();
                         new_groups.push(new_sequence_group);
-                        println!(
+                        trace!(
                             "recursive call: using sequence starting at {}",
                             new_tile_str
                         );
@@ -437,7 +547,7 @@ pub fn tile_grouping(
                         if let Some(new_winning_hands) =
                             tile_grouping(&remaining_tiles, &new_groups)
                         {
-                            println!("found a winning hand using tile {}", new_tile_str);
+                            trace!("found a winning hand using tile {}", new_tile_str);
                             winning_hands.extend(new_winning_hands);
                         }
                     }
@@ -479,7 +589,7 @@ pub fn tile_grouping(
 
                         if !has_winning_hand_with_this_pair {
                             // recursive call
-                            println!("recursive call: using pair of {}", new_tile_str);
+                            trace!("recursive call: using pair of {}", new_tile_str);
 
                             let mut new_groups = tile_groups.clone();
                             new_groups.push(new_pair_group);
@@ -487,7 +597,7 @@ pub fn tile_grouping(
                             if let Some(new_winning_hands) =
                                 tile_grouping(&remaining_tiles, &new_groups)
                             {
-                                println!("found a winning hand using tile {}", new_tile_str);
+                                trace!("found a winning hand using tile {}", new_tile_str);
                                 winning_hands.extend(new_winning_hands);
                             }
                         }
@@ -533,7 +643,7 @@ pub fn tile_grouping(
 
                     if !has_winning_hand_with_triplet {
                         // recursive call
-                        println!("recursive call: using triplet of {}", new_tile_str);
+                        trace!("recursive call: using triplet of {}", new_tile_str);
 
                         let mut new_groups = tile_groups.clone();
                         new_groups.push(new_triplet_group);
@@ -541,7 +651,7 @@ pub fn tile_grouping(
                         if let Some(new_winning_hands) =
                             tile_grouping(&remaining_tiles, &new_groups)
                         {
-                            println!("found a winning hand using tile {}", new_tile_str);
+                            trace!("found a winning hand using tile {}", new_tile_str);
                             winning_hands.extend(new_winning_hands);
                         }
                     }
@@ -590,7 +700,7 @@ pub fn tile_grouping(
 
                     if !has_winning_hand_with_quad {
                         // recursive call
-                        println!("recursive call: using quad of {}", new_tile_str);
+                        trace!("recursive call: using quad of {}", new_tile_str);
 
                         let mut new_groups = tile_groups.clone();
                         new_groups.push(new_quad_group);
@@ -598,7 +708,7 @@ pub fn tile_grouping(
                         if let Some(new_winning_hands) =
                             tile_grouping(&remaining_tiles, &new_groups)
                         {
-                            println!("found a winning hand using tile {}", new_tile_str);
+                            trace!("found a winning hand using tile {}", new_tile_str);
                             winning_hands.extend(new_winning_hands);
                         }
                     }
@@ -607,7 +717,7 @@ pub fn tile_grouping(
                 // we have to use this tile in the winning hand somehow - if there's no winning hands at this point,
                 // then there are no winning hands at all
                 return if winning_hands.is_empty() {
-                    println!("invalid grouping: could not use tile {}", new_tile_str);
+                    trace!("invalid grouping: could not use tile {}", new_tile_str);
                     None
                 } else {
                     Some(winning_hands)
@@ -616,7 +726,7 @@ pub fn tile_grouping(
         }
     }
 
-    println!("invalid grouping: no more tiles");
+    trace!("invalid grouping: no more tiles");
     return None;
 }
 
@@ -707,7 +817,7 @@ pub fn tenpai_grouping(
 
     // can return multiple values if there are multiple valid groupings
 
-    println!(
+    trace!(
         "{} remaining tiles: {:?}, tile_groups: {:?}",
         tiles.len(),
         tiles,
@@ -721,41 +831,41 @@ pub fn tenpai_grouping(
     let num_wait_groups = number_wait_groups(tile_groups);
 
     if num_incomplete_groups > 2 {
-        println!("not tenpai hand: more than two incomplete groups");
+        trace!("not tenpai hand: more than two incomplete groups");
         return None;
     }
     if num_single_tile_groups > 1 {
-        println!("not tenpai hand: more than one single tile group");
+        trace!("not tenpai hand: more than one single tile group");
         return None;
     }
     if num_single_tile_groups == 1 && num_incomplete_groups > 1 {
-        println!(
+        trace!(
             "not tenpai hand: if there is a single-tile group, all other groups must be complete"
         );
         return None;
     }
     if num_wait_groups > 1 {
-        println!("not tenpai hand: more than one two-tile, non-pair wait group");
+        trace!("not tenpai hand: more than one two-tile, non-pair wait group");
         return None;
     }
     if num_wait_groups == 1 && (num_pair_groups > 1 || num_single_tile_groups > 0) {
-        println!(
+        trace!(
             "not tenpai hand: if there is a two-tile, non-pair wait group, there can only be one other incomplete group, and it must be a pair"
         );
         return None;
     }
     if num_incomplete_groups == 2 && num_pair_groups == 0 {
-        println!("not tenpai hand: two incomplete groups, but neither is a pair");
+        trace!("not tenpai hand: two incomplete groups, but neither is a pair");
         return None;
     }
     if num_pair_groups == 2 && (num_single_tile_groups > 0 || num_wait_groups > 0) {
-        println!(
+        trace!(
             "not tenpai hand: two pair groups, but there are other incomplete groups is a pair"
         );
         return None;
     }
     if num_pair_groups == 1 && (num_single_tile_groups > 0 || num_wait_groups > 1) {
-        println!("not tenpai hand: one pair group, there can only be one other two-tile, non-pair wait group");
+        trace!("not tenpai hand: one pair group, there can only be one other two-tile, non-pair wait group");
         return None;
     }
 
@@ -767,7 +877,7 @@ pub fn tenpai_grouping(
         // println!("pair tile: {:?}", tiles::get_pair_group(hand_groups));
         // println!("melds: {:?}", hand_groups);
         if num_incomplete_groups == 0 {
-            println!("not tenpai hand: no incomplete groups");
+            trace!("not tenpai hand: no incomplete groups");
             return None;
         } else if num_single_tile_groups >= 1 {
             if num_single_tile_groups == 1 {
@@ -776,14 +886,14 @@ pub fn tenpai_grouping(
                     && num_wait_groups == 0
                     && num_incomplete_groups == 1
                 {
-                    println!("found tenpai hand: {:?}", tile_groups);
+                    trace!("found tenpai hand: {:?}", tile_groups);
                     return Some(vec![tile_groups.to_vec()]);
                 } else {
-                    println!("not tenpai hand: one single-tile group must be with 4 complete groups (no other incomplete groups)");
+                    trace!("not tenpai hand: one single-tile group must be with 4 complete groups (no other incomplete groups)");
                     return None;
                 }
             } else {
-                println!("not tenpai hand: more than one single-tile group");
+                trace!("not tenpai hand: more than one single-tile group");
                 return None;
             }
         } else if num_wait_groups >= 1 {
@@ -793,16 +903,16 @@ pub fn tenpai_grouping(
                     && num_incomplete_groups == 2
                     && num_single_tile_groups == 0
                 {
-                    println!("found tenpai hand: {:?}", tile_groups);
+                    trace!("found tenpai hand: {:?}", tile_groups);
                     return Some(vec![tile_groups.to_vec()]);
                 } else {
-                    println!(
+                    trace!(
                         "not tenpai hand: one two-tile wait group must be with 3 complete groups and one pair group"
                     );
                     return None;
                 }
             } else {
-                println!("not tenpai hand: more than one two-tile wait group");
+                trace!("not tenpai hand: more than one two-tile wait group");
                 return None;
             }
         } else if num_pair_groups >= 1 {
@@ -812,10 +922,10 @@ pub fn tenpai_grouping(
                     && num_incomplete_groups == 2
                     && num_single_tile_groups == 0
                 {
-                    println!("found tenpai hand: {:?}", tile_groups);
+                    trace!("found tenpai hand: {:?}", tile_groups);
                     return Some(vec![tile_groups.to_vec()]);
                 } else {
-                    println!(
+                    trace!(
                         "not tenpai hand: one pair group must be with 3 complete groups and one two-tile wait group"
                     );
                     return None;
@@ -826,14 +936,14 @@ pub fn tenpai_grouping(
                     && num_incomplete_groups == 2
                     && num_single_tile_groups == 0
                 {
-                    println!("found tenpai hand: {:?}", tile_groups);
+                    trace!("found tenpai hand: {:?}", tile_groups);
                     return Some(vec![tile_groups.to_vec()]);
                 } else {
-                    println!("not tenpai hand: two pair groups must be with 3 complete groups (no other incomplete groups)");
+                    trace!("not tenpai hand: two pair groups must be with 3 complete groups (no other incomplete groups)");
                     return None;
                 }
             } else {
-                println!("not tenpai hand: more than two pair groups");
+                trace!("not tenpai hand: more than two pair groups");
                 return None;
             }
         } else {
@@ -864,7 +974,7 @@ pub fn tenpai_grouping(
                 // isolated honor tile
 
                 if must_be_complete_group || must_be_complete_group_or_pair {
-                    println!("cannot use isolated honor tile");
+                    trace!("cannot use isolated honor tile");
                     return None;
                 }
 
@@ -904,7 +1014,7 @@ pub fn tenpai_grouping(
                 // no way for a single honor tile type to be used in more than one meld/group
 
                 if must_be_complete_group {
-                    println!("cannot use pair of honor tiles");
+                    trace!("cannot use pair of honor tiles");
                     return None;
                 }
 
@@ -1015,7 +1125,7 @@ pub fn tenpai_grouping(
                     panic!("Should have only three or four tiles!");
                 }
             } else {
-                println!("impossible, cannot be more than 4 tiles!");
+                trace!("impossible, cannot be more than 4 tiles!");
                 return None;
             }
         }
@@ -1044,12 +1154,12 @@ pub fn tenpai_grouping(
                 if tile_count == 0 {
                     continue;
                 }
-                println!("considering tile {}", new_tile_str);
+                trace!("considering tile {}", new_tile_str);
                 let mut tenpai_hands: Vec<Vec<tiles::TileGroup>> = Vec::new();
 
                 if tile_count >= 4 {
                     // four copies of number tile can be used for quad
-                    println!("checking for quad of {new_tile_str}");
+                    trace!("checking for quad of {new_tile_str}");
 
                     // build remaining tiles by removing four copies of the tile
                     let remaining_tiles = tiles.clone();
@@ -1075,7 +1185,7 @@ pub fn tenpai_grouping(
                     };
 
                     // recursive call
-                    println!("recursive call: using quad of {}", new_tile_str);
+                    trace!("recursive call: using quad of {}", new_tile_str);
 
                     let mut new_groups = tile_groups.clone();
                     new_groups.push(new_quad_group);
@@ -1090,13 +1200,13 @@ pub fn tenpai_grouping(
                                 get_tile_groups_string(&new_tenpai_hand_grouping);
                             if !tenpai_hand_strings.contains(&new_tile_groups_string) {
                                 tenpai_hands.push(new_tenpai_hand_grouping.clone());
-                                println!(
+                                trace!(
                                     "found new tenpai grouping! {}, now there are {} tenpai_hands",
                                     new_tile_groups_string,
                                     tenpai_hands.len()
                                 );
                             } else {
-                                println!(
+                                trace!(
                                     "found duplicated tenpai grouping! {}",
                                     new_tile_groups_string
                                 );
@@ -1107,7 +1217,7 @@ pub fn tenpai_grouping(
 
                 if tile_count >= 3 {
                     // three copies of number tile can be used for triplet
-                    println!("checking for triplet of {new_tile_str}");
+                    trace!("checking for triplet of {new_tile_str}");
 
                     // build remaining tiles by removing three copies of the tile
                     let remaining_tiles = tiles.clone();
@@ -1129,7 +1239,7 @@ pub fn tenpai_grouping(
                     };
 
                     // recursive call
-                    println!("recursive call: using triplet of {}", new_tile_str);
+                    trace!("recursive call: using triplet of {}", new_tile_str);
 
                     let mut new_groups = tile_groups.clone();
                     new_groups.push(new_triplet_group);
@@ -1144,13 +1254,13 @@ pub fn tenpai_grouping(
                                 get_tile_groups_string(&new_tenpai_hand_grouping);
                             if !tenpai_hand_strings.contains(&new_tile_groups_string) {
                                 tenpai_hands.push(new_tenpai_hand_grouping.clone());
-                                println!(
+                                trace!(
                                     "found new tenpai grouping! {}, now there are {} tenpai_hands",
                                     new_tile_groups_string,
                                     tenpai_hands.len()
                                 );
                             } else {
-                                println!(
+                                trace!(
                                     "found duplicated tenpai grouping! {}",
                                     new_tile_groups_string
                                 );
@@ -1163,7 +1273,7 @@ pub fn tenpai_grouping(
                     // two copies of number tile can be used for pair
 
                     if must_be_complete_group {
-                        println!("cannot use pair of {} tiles", new_tile_str);
+                        trace!("cannot use pair of {} tiles", new_tile_str);
                     } else {
                         // build remaining tiles by removing two copies of the tile
                         let remaining_tiles = tiles.clone();
@@ -1183,7 +1293,7 @@ pub fn tenpai_grouping(
                         // println!("found pair of {}, remaining tiles: {:?}", new_tile_str, remaining_tiles);
 
                         // recursive call
-                        println!("recursive call: using pair of {}", new_tile_str);
+                        trace!("recursive call: using pair of {}", new_tile_str);
 
                         let mut new_groups = tile_groups.clone();
                         new_groups.push(new_pair_group);
@@ -1200,13 +1310,13 @@ pub fn tenpai_grouping(
                                     get_tile_groups_string(&new_tenpai_hand_grouping);
                                 if !tenpai_hand_strings.contains(&new_tile_groups_string) {
                                     tenpai_hands.push(new_tenpai_hand_grouping.clone());
-                                    println!(
+                                    trace!(
                                         "found new tenpai grouping! {}, now there are {} tenpai_hands",
                                         new_tile_groups_string,
                                         tenpai_hands.len()
                                     );
                                 } else {
-                                    println!(
+                                    trace!(
                                         "found duplicated tenpai grouping! {}",
                                         new_tile_groups_string
                                     );
@@ -1222,7 +1332,7 @@ pub fn tenpai_grouping(
                     // always need to check for single tile
 
                     if must_be_complete_group || must_be_complete_group_or_pair {
-                        println!("cannot use {} as single tile", new_tile_str);
+                        trace!("cannot use {} as single tile", new_tile_str);
                     } else {
                         let remaining_tiles = tiles.clone();
                         let (remaining_tiles, removed_first_tile) =
@@ -1233,7 +1343,7 @@ pub fn tenpai_grouping(
                         };
                         let mut new_groups = tile_groups.clone();
                         new_groups.push(new_single_tile_group);
-                        println!("recursive call: using single tile {}", new_tile_str);
+                        trace!("recursive call: using single tile {}", new_tile_str);
                         if let Some(new_tenpai_hands) =
                             tenpai_grouping(&remaining_tiles, &new_groups)
                         {
@@ -1246,13 +1356,13 @@ pub fn tenpai_grouping(
                                     get_tile_groups_string(&new_tenpai_hand_grouping);
                                 if !tenpai_hand_strings.contains(&new_tile_groups_string) {
                                     tenpai_hands.push(new_tenpai_hand_grouping.clone());
-                                    println!(
+                                    trace!(
                                         "found new tenpai grouping! {}, now there are {} tenpai_hands",
                                         new_tile_groups_string,
                                         tenpai_hands.len()
                                     );
                                 } else {
-                                    println!(
+                                    trace!(
                                         "found duplicated tenpai grouping! {}",
                                         new_tile_groups_string
                                     );
@@ -1274,7 +1384,7 @@ pub fn tenpai_grouping(
 
                         // need to check for open/edge wait as well
                         if must_be_complete_group || must_be_complete_group_or_pair {
-                            println!("cannot use {} as open/edge wait", new_tile_str);
+                            trace!("cannot use {} as open/edge wait", new_tile_str);
                         } else {
                             if second_tile_rank.is_ok()
                                 && tile_counts
@@ -1286,7 +1396,7 @@ pub fn tenpai_grouping(
                                     .unwrap_or(&0)
                                     > &0
                             {
-                                println!(
+                                trace!(
                                     "checking for open/edge wait starting at {}",
                                     new_tile_str
                                 );
@@ -1324,7 +1434,7 @@ pub fn tenpai_grouping(
                                 // recursive call
                                 let mut new_groups = tile_groups.clone();
                                 new_groups.push(new_wait_group);
-                                println!(
+                                trace!(
                                     "recursive call: using open/edge wait starting at {}",
                                     new_tile_str
                                 );
@@ -1342,13 +1452,13 @@ pub fn tenpai_grouping(
                                             get_tile_groups_string(&new_tenpai_hand_grouping);
                                         if !tenpai_hand_strings.contains(&new_tile_groups_string) {
                                             tenpai_hands.push(new_tenpai_hand_grouping.clone());
-                                            println!(
+                                            trace!(
                                                 "found new tenpai grouping! {}, now there are {} tenpai_hands",
                                                 new_tile_groups_string,
                                                 tenpai_hands.len()
                                             );
                                         } else {
-                                            println!(
+                                            trace!(
                                                 "found duplicated tenpai grouping! {}",
                                                 new_tile_groups_string
                                             );
@@ -1371,7 +1481,7 @@ pub fn tenpai_grouping(
                         );
 
                         if must_be_complete_group || must_be_complete_group_or_pair {
-                            println!("cannot use {} as closed wait", new_tile_str);
+                            trace!("cannot use {} as closed wait", new_tile_str);
                         } else {
                             // check for closed wait
                             if third_tile_rank.is_ok()
@@ -1384,7 +1494,7 @@ pub fn tenpai_grouping(
                                     .unwrap_or(&0)
                                     > &0
                             {
-                                println!("checking for closed wait starting at {}", new_tile_str);
+                                trace!("checking for closed wait starting at {}", new_tile_str);
                                 let third_tile_rank = tiles::TileRank::Number(
                                     third_tile_rank.expect("Result should not be Err!"),
                                 );
@@ -1413,7 +1523,7 @@ pub fn tenpai_grouping(
                                 // recursive call
                                 let mut new_groups = tile_groups.clone();
                                 new_groups.push(new_wait_group);
-                                println!(
+                                trace!(
                                     "recursive call: using closed wait starting at {}",
                                     new_tile_str
                                 );
@@ -1431,13 +1541,13 @@ pub fn tenpai_grouping(
                                             get_tile_groups_string(&new_tenpai_hand_grouping);
                                         if !tenpai_hand_strings.contains(&new_tile_groups_string) {
                                             tenpai_hands.push(new_tenpai_hand_grouping.clone());
-                                            println!(
+                                            trace!(
                                                 "found new tenpai grouping! {}, now there are {} tenpai_hands",
                                                 new_tile_groups_string,
                                                 tenpai_hands.len()
                                             );
                                         } else {
-                                            println!(
+                                            trace!(
                                                 "found duplicated tenpai grouping! {}",
                                                 new_tile_groups_string
                                             );
@@ -1467,7 +1577,7 @@ pub fn tenpai_grouping(
                                 .unwrap_or(&0)
                                 > &0
                         {
-                            println!("checking for sequence starting at {}", new_tile_str);
+                            trace!("checking for sequence starting at {}", new_tile_str);
                             let second_tile_rank = tiles::TileRank::Number(
                                 second_tile_rank.expect("Result should not be Err!"),
                             );
@@ -1512,7 +1622,7 @@ pub fn tenpai_grouping(
                             // recursive call
                             let mut new_groups = tile_groups.clone();
                             new_groups.push(new_sequence_group);
-                            println!(
+                            trace!(
                                 "recursive call: using sequence starting at {}",
                                 new_tile_str
                             );
@@ -1529,13 +1639,13 @@ pub fn tenpai_grouping(
                                         get_tile_groups_string(&new_tenpai_hand_grouping);
                                     if !tenpai_hand_strings.contains(&new_tile_groups_string) {
                                         tenpai_hands.push(new_tenpai_hand_grouping.clone());
-                                        println!(
+                                        trace!(
                                             "found new tenpai grouping! {}, now there are {} tenpai_hands",
                                             new_tile_groups_string,
                                             tenpai_hands.len()
                                         );
                                     } else {
-                                        println!(
+                                        trace!(
                                             "found duplicated tenpai grouping! {}",
                                             new_tile_groups_string
                                         );
@@ -1547,13 +1657,13 @@ pub fn tenpai_grouping(
                 }
                 // we have to use this tile in the tenpai hand somehow - if there's no tenpai hands at this point,
                 // then there are no tenpai hands at all
-                println!(
+                trace!(
                     "there are {} tenpai_hands for tile {}",
                     tenpai_hands.len(),
                     new_tile_str
                 );
                 return if tenpai_hands.is_empty() {
-                    println!("invalid grouping: could not use tile {}", new_tile_str);
+                    trace!("invalid grouping: could not use tile {}", new_tile_str);
                     None
                 } else {
                     Some(tenpai_hands)
@@ -1729,6 +1839,79 @@ mod tests {
     // importing names from outer (for mod tests) scope.
     use super::*;
 
+    #[test]
+    fn test_decompose_winning_hand() {
+        // 111222333m 456p 77s: three triplets, or three identical sequences (123m)
+        let hand_tiles = tiles::tiles_from_string("11122233m456p77s");
+        let decompositions =
+            decompose_winning_hand(&hand_tiles, &vec![], &tiles::Tile::from_string("3m"));
+        let mut decomposition_strings: Vec<String> = decompositions
+            .iter()
+            .map(get_tile_groups_string)
+            .collect();
+        decomposition_strings.sort();
+        assert_eq!(
+            decomposition_strings,
+            vec![
+                "1m,1m,1m|2m,2m,2m|3m,3m,3m|4p,5p,6p|7s,7s",
+                "1m,2m,3m|1m,2m,3m|1m,2m,3m|4p,5p,6p|7s,7s",
+            ]
+        );
+
+        // with a declared group: 123m 123p 123s 789p 1z + chi789p, winning on the 1z pair
+        let (hand_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("123m123p123s1z + chi789p").unwrap();
+        let decompositions = decompose_winning_hand(
+            &hand_tiles,
+            &declared_groups,
+            &tiles::Tile::from_string("1z"),
+        );
+        assert_eq!(decompositions.len(), 1);
+        assert_eq!(
+            get_tile_groups_string(&decompositions[0]),
+            "1m,2m,3m|1p,2p,3p|1s,2s,3s|1z,1z|7p,8p,9p"
+        );
+        assert!(decompositions[0]
+            .iter()
+            .any(|tile_group| tile_group.is_open()));
+
+        // 223344m 556677p 88s: two sets of identical sequences (ryanpeikou), or seven pairs
+        let hand_tiles = tiles::tiles_from_string("223344m556677p8s");
+        let decompositions =
+            decompose_winning_hand(&hand_tiles, &vec![], &tiles::Tile::from_string("8s"));
+        assert_eq!(decompositions.len(), 2);
+        assert!(decompositions
+            .iter()
+            .any(|grouping| number_pair_groups(grouping) == 7));
+
+        // not a winning hand
+        let hand_tiles = tiles::tiles_from_string("123m456p789s1234z");
+        assert!(
+            decompose_winning_hand(&hand_tiles, &vec![], &tiles::Tile::from_string("5z"))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_first_copy_index_red_five() {
+        let tiles = tiles::tiles_from_string("0m34m");
+        let three_rank = tiles::TileRank::Number(tiles::NumberTileRank::Three);
+        let five_rank = tiles::TileRank::Number(tiles::NumberTileRank::Five);
+        // the red five is only treated as a normal five, not as any other rank
+        assert_eq!(
+            first_copy_index(&tiles, three_rank, tiles::TileSuit::Man, true),
+            Some(1)
+        );
+        assert_eq!(
+            first_copy_index(&tiles, five_rank, tiles::TileSuit::Man, true),
+            Some(0)
+        );
+        assert_eq!(
+            first_copy_index(&tiles, five_rank, tiles::TileSuit::Man, false),
+            None
+        );
+    }
+
     #[test]
     fn test_count_tiles_by_suit_rank() {
         let tiles = Vec::from([
@@ -1880,6 +2063,76 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hand_fingerprint_suit_permutation() {
+        // 1-shanten hand: 23345m 46p 123789s
+        let man_pin_tiles = Vec::from([
+            tiles::Tile::from_string("2m"),
+            tiles::Tile::from_string("3m"),
+            tiles::Tile::from_string("3m"),
+            tiles::Tile::from_string("4m"),
+            tiles::Tile::from_string("5m"),
+            tiles::Tile::from_string("4p"),
+            tiles::Tile::from_string("6p"),
+            tiles::Tile::from_string("1s"),
+            tiles::Tile::from_string("2s"),
+            tiles::Tile::from_string("3s"),
+            tiles::Tile::from_string("7s"),
+            tiles::Tile::from_string("8s"),
+            tiles::Tile::from_string("9s"),
+        ]);
+        // same shape, but with the suits relabeled (man -> sou, pin -> man, sou -> pin)
+        // and with a red five: 23340s 46m 123789p
+        let sou_man_tiles = Vec::from([
+            tiles::Tile::from_string("2s"),
+            tiles::Tile::from_string("3s"),
+            tiles::Tile::from_string("3s"),
+            tiles::Tile::from_string("4s"),
+            tiles::Tile::from_string("0s"),
+            tiles::Tile::from_string("4m"),
+            tiles::Tile::from_string("6m"),
+            tiles::Tile::from_string("1p"),
+            tiles::Tile::from_string("2p"),
+            tiles::Tile::from_string("3p"),
+            tiles::Tile::from_string("7p"),
+            tiles::Tile::from_string("8p"),
+            tiles::Tile::from_string("9p"),
+        ]);
+        assert_eq!(
+            hand_fingerprint(&man_pin_tiles),
+            "000101000|012110000|111000111|0000000"
+        );
+        assert_eq!(
+            hand_fingerprint(&man_pin_tiles),
+            hand_fingerprint(&sou_man_tiles)
+        );
+
+        // replacing the 46p with a pair of east wind tiles is a different shape
+        let honor_tiles = Vec::from([
+            tiles::Tile::from_string("2m"),
+            tiles::Tile::from_string("3m"),
+            tiles::Tile::from_string("3m"),
+            tiles::Tile::from_string("4m"),
+            tiles::Tile::from_string("5m"),
+            tiles::Tile::from_string("1z"),
+            tiles::Tile::from_string("1z"),
+            tiles::Tile::from_string("1s"),
+            tiles::Tile::from_string("2s"),
+            tiles::Tile::from_string("3s"),
+            tiles::Tile::from_string("7s"),
+            tiles::Tile::from_string("8s"),
+            tiles::Tile::from_string("9s"),
+        ]);
+        assert_eq!(
+            hand_fingerprint(&honor_tiles),
+            "000000000|012110000|111000111|2000000"
+        );
+        assert_ne!(
+            hand_fingerprint(&man_pin_tiles),
+            hand_fingerprint(&honor_tiles)
+        );
+    }
+
     // winning hands taken from my Mahjong Soul logs
     // game: 4-player East round, Silver room, 2023-06-03 09:26
     #[test]
@@ -2250,6 +2503,34 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_tile_grouping_serde_round_trip() {
+        let (winning_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("789m666p05s + pon9p + chi789s").expect("Valid hand string");
+        let winning_tile_groups = tile_grouping(&winning_tiles, &declared_groups)
+            .expect("Expect some winning groupings");
+        let winning_grouping = winning_tile_groups
+            .first()
+            .expect("Expect a winning grouping");
+
+        // tiles are serialized as their MSPZ strings
+        let json = serde_json::to_string(winning_grouping).expect("Grouping must serialize");
+        assert!(json.contains("\"9p\""));
+        assert!(json.contains("\"0s\""));
+
+        let deserialized_grouping: Vec<tiles::TileGroup> =
+            serde_json::from_str(&json).expect("Grouping must deserialize");
+        assert_eq!(
+            get_tile_groups_string(&deserialized_grouping),
+            get_tile_groups_string(winning_grouping)
+        );
+        assert_eq!(
+            serde_json::to_string(&deserialized_grouping).expect("Grouping must serialize"),
+            json
+        );
+    }
+
     #[test]
     fn test_tenpai_grouping_two_pairs() {
         // from riichi wiki: https://riichi.wiki/Tenpai
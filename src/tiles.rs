@@ -1,9 +1,23 @@
 use std::fmt;
+use std::str::FromStr;
 
 // using the MPSZ notation, described here: https://ctan.math.utah.edu/ctan/tex-archive/graphics/mahjong/mahjong-code.pdf
 pub const TILE_SUITS_CHARS: [char; 4] = ['m', 'p', 's', 'z'];
 // number of tiles in a standard riichi mahjong set
 pub const NUM_TILES: u32 = 3 * 4 * 9 + 4 * (4 + 3);
+// number of distinct tile kinds (red fives are the same kind as normal fives): 1-9 in each numbered suit, and 7 honor tiles
+pub const NUM_TILE_KINDS: usize = 3 * 9 + 7;
+
+// the dora tile kind for each dora indicator tile kind (see `Tile::kind_index()`): the next rank in the same suit,
+// where numbered tiles wrap from 9 to 1, winds cycle east -> south -> west -> north -> east,
+// and dragons cycle white -> green -> red -> white
+pub const DORA_FROM_INDICATOR: [usize; NUM_TILE_KINDS] = [
+    1, 2, 3, 4, 5, 6, 7, 8, 0, // man
+    10, 11, 12, 13, 14, 15, 16, 17, 9, // pin
+    19, 20, 21, 22, 23, 24, 25, 26, 18, // sou
+    28, 29, 30, 27, // winds
+    32, 33, 31, // dragons
+];
 
 /// The possible suits of a tile
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
@@ -203,6 +217,36 @@ impl fmt::Debug for Tile {
     }
 }
 
+/// Parses a single tile from the same 2-character representation used by `to_string()` (see `Tile::from_string()`),
+/// but returns an Err instead of panicking if the string isn't exactly one tile in MSPZ notation, e.g. "10m" or "8z"
+impl FromStr for Tile {
+    type Err = &'static str;
+
+    fn from_str(tile_string: &str) -> Result<Self, Self::Err> {
+        match try_tiles_from_string(tile_string)?[..] {
+            [tile] => Ok(tile),
+            _ => Err("Must be exactly one tile!"),
+        }
+    }
+}
+
+/// Serializes the tile as its MSPZ string (see `Tile::to_string()`) e.g. "7m" or "0p" (red five)
+#[cfg(feature = "serde")]
+impl serde::Serialize for Tile {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Deserializes the tile from its MSPZ string (see `Tile::from_string()`)
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Tile {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tile_string = String::deserialize(deserializer)?;
+        tile_string.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl Tile {
     // TODO use this function when initializing tiles via serial number?
     pub fn is_valid_serial(&self) -> bool {
@@ -379,6 +423,28 @@ impl Tile {
         tile_string
     }
 
+    /// Represents the tile as a character from the Unicode Mahjong Tiles block (red fives are shown as normal fives)
+    /// e.g. for 1-man: '\u{1F007}', for 9-sou: '\u{1F018}', for red dragon: '\u{1F004}'
+    pub fn to_unicode(&self) -> char {
+        let kind_index = self.kind_index();
+        let codepoint = match kind_index / 9 {
+            // the block is ordered man, sou, pin (not man, pin, sou)
+            0 => 0x1F007 + kind_index,
+            1 => 0x1F019 + (kind_index - 9),
+            2 => 0x1F010 + (kind_index - 2 * 9),
+            _ => {
+                // the block is ordered east, south, west, north, then red, green, white (the reverse of 5z, 6z, 7z)
+                let honor_index = kind_index - 3 * 9;
+                if honor_index < 4 {
+                    0x1F000 + honor_index
+                } else {
+                    0x1F004 + (6 - honor_index)
+                }
+            }
+        };
+        char::from_u32(codepoint as u32).expect("Mahjong tile codepoint must be valid")
+    }
+
     // constructors
 
     /// Constructs a Tile from its suit and rank (in MSPZ notation)
@@ -500,60 +566,20 @@ impl Tile {
                 || self.rank() == TileRank::Honor(HonorTileRank::White))
     }
 
+    /// If the tile is a wind tile (east, south, west, or north)
+    pub fn is_wind(&self) -> bool {
+        self.suit() == TileSuit::Honor && !self.is_dragon()
+    }
+
     /// Is this tile a dora tile based on the indicator tile?
     pub fn is_dora_from_indicator(&self, dora_indicator: &Tile) -> bool {
-        if self.suit() != dora_indicator.suit() {
-            return false;
-        }
-        match self.suit() {
-            TileSuit::Honor => {
-                let indicated_dora_rank = match dora_indicator.rank() {
-                    TileRank::Honor(indicator_rank) => match indicator_rank {
-                        HonorTileRank::East => HonorTileRank::South,
-                        HonorTileRank::South => HonorTileRank::West,
-                        HonorTileRank::West => HonorTileRank::North,
-                        HonorTileRank::North => HonorTileRank::East,
-                        HonorTileRank::White => HonorTileRank::Green,
-                        HonorTileRank::Green => HonorTileRank::Red,
-                        HonorTileRank::Red => HonorTileRank::White,
-                    },
-                    _ => panic!("Expected dora indicator to be an honor tile!"),
-                };
-                match self.rank() {
-                    TileRank::Honor(self_rank) => self_rank == indicated_dora_rank,
-                    _ => panic!("Expected this tile to also be an honor tile!"),
-                }
-            }
-            _ => {
-                let indicated_dora_rank = match dora_indicator.rank() {
-                    TileRank::Number(indicator_rank) => match indicator_rank {
-                        NumberTileRank::One => NumberTileRank::Two,
-                        NumberTileRank::Two => NumberTileRank::Three,
-                        NumberTileRank::Three => NumberTileRank::Four,
-                        NumberTileRank::Four => NumberTileRank::Five,
-                        NumberTileRank::RedFive => NumberTileRank::Six,
-                        NumberTileRank::Five => NumberTileRank::Six,
-                        NumberTileRank::Six => NumberTileRank::Seven,
-                        NumberTileRank::Seven => NumberTileRank::Eight,
-                        NumberTileRank::Eight => NumberTileRank::Nine,
-                        NumberTileRank::Nine => NumberTileRank::One,
-                    },
-                    _ => panic!("Expected dora indicator to be a number tile!"),
-                };
-                match self.rank() {
-                    TileRank::Number(self_rank) => {
-                        // if the dora indicator is a 4, then both red-five and normal-five count as dora tiles
-                        if self_rank == NumberTileRank::RedFive
-                            && indicated_dora_rank == NumberTileRank::Five
-                        {
-                            return true;
-                        }
-                        self_rank == indicated_dora_rank
-                    }
-                    _ => panic!("Expected this tile to also be a number tile!"),
-                }
-            }
-        }
+        // red fives are the same tile kind as normal fives, so they're also dora if the indicator is a 4
+        self.kind_index() == DORA_FROM_INDICATOR[dora_indicator.kind_index()]
+    }
+
+    /// The dora tile indicated by this tile as a dora indicator (never a red five)
+    pub fn dora_from_indicator(&self) -> Tile {
+        Tile::from_kind_index(DORA_FROM_INDICATOR[self.kind_index()])
     }
 
     /// If the tile is rank 2-8 in a numbered suit, i.e. is not an honor tile or a terminal tile
@@ -580,11 +606,179 @@ impl Tile {
         // used for counting dora
         self.is_number_suit() && self.rank() == TileRank::Number(NumberTileRank::RedFive)
     }
+
+    /// The index of the tile's kind (ignoring red fives), from 0 to `NUM_TILE_KINDS - 1`:
+    /// 1-9m are 0-8, 1-9p are 9-17, 1-9s are 18-26, and honors (east, south, west, north, white, green, red) are 27-33
+    pub fn kind_index(&self) -> usize {
+        if self.is_number_suit() {
+            let suit_index = (self.serial / (4 * 9)) as usize;
+            let rank_num =
+                self.sequence_rank_num()
+                    .expect("Numbered tile must have a sequence rank") as usize;
+            suit_index * 9 + (rank_num - 1)
+        } else {
+            3 * 9 + ((self.serial - (3 * 36)) % 7) as usize
+        }
+    }
+
+    /// Constructs the first copy of a tile kind (see `kind_index()`) - never constructs a red five
+    /// e.g. 0 -> 1-man, 13 -> 5-pin, 33 -> red dragon
+    pub fn from_kind_index(kind_index: usize) -> Self {
+        assert!(kind_index < NUM_TILE_KINDS, "Invalid tile kind index");
+        if kind_index < 3 * 9 {
+            let suit = TILE_SUITS_CHARS[kind_index / 9];
+            let rank = char::from_digit((kind_index % 9) as u32 + 1, 10).unwrap();
+            Self::from_string(format!("{}{}", rank, suit).as_str())
+        } else {
+            let rank = char::from_digit((kind_index - 3 * 9) as u32 + 1, 10).unwrap();
+            Self::from_string(format!("{}z", rank).as_str())
+        }
+    }
+}
+
+/// Represents the hand with the number of tiles of each tile kind (see `Tile::kind_index()`) as Unicode mahjong tiles
+/// (see `Tile::to_unicode()`), in order of suit (man, pin, sou, honors) and rank
+pub fn unicode_from_counts(tile_kind_counts: &[u8; NUM_TILE_KINDS]) -> String {
+    tile_kind_counts
+        .iter()
+        .enumerate()
+        .flat_map(|(kind_index, count)| {
+            std::iter::repeat_n(
+                Tile::from_kind_index(kind_index).to_unicode(),
+                *count as usize,
+            )
+        })
+        .collect()
+}
+
+/// Constructs a list of Tiles from a hand in MSPZ notation, where consecutive ranks share the suit that follows them
+/// e.g. "123m055p11z" -> 1-man, 2-man, 3-man, red-5-pin, 5-pin, 5-pin, East wind, East wind.
+/// Every tile is constructed with `Tile::from_string()`, so this is mostly useful for counting-based logic
+/// (which doesn't depend on which copy of a tile is used), e.g. shanten and ukiere.
+pub fn tiles_from_string(hand_string: &str) -> Vec<Tile> {
+    try_tiles_from_string(hand_string).expect("Invalid hand string")
+}
+
+/// Same as `tiles_from_string()`, but returns an Err instead of panicking if the string isn't valid MSPZ notation
+pub fn try_tiles_from_string(hand_string: &str) -> Result<Vec<Tile>, &'static str> {
+    let mut tiles = Vec::new();
+    let mut pending_ranks: Vec<char> = Vec::new();
+    for hand_char in hand_string.chars() {
+        if TILE_SUITS_CHARS.contains(&hand_char) {
+            if pending_ranks.is_empty() {
+                return Err("Suit char must follow at least one rank char!");
+            }
+            let tile_suit = TileSuit::try_from(hand_char)?;
+            for rank_char in pending_ranks.drain(..) {
+                if tile_suit.is_number() {
+                    NumberTileRank::try_from(rank_char)?;
+                } else {
+                    HonorTileRank::try_from(rank_char)?;
+                }
+                tiles.push(Tile::from_string(
+                    format!("{}{}", rank_char, hand_char).as_str(),
+                ));
+            }
+        } else {
+            pending_ranks.push(hand_char);
+        }
+    }
+    if !pending_ranks.is_empty() {
+        return Err("Rank chars must be followed by a suit char!");
+    }
+    Ok(tiles)
+}
+
+/// Rewrites a hand in MSPZ notation (see `tiles_from_string()`) in canonical form: the tiles sorted by suit
+/// (man, pin, sou, honors) and rank, with each suit written once after all of its ranks (a red five is sorted
+/// just before the normal fives), e.g. "46p255567s33478m4s" -> "33478m46p2455567s".
+/// Returns an Err if the string isn't valid MSPZ notation (see `try_tiles_from_string()`).
+pub fn normalize_hand_string(hand_string: &str) -> Result<String, &'static str> {
+    let mut tiles = try_tiles_from_string(hand_string)?;
+    tiles.sort_by_key(|tile| (tile.kind_index(), !tile.is_red_five()));
+
+    let mut normalized_string = String::new();
+    let mut pending_suit: Option<char> = None;
+    for tile in tiles.iter() {
+        let suit_char = char::from(tile.suit());
+        if pending_suit.is_some_and(|pending_suit| pending_suit != suit_char) {
+            normalized_string.extend(pending_suit);
+        }
+        normalized_string.push(char::from(tile.rank()));
+        pending_suit = Some(suit_char);
+    }
+    normalized_string.extend(pending_suit);
+    Ok(normalized_string)
+}
+
+/// Constructs a hand with called melds (and kans) from a string: the concealed tiles in MSPZ notation (see `tiles_from_string()`),
+/// followed by each meld separated by a `+`, where each meld is the type of call followed by its tiles in MSPZ notation:
+/// - `chi`: an open sequence, e.g. `chi234s`
+/// - `pon`: an open triplet, e.g. `pon777z` (or just `pon7z`)
+/// - `kan`: an open quad (called from a discard), e.g. `kan1111m` (or just `kan1m`)
+/// - `kakan`: an open quad (added to a called triplet), e.g. `kakan9p`
+/// - `ankan`: a closed quad, e.g. `ankan2z`
+///
+/// e.g. "234m567p88s + chi234s + pon7z" -> the concealed tiles 234m567p88s, an open sequence 234s, and an open triplet 777z
+pub fn hand_and_melds_from_string(
+    hand_string: &str,
+) -> Result<(Vec<Tile>, Vec<TileGroup>), &'static str> {
+    let mut hand_parts = hand_string.split('+').map(|hand_part| hand_part.trim());
+    let concealed_tiles = try_tiles_from_string(hand_parts.next().unwrap_or(""))?;
+
+    let mut melds: Vec<TileGroup> = Vec::new();
+    for meld_string in hand_parts {
+        let (call_type, meld_tiles_string) = ["chi", "pon", "kakan", "ankan", "kan"]
+            .iter()
+            .find_map(|call_type| {
+                meld_string
+                    .strip_prefix(call_type)
+                    .map(|meld_tiles_string| (*call_type, meld_tiles_string))
+            })
+            .ok_or("Meld must start with a call type (chi, pon, kan, kakan, or ankan)!")?;
+        let mut meld_tiles = try_tiles_from_string(meld_tiles_string)?;
+        let num_meld_tiles = if call_type == "chi" || call_type == "pon" {
+            3
+        } else {
+            4
+        };
+        if call_type != "chi" && meld_tiles.len() == 1 {
+            meld_tiles = vec![meld_tiles[0]; num_meld_tiles];
+        }
+        if meld_tiles.len() != num_meld_tiles {
+            return Err("Wrong number of tiles in meld!");
+        }
+        if call_type == "chi" {
+            meld_tiles.sort_by_key(|tile| tile.sequence_rank_num());
+        }
+
+        let meld = match call_type {
+            "chi" => TileGroup::Sequence {
+                open: true,
+                tiles: [meld_tiles[0], meld_tiles[1], meld_tiles[2]],
+            },
+            "pon" => TileGroup::Triplet {
+                open: true,
+                tiles: [meld_tiles[0], meld_tiles[1], meld_tiles[2]],
+            },
+            _ => TileGroup::Quad {
+                open: call_type != "ankan",
+                added: call_type == "kakan",
+                tiles: [meld_tiles[0], meld_tiles[1], meld_tiles[2], meld_tiles[3]],
+            },
+        };
+        if !meld.is_valid() {
+            return Err("Invalid tiles for meld!");
+        }
+        melds.push(meld);
+    }
+    Ok((concealed_tiles, melds))
 }
 
 /// A group of tiles - used for identifying winning hand shapes (generally, 4 complete groups and a pair),
 /// and for classifying their value (based on yaku list)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TileGroup {
     /// three tiles with the same suit and same rank
     Triplet {
@@ -648,11 +842,10 @@ impl TileGroup {
     pub fn is_valid(&self) -> bool {
         match self {
             Self::Triplet { tiles, .. } => {
-                // check that all tiles have the same rank & suit
-                let rank = tiles[0].rank();
-                let suit = tiles[0].suit();
+                // check that all tiles have the same rank & suit (a red five is the same as a normal five)
+                let kind_index = tiles[0].kind_index();
                 for index in 1..3 {
-                    if tiles[index].rank() != rank || tiles[index].suit() != suit {
+                    if tiles[index].kind_index() != kind_index {
                         return false;
                     }
                 }
@@ -664,11 +857,10 @@ impl TileGroup {
                     return false;
                 }
 
-                // check that all tiles have the same rank & suit
-                let rank = tiles[0].rank();
-                let suit = tiles[0].suit();
+                // check that all tiles have the same rank & suit (a red five is the same as a normal five)
+                let kind_index = tiles[0].kind_index();
                 for index in 1..4 {
-                    if tiles[index].rank() != rank || tiles[index].suit() != suit {
+                    if tiles[index].kind_index() != kind_index {
                         return false;
                     }
                 }
@@ -701,10 +893,8 @@ impl TileGroup {
                 rank0 + 1 == rank1 && rank1 + 1 == rank2
             }
             Self::Pair { tiles } => {
-                // check that both tiles have the same rank & suit
-                let rank = tiles[0].rank();
-                let suit = tiles[0].suit();
-                tiles[1].rank() == rank && tiles[1].suit() == suit
+                // check that both tiles have the same rank & suit (a red five is the same as a normal five)
+                tiles[1].kind_index() == tiles[0].kind_index()
             }
             Self::OpenWait { tiles, .. } => {
                 // both tiles must be in the same numbered suit (no sequences possible in honors suits)
@@ -776,6 +966,20 @@ impl TileGroup {
         }
     }
 
+    /// Get all tiles of the group (in the same order as the group)
+    pub fn tiles(&self) -> Vec<Tile> {
+        match self {
+            Self::Triplet { tiles, .. } => tiles.to_vec(),
+            Self::Quad { tiles, .. } => tiles.to_vec(),
+            Self::Sequence { tiles, .. } => tiles.to_vec(),
+            Self::Pair { tiles, .. } => tiles.to_vec(),
+            Self::OpenWait { tiles, .. } => tiles.to_vec(),
+            Self::ClosedWait { tiles, .. } => tiles.to_vec(),
+            Self::EdgeWait { tiles, .. } => tiles.to_vec(),
+            Self::SingleTile { tile, .. } => vec![*tile],
+        }
+    }
+
     /// Get all tiles of the group sorted lexicographically (by their MPSZ notation)
     pub fn to_tiles_string(&self) -> String {
         let mut tile_strings = match self {
@@ -818,6 +1022,30 @@ pub fn get_pair_group(tile_groups: &Vec<TileGroup>) -> Option<Tile> {
     return None;
 }
 
+/// The dora tiles indicated by each of the dora indicators (in the same order), see `Tile::dora_from_indicator()`
+pub fn dora_tiles_from_indicators(dora_indicators: &Vec<Tile>) -> Vec<Tile> {
+    dora_indicators
+        .iter()
+        .map(|indicator| indicator.dora_from_indicator())
+        .collect()
+}
+
+/// The tile of the head (i.e. the single pair) of a hand grouping. Returns None if the grouping doesn't have exactly
+/// one pair, e.g. a seven pairs grouping (which has no single head), or a hand that isn't complete yet (no pair).
+pub fn get_head_tile(tile_groups: &Vec<TileGroup>) -> Option<Tile> {
+    let mut pair_tiles = tile_groups
+        .iter()
+        .filter_map(|tile_group| match tile_group {
+            TileGroup::Pair { tiles } => Some(tiles[0]),
+            _ => None,
+        });
+    let head_tile = pair_tiles.next();
+    if pair_tiles.next().is_some() {
+        return None;
+    }
+    head_tile
+}
+
 #[cfg(test)]
 mod tests {
     // importing names from outer (for mod tests) scope.
@@ -1004,6 +1232,61 @@ mod tests {
         assert_eq!(red_dragon_tile.to_string(), "7z".to_string());
     }
 
+    #[test]
+    fn test_tile_parse() {
+        for suit_char in TILE_SUITS_CHARS {
+            let max_rank = if suit_char == 'z' { 7 } else { 9 };
+            for rank in 1..=max_rank {
+                let tile_string = format!("{}{}", rank, suit_char);
+                let tile: Tile = tile_string.parse().expect("Valid tile string");
+                assert_eq!(format!("{}", tile), tile_string);
+                assert_eq!(
+                    tile.kind_index(),
+                    Tile::from_string(&tile_string).kind_index()
+                );
+            }
+        }
+        let red_five: Tile = "0p".parse().expect("Valid tile string");
+        assert!(red_five.is_red_five());
+        assert_eq!(format!("{}", red_five), "0p");
+
+        for invalid_string in ["", "m", "1", "10m", "8z", "0z", "1x", "11m", "1m2p"] {
+            assert!(
+                invalid_string.parse::<Tile>().is_err(),
+                "{}",
+                invalid_string
+            );
+        }
+    }
+
+    #[test]
+    fn test_tile_to_unicode() {
+        assert_eq!(Tile::from_string("1m").to_unicode(), '\u{1F007}');
+        assert_eq!(Tile::from_string("9m").to_unicode(), '\u{1F00F}');
+        assert_eq!(Tile::from_string("1p").to_unicode(), '\u{1F019}');
+        assert_eq!(Tile::from_string("0p").to_unicode(), '\u{1F01D}');
+        assert_eq!(Tile::from_string("1s").to_unicode(), '\u{1F010}');
+        assert_eq!(Tile::from_string("9s").to_unicode(), '\u{1F018}');
+        assert_eq!(Tile::from_string("1z").to_unicode(), '\u{1F000}');
+        assert_eq!(Tile::from_string("4z").to_unicode(), '\u{1F003}');
+        // white, green, red dragons
+        assert_eq!(Tile::from_string("5z").to_unicode(), '\u{1F006}');
+        assert_eq!(Tile::from_string("6z").to_unicode(), '\u{1F005}');
+        assert_eq!(Tile::from_string("7z").to_unicode(), '\u{1F004}');
+
+        let tile_kind_counts = {
+            let mut tile_kind_counts = [0u8; NUM_TILE_KINDS];
+            for tile in tiles_from_string("7z11m9s") {
+                tile_kind_counts[tile.kind_index()] += 1;
+            }
+            tile_kind_counts
+        };
+        assert_eq!(
+            unicode_from_counts(&tile_kind_counts),
+            "\u{1F007}\u{1F007}\u{1F018}\u{1F004}"
+        );
+    }
+
     #[test]
     fn test_tile_sequence_rank_num() {
         let man_tile = Tile::from_string("1m");
@@ -1050,6 +1333,7 @@ mod tests {
         let man_tile = Tile::from_string("1m");
         assert!(!man_tile.is_honor());
         assert!(!man_tile.is_dragon());
+        assert!(!man_tile.is_wind());
         assert_eq!(man_tile.human_suit(), 'm');
         assert_eq!(man_tile.human_rank(), '1');
         assert_eq!(man_tile.rank_numeric_value(), Some(1));
@@ -1082,6 +1366,7 @@ mod tests {
         let east_wind_tile = Tile::from_string("1z");
         assert!(east_wind_tile.is_honor());
         assert!(!east_wind_tile.is_dragon());
+        assert!(east_wind_tile.is_wind());
         assert_eq!(east_wind_tile.human_suit(), 'w');
         assert_eq!(east_wind_tile.human_rank(), 'E');
         assert_eq!(east_wind_tile.to_human_string(), "Ew".to_string());
@@ -1103,6 +1388,7 @@ mod tests {
         let north_wind_tile = Tile::from_string("4z");
         assert!(north_wind_tile.is_honor());
         assert!(!north_wind_tile.is_dragon());
+        assert!(north_wind_tile.is_wind());
         assert_eq!(north_wind_tile.human_suit(), 'w');
         assert_eq!(north_wind_tile.human_rank(), 'N');
         assert_eq!(north_wind_tile.to_human_string(), "Nw".to_string());
@@ -1110,6 +1396,7 @@ mod tests {
         let white_dragon_tile = Tile::from_string("5z");
         assert!(white_dragon_tile.is_honor());
         assert!(white_dragon_tile.is_dragon());
+        assert!(!white_dragon_tile.is_wind());
         assert_eq!(white_dragon_tile.human_suit(), 'd');
         assert_eq!(white_dragon_tile.human_rank(), 'W');
         assert_eq!(white_dragon_tile.to_human_string(), "Wd".to_string());
@@ -1254,6 +1541,137 @@ mod tests {
         assert_eq!(num_red_fives, expected_num_red_fives);
     }
 
+    #[test]
+    fn test_tile_kind_index_counts() {
+        // every tile kind has exactly 4 copies (red fives are the same kind as normal fives)
+        let mut kind_counts = [0u32; NUM_TILE_KINDS];
+        for serial in 0..NUM_TILES {
+            let tile = Tile { serial };
+            kind_counts[tile.kind_index()] += 1;
+        }
+        assert_eq!(kind_counts, [4u32; NUM_TILE_KINDS]);
+
+        assert_eq!(Tile::from_string("1m").kind_index(), 0);
+        assert_eq!(Tile::from_string("0p").kind_index(), 13);
+        assert_eq!(Tile::from_string("5p").kind_index(), 13);
+        assert_eq!(Tile::from_string("9s").kind_index(), 26);
+        assert_eq!(Tile::from_string("1z").kind_index(), 27);
+        assert_eq!(Tile::from_string("7z").kind_index(), 33);
+
+        for kind_index in 0..NUM_TILE_KINDS {
+            let tile = Tile::from_kind_index(kind_index);
+            assert_eq!(tile.kind_index(), kind_index);
+            assert!(!tile.is_red_five());
+        }
+    }
+
+    #[test]
+    fn test_tiles_from_string() {
+        let tiles = tiles_from_string("123m055p11z");
+        let tile_strings: Vec<String> = tiles.iter().map(|tile| tile.to_string()).collect();
+        assert_eq!(
+            tile_strings,
+            vec!["1m", "2m", "3m", "0p", "5p", "5p", "1z", "1z"]
+        );
+        assert!(tiles_from_string("").is_empty());
+    }
+
+    #[test]
+    fn test_normalize_hand_string() {
+        assert_eq!(
+            normalize_hand_string("46p255567s33478m4s"),
+            Ok(String::from("33478m46p2455567s"))
+        );
+        assert_eq!(
+            normalize_hand_string("1239m456p3478s55z9p"),
+            Ok(String::from("1239m4569p3478s55z"))
+        );
+        assert_eq!(
+            normalize_hand_string("111z222z333z55z99m"),
+            Ok(String::from("99m11122233355z"))
+        );
+        assert_eq!(
+            normalize_hand_string("1234678m55m9m12p1z"),
+            Ok(String::from("1234556789m12p1z"))
+        );
+        assert_eq!(
+            normalize_hand_string("1p2p3p4p5p"),
+            Ok(String::from("12345p"))
+        );
+        // the red five is sorted before the normal fives
+        assert_eq!(
+            normalize_hand_string("234m3450m678p"),
+            Ok(String::from("2334405m678p"))
+        );
+        // already canonical
+        assert_eq!(
+            normalize_hand_string("123m055p11z"),
+            Ok(String::from("123m055p11z"))
+        );
+        assert_eq!(normalize_hand_string(""), Ok(String::new()));
+        // invalid hand strings
+        assert!(normalize_hand_string("1239m456p3478s55").is_err());
+        assert!(normalize_hand_string("123m8z").is_err());
+        assert!(normalize_hand_string("12x3m").is_err());
+    }
+
+    #[test]
+    fn test_hand_and_melds_from_string() {
+        let (concealed_tiles, melds) =
+            hand_and_melds_from_string("234m567p88s + chi243s + pon7z").expect("Valid hand string");
+        let concealed_strings: Vec<String> = concealed_tiles
+            .iter()
+            .map(|tile| tile.to_string())
+            .collect();
+        assert_eq!(
+            concealed_strings,
+            vec!["2m", "3m", "4m", "5p", "6p", "7p", "8s", "8s"]
+        );
+        assert_eq!(melds.len(), 2);
+        assert!(matches!(melds[0], TileGroup::Sequence { open: true, .. }));
+        assert!(melds[0].is_valid());
+        assert_eq!(melds[0].to_tiles_string(), "2s,3s,4s");
+        assert!(matches!(melds[1], TileGroup::Triplet { open: true, .. }));
+        assert_eq!(melds[1].to_tiles_string(), "7z,7z,7z");
+
+        let (concealed_tiles, melds) =
+            hand_and_melds_from_string("11z+ankan2222m+kan9p+kakan1s+pon555z")
+                .expect("Valid hand string");
+        assert_eq!(concealed_tiles.len(), 2);
+        assert!(matches!(
+            melds[0],
+            TileGroup::Quad {
+                open: false,
+                added: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            melds[1],
+            TileGroup::Quad {
+                open: true,
+                added: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            melds[2],
+            TileGroup::Quad {
+                open: true,
+                added: true,
+                ..
+            }
+        ));
+        assert!(matches!(melds[3], TileGroup::Triplet { open: true, .. }));
+
+        // invalid melds
+        assert!(hand_and_melds_from_string("123m + chi135s").is_err());
+        assert!(hand_and_melds_from_string("123m + pon12s").is_err());
+        assert!(hand_and_melds_from_string("123m + peng7z").is_err());
+        assert!(hand_and_melds_from_string("123m + chi123z").is_err());
+        assert!(hand_and_melds_from_string("12x3m").is_err());
+    }
+
     #[test]
     fn test_tile_group_is_valid() {
         let valid_triplet_group = TileGroup::Triplet {
@@ -1278,9 +1696,153 @@ mod tests {
         };
         assert!(!invalid_triplet_group.is_valid());
 
+        // a red five is the same rank as a normal five
+        let red_five_triplet_group = TileGroup::Triplet {
+            open: false,
+            tiles: [
+                Tile::from_string("0p"),
+                Tile::from_string("5p"),
+                Tile::from_string("5p"),
+            ],
+        };
+        assert!(red_five_triplet_group.is_valid());
+        let red_five_pair_group = TileGroup::Pair {
+            tiles: [Tile::from_string("5s"), Tile::from_string("0s")],
+        };
+        assert!(red_five_pair_group.is_valid());
+        let invalid_red_five_pair_group = TileGroup::Pair {
+            tiles: [Tile::from_string("0s"), Tile::from_string("5p")],
+        };
+        assert!(!invalid_red_five_pair_group.is_valid());
+
         // TODO add more test cases for different group types (quad, sequences, open wait, etc.)
     }
 
+    #[test]
+    fn test_get_head_tile() {
+        let sequence = TileGroup::Sequence {
+            open: false,
+            tiles: [
+                Tile::from_string("1m"),
+                Tile::from_string("2m"),
+                Tile::from_string("3m"),
+            ],
+        };
+        let triplet = TileGroup::Triplet {
+            open: true,
+            tiles: [
+                Tile::from_string("7z"),
+                Tile::from_string("7z"),
+                Tile::from_string("7z"),
+            ],
+        };
+        let pair = |tile_string: &str| TileGroup::Pair {
+            tiles: [
+                Tile::from_string(tile_string),
+                Tile::from_string(tile_string),
+            ],
+        };
+
+        // standard hand shape: 4 groups and a pair
+        let standard_groups = vec![
+            sequence.clone(),
+            sequence.clone(),
+            triplet.clone(),
+            triplet.clone(),
+            pair("9s"),
+        ];
+        assert_eq!(
+            get_head_tile(&standard_groups).map(|tile| tile.to_string()),
+            Some(String::from("9s"))
+        );
+
+        // seven pairs has no single head
+        let seven_pairs_groups = ["1m", "9m", "1p", "9p", "1s", "9s", "1z"]
+            .iter()
+            .map(|tile_string| pair(tile_string))
+            .collect();
+        assert!(get_head_tile(&seven_pairs_groups).is_none());
+        assert!(get_pair_group(&seven_pairs_groups).is_some());
+
+        // headless: 4 groups and a single tile wait (before winning)
+        let headless_groups = vec![
+            sequence.clone(),
+            sequence,
+            triplet.clone(),
+            triplet,
+            TileGroup::SingleTile {
+                tile: Tile::from_string("9s"),
+            },
+        ];
+        assert!(get_head_tile(&headless_groups).is_none());
+    }
+
+    #[test]
+    fn test_dora_from_indicator_table() {
+        let expected_dora_strings = [
+            ("1m", "2m"),
+            ("2m", "3m"),
+            ("3m", "4m"),
+            ("4m", "5m"),
+            ("5m", "6m"),
+            ("6m", "7m"),
+            ("7m", "8m"),
+            ("8m", "9m"),
+            ("9m", "1m"),
+            ("1p", "2p"),
+            ("2p", "3p"),
+            ("3p", "4p"),
+            ("4p", "5p"),
+            ("5p", "6p"),
+            ("6p", "7p"),
+            ("7p", "8p"),
+            ("8p", "9p"),
+            ("9p", "1p"),
+            ("1s", "2s"),
+            ("2s", "3s"),
+            ("3s", "4s"),
+            ("4s", "5s"),
+            ("5s", "6s"),
+            ("6s", "7s"),
+            ("7s", "8s"),
+            ("8s", "9s"),
+            ("9s", "1s"),
+            ("1z", "2z"),
+            ("2z", "3z"),
+            ("3z", "4z"),
+            ("4z", "1z"),
+            ("5z", "6z"),
+            ("6z", "7z"),
+            ("7z", "5z"),
+        ];
+        assert_eq!(expected_dora_strings.len(), NUM_TILE_KINDS);
+        for (indicator_string, dora_string) in expected_dora_strings {
+            let indicator = Tile::from_string(indicator_string);
+            let dora = Tile::from_string(dora_string);
+            assert_eq!(
+                DORA_FROM_INDICATOR[indicator.kind_index()],
+                dora.kind_index()
+            );
+            assert_eq!(indicator.dora_from_indicator().to_string(), dora_string);
+            assert!(dora.is_dora_from_indicator(&indicator));
+        }
+        // a red five indicator indicates the six
+        assert_eq!(
+            Tile::from_string("0s").dora_from_indicator().to_string(),
+            "6s"
+        );
+    }
+
+    #[test]
+    fn test_dora_tiles_from_indicators_wrap() {
+        let dora_tiles = dora_tiles_from_indicators(&tiles_from_string("9m9p9s4z7z"));
+        let dora_strings: Vec<String> = dora_tiles.iter().map(|tile| tile.to_string()).collect();
+        // 9 wraps to 1 in each numbered suit, north wraps to east, and red wraps to white
+        assert_eq!(dora_strings, vec!["1m", "1p", "1s", "1z", "5z"]);
+
+        assert!(dora_tiles_from_indicators(&vec![]).is_empty());
+    }
+
     #[test]
     fn test_is_dora_from_indicator_four() {
         let indicator = Tile::from_string("4m");
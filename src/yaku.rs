@@ -1,5 +1,8 @@
-use crate::{state, tile_grouping, tiles};
+use crate::{shanten, state, tile_grouping, tiles};
+use std::collections::{HashMap, HashSet};
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Yaku {
     // 1 han
     MenzenTsumo, // i.e. fully concealed hand, winning with a closed hand by self-draw
@@ -13,6 +16,7 @@ pub enum Yaku {
     Chankan,  // i.e. robbing a kan, winning on the tile added to another player's open triplet
     Tanyao,   // i.e. all simples, hand has no terminal or honor tiles
     Yakuhai,
+    OpenRiichi, // i.e. riichi with the hand revealed, a local yaku worth 1 han more than riichi
 
     // 2 han
     DoubleRiichi,
@@ -52,6 +56,12 @@ pub enum Yaku {
 
     // special case
     NagashiMangan, // at exhaustive draw, if a player only discarded terminals and honors, and none of their discards were called
+    Renhou, // i.e. hand of man, non-dealer wins by ron before their first draw (a local yaku, see `scoring::RenhouValue`)
+
+    // local yakuman (only scored if the rules allow local yaku)
+    Daisharin,   // i.e. big wheels, a closed hand of 22334455667788 in pinzu
+    Daichikurin, // i.e. big bamboo forest, a closed hand of 22334455667788 in souzu
+    Daisuurin,   // i.e. big number neighbors, a closed hand of 22334455667788 in manzu
 }
 
 impl Yaku {
@@ -59,6 +69,7 @@ impl Yaku {
         match yaku {
             Self::MenzenTsumo => 1,
             Self::Riichi => 1,
+            Self::OpenRiichi => 1,
             Self::Ippatsu => 1,
             Self::Pinfu => 1,
             Self::Iipeikou => 1,
@@ -97,6 +108,61 @@ impl Yaku {
             Self::Tenhou => 13,
             Self::Chiihou => 13,
             Self::NagashiMangan => 5, // this yaku is not compatible with other yaku but is worth mangan tsumo, which can be reached at 5 han
+            Self::Renhou => 5, // worth mangan under the rules that score it as mangan, and a yakuman under the rules that score it as a yakuman
+            Self::Daisharin => 13,
+            Self::Daichikurin => 13,
+            Self::Daisuurin => 13,
+        }
+    }
+
+    /// The human-readable name of the yaku, e.g. "Menzen Tsumo"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MenzenTsumo => "Menzen Tsumo",
+            Self::Riichi => "Riichi",
+            Self::OpenRiichi => "Open Riichi",
+            Self::Ippatsu => "Ippatsu",
+            Self::Pinfu => "Pinfu",
+            Self::Iipeikou => "Iipeikou",
+            Self::Haitei => "Haitei",
+            Self::Houtei => "Houtei",
+            Self::Rinshan => "Rinshan",
+            Self::Chankan => "Chankan",
+            Self::Tanyao => "Tanyao",
+            Self::Yakuhai => "Yakuhai",
+            Self::DoubleRiichi => "Double Riichi",
+            Self::Chanta => "Chanta",
+            Self::SanshokuDoujun => "Sanshoku Doujun",
+            Self::Ittsu => "Ittsu",
+            Self::Toitoi => "Toitoi",
+            Self::Sanankou => "Sanankou",
+            Self::SanshokuDoukou => "Sanshoku Doukou",
+            Self::Sankantsu => "Sankantsu",
+            Self::Chiitoitsu => "Chiitoitsu",
+            Self::Honroutou => "Honroutou",
+            Self::Shousangen => "Shousangen",
+            Self::Honitsu => "Honitsu",
+            Self::Junchan => "Junchan",
+            Self::Ryanpeikou => "Ryanpeikou",
+            Self::Chinitsu => "Chinitsu",
+            Self::KazoeYakuman => "Kazoe Yakuman",
+            Self::KokushiMusou => "Kokushi Musou",
+            Self::Suuankou => "Suuankou",
+            Self::Daisangen => "Daisangen",
+            Self::Shousuushii => "Shousuushii",
+            Self::Daisuushii => "Daisuushii",
+            Self::Tsuuiisou => "Tsuuiisou",
+            Self::Chinroutou => "Chinroutou",
+            Self::Ryuuiisou => "Ryuuiisou",
+            Self::ChuurenPoutou => "Chuuren Poutou",
+            Self::Suukantsu => "Suukantsu",
+            Self::Tenhou => "Tenhou",
+            Self::Chiihou => "Chiihou",
+            Self::NagashiMangan => "Nagashi Mangan",
+            Self::Renhou => "Renhou",
+            Self::Daisharin => "Daisharin",
+            Self::Daichikurin => "Daichikurin",
+            Self::Daisuurin => "Daisuurin",
         }
     }
 }
@@ -151,6 +217,20 @@ pub fn han_from_yakuhai_yaku(
     }
 }
 
+/// Menzen tsumo requires a closed hand (closed quads are allowed) that wins by self-draw
+pub fn has_menzen_tsumo(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> bool {
+    let is_hand_closed = !tile_grouping.iter().any(|tile_group| tile_group.is_open());
+    is_hand_closed
+        && matches!(
+            player_state.winning_tile_source,
+            Some(state::WinningTileSource::SelfDraw { .. })
+        )
+}
+
 pub fn has_riichi_yaku(
     _tile_grouping: &Vec<tiles::TileGroup>,
     _hand_state: &state::HandState,
@@ -160,6 +240,16 @@ pub fn has_riichi_yaku(
     player_state.in_riichi
 }
 
+/// Open riichi is a riichi declared with the hand revealed. It's a local yaku, so it's only scored if the rules
+/// allow it (see `scoring::LocalYakuConfig`), and it's worth 1 han on top of the riichi yaku (which is still scored).
+pub fn has_open_riichi(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> bool {
+    player_state.in_open_riichi && has_riichi_yaku(tile_grouping, hand_state, player_state)
+}
+
 pub fn has_tanyao(
     tile_grouping: &Vec<tiles::TileGroup>,
     _hand_state: &state::HandState,
@@ -203,6 +293,7 @@ pub fn has_tanyao(
 
 pub fn has_pinfu(
     tile_grouping: &Vec<tiles::TileGroup>,
+    winning_tile: &tiles::Tile,
     hand_state: &state::HandState,
     player_state: &state::PlayerState,
 ) -> bool {
@@ -244,653 +335,3842 @@ pub fn has_pinfu(
         // println!("need sequences only for pinfu!");
         return false;
     }
-    // TODO additionally must check the wait pattern (must be a open-wait i.e. two-sided wait, not a closed-wait, edge-wait, or pair-wait)
-    true
+    // the winning tile must complete a sequence with an open wait (not a closed wait, edge wait, or pair wait)
+    is_ryanmen_wait(tile_grouping, winning_tile)
 }
 
-// TODO do we assume that the winning grouping is already checked as a winning hand?
-// TODO we need to make sure that the fu is scored using the grouping that produces the maximum han
-pub fn scoring_fu(
-    player_tiles: &Vec<tiles::Tile>,
-    added_tile: &tiles::Tile,
-    tile_grouping: &Vec<tiles::TileGroup>,
-    hand_state: &state::HandState,
-    player_state: &state::PlayerState,
-) -> u32 {
-    let mut new_tiles = player_tiles.clone();
-    new_tiles.push(added_tile.clone());
-    let _existing_tile_groups: Vec<tiles::TileGroup> = Vec::new();
-
-    // special case: chiitoitsu (seven pairs) is always scored as 25 fu
-    if let Some(_seven_pairs_groups) =
-        tile_grouping::seven_pairs_tile_grouping(&new_tiles, &_existing_tile_groups)
-    {
-        return 25;
+/// The fu from a triplet or quad, which doubles if the tile is a terminal or honor, and doubles if the group is closed:
+/// an open triplet earns 2 fu (4 fu for terminals/honors), a closed triplet 4 fu (8 fu), an open kan (minkan or
+/// shouminkan) 8 fu (16 fu), and a closed kan (ankan) 16 fu (32 fu). Other groups earn no fu.
+pub fn fu_from_tile_group(tile_group: &tiles::TileGroup) -> u32 {
+    let (base_fu, open, tile) = match tile_group {
+        tiles::TileGroup::Triplet { open, tiles } => (2, *open, tiles[0]),
+        tiles::TileGroup::Quad { open, tiles, .. } => (8, *open, tiles[0]),
+        _ => return 0,
+    };
+    assert!(tile_group.is_valid());
+    let mut group_fu = base_fu;
+    if !tile.is_simple() {
+        group_fu *= 2;
     }
+    if !open {
+        group_fu *= 2;
+    }
+    group_fu
+}
 
-    // assert this is a winning hand, and get hand grouping(s)
-    let _tile_groups = tile_grouping::tile_grouping(&new_tiles, &_existing_tile_groups)
-        .expect("Should be a winning hand");
-
-    // TODO we need to make sure that the fu is consistent with the grouping that scores the maximum han
-
-    // fu from tile groups (triplets and quads earn fu based on open/closed and if the tile is simple or not)
-    let mut fu_from_groups = 0;
+/// The fu from the wait pattern, based on which group in the winning hand grouping the winning tile completed:
+/// a pair wait (tanki), closed wait (kanchan), or edge wait (penchan) earns 2 fu, and an open wait (ryanmen) or
+/// a wait on either of two pairs (shanpon) earns 0 fu. If the winning tile could have completed multiple groups
+/// in this grouping, the interpretation that earns the most fu is used.
+/// e.g. for a 3456 wait (nobetan), winning on 3 or 6 always completes the pair (33 + 456 or 345 + 66), so it's a pair wait.
+pub fn fu_from_wait(tile_grouping: &Vec<tiles::TileGroup>, winning_tile: &tiles::Tile) -> u32 {
+    let mut max_wait_fu = 0;
     for tile_group in tile_grouping {
-        fu_from_groups += match tile_group {
-            tiles::TileGroup::Triplet { open, tiles } => {
-                assert!(tile_group.is_valid());
-                let mut triplet_fu = 2;
-                if !tiles[0].is_simple() {
-                    triplet_fu *= 2;
-                }
-                if !open {
-                    triplet_fu *= 2;
-                }
-                triplet_fu
+        if tile_group.is_open() {
+            // called groups were already complete before the winning tile
+            continue;
+        }
+        let wait_fu = match tile_group {
+            tiles::TileGroup::Pair { tiles }
+                if tiles[0].kind_index() == winning_tile.kind_index() =>
+            {
+                2
             }
-            tiles::TileGroup::Quad { open, tiles, .. } => {
-                assert!(tile_group.is_valid());
-                let mut quad_fu = 8;
-                if !tiles[0].is_simple() {
-                    quad_fu *= 2;
-                }
-                if !open {
-                    quad_fu *= 2;
+            tiles::TileGroup::Sequence { tiles, .. } => {
+                match sequence_wait_is_ryanmen(tiles, winning_tile) {
+                    Some(false) => 2,
+                    _ => 0,
                 }
-                quad_fu
             }
             _ => 0,
         };
+        max_wait_fu = max_wait_fu.max(wait_fu);
     }
-    let fu_from_groups = fu_from_groups;
-
-    // TODO fu from waits
-    let fu_from_wait = 0;
+    max_wait_fu
+}
 
-    // fu from pair (earns 2 fu if the tile would be yakuhai, 4 fu if the wind is both seat and dealer wind)
-    let round_wind_rank = hand_state.round_wind.to_rank();
-    let seat_wind_rank = player_state.seat_wind.to_rank();
-    let pair_tile =
-        tiles::get_pair_group(&tile_grouping).expect("Should be a pair in winning hand");
-    let fu_from_pair = if is_yakuhai_tile(&pair_tile, round_wind_rank, seat_wind_rank) {
-        if pair_tile.rank() == round_wind_rank && pair_tile.rank() == seat_wind_rank {
-            4
-        } else {
-            2
+/// If the winning tile completed a closed sequence in the winning hand grouping with an open wait (ryanmen),
+/// which is required for pinfu. e.g. for a 3456 wait (nobetan), winning on 3 or 6 completes the pair, not a sequence.
+pub fn is_ryanmen_wait(tile_grouping: &Vec<tiles::TileGroup>, winning_tile: &tiles::Tile) -> bool {
+    tile_grouping.iter().any(|tile_group| match tile_group {
+        tiles::TileGroup::Sequence { open, tiles } => {
+            !open && sequence_wait_is_ryanmen(tiles, winning_tile) == Some(true)
         }
-    } else {
-        0
-    };
-
-    // fu from winning condition
-    let is_hand_closed: bool = true;
-    let winning_condition = player_state.winning_tile_source;
+        _ => false,
+    })
+}
 
-    let fu_from_winning_condition = match winning_condition.expect("Must be a winning tile source")
+/// If the winning tile is part of the sequence, whether the sequence was waiting on it with an open wait (ryanmen),
+/// as opposed to a closed wait (kanchan) or an edge wait (penchan). Returns None if the winning tile isn't in the sequence.
+fn sequence_wait_is_ryanmen(
+    sequence_tiles: &[tiles::Tile; 3],
+    winning_tile: &tiles::Tile,
+) -> Option<bool> {
+    if !sequence_tiles
+        .iter()
+        .any(|tile| tile.kind_index() == winning_tile.kind_index())
     {
-        state::WinningTileSource::Discard => {
-            if is_hand_closed {
-                10
-            } else {
-                // TODO if no fu from tile groups or waiting pattern, then the 20 fu hand is forced to 30 fu
-                0
+        return None;
+    }
+    let lowest_rank = sequence_tiles
+        .iter()
+        .map(|tile| {
+            tile.sequence_rank_num()
+                .expect("Sequence tile should be in a numbered suit")
+        })
+        .min()
+        .expect("Sequence should have tiles");
+    let winning_rank = winning_tile
+        .sequence_rank_num()
+        .expect("Winning tile in a sequence should be in a numbered suit");
+    if winning_rank == lowest_rank + 1 {
+        // closed wait (kanchan) e.g. 46 waiting on 5
+        Some(false)
+    } else if (winning_rank == lowest_rank && lowest_rank == 7)
+        || (winning_rank == lowest_rank + 2 && lowest_rank == 1)
+    {
+        // edge wait (penchan) i.e. 89 waiting on 7, or 12 waiting on 3
+        Some(false)
+    } else {
+        Some(true)
+    }
+}
+
+/// Ittsu can be scored with an open hand (the called sequences count towards the 123-456-789 run),
+/// but is worth 1 han less when the hand is open (2 han closed, 1 han open)
+pub fn han_from_ittsu_yaku(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> Option<u32> {
+    let mut is_hand_open = false;
+    // mapping of tile suit -> the starting ranks of the sequences that can be used for ittsu (i.e. 1, 4, or 7)
+    let mut tile_suit_to_ittsu_sequence_ranks: HashMap<tiles::TileSuit, HashSet<u32>> =
+        HashMap::new();
+    for tile_group in tile_grouping {
+        if tile_group.is_open() {
+            is_hand_open = true;
+        }
+        match tile_group {
+            tiles::TileGroup::Sequence { tiles, .. } => {
+                assert!(tile_group.is_valid());
+                let lowest_rank = tiles
+                    .iter()
+                    .map(|tile| {
+                        tile.sequence_rank_num()
+                            .expect("Sequence tile should be in a numbered suit")
+                    })
+                    .min()
+                    .expect("Sequence should have tiles");
+                if lowest_rank == 1 || lowest_rank == 4 || lowest_rank == 7 {
+                    tile_suit_to_ittsu_sequence_ranks
+                        .entry(tiles[0].suit())
+                        .or_default()
+                        .insert(lowest_rank);
+                }
             }
+            _ => continue,
         }
-        state::WinningTileSource::SelfDraw => {
-            // TODO if the closed hand with tsumo satisfies all other criteria for pinfu, these 2 fu are not awarded (the 1 han for pinfu is awarded instead)
-            2
+    }
+
+    for ittsu_sequence_ranks in tile_suit_to_ittsu_sequence_ranks.values() {
+        if ittsu_sequence_ranks.len() == 3 {
+            return if is_hand_open { Some(1) } else { Some(2) };
         }
-        state::WinningTileSource::DeadWall => {
-            // TODO some scoring rule variations (rishan fu) don't award 2 fu for tsumo win off of kan replacement tile, as winning off of this tile awards the rinshan yaku (1 han)
-            2
+    }
+    None
+}
+
+/// Sankantsu can be scored with an open hand: both called quads and closed quads count towards the three quads
+/// (four quads is the suukantsu yakuman instead)
+pub fn has_sankantsu(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    let num_quads = tile_grouping
+        .iter()
+        .filter(|tile_group| matches!(tile_group, tiles::TileGroup::Quad { .. }))
+        .count();
+    num_quads == 3
+}
+
+/// Sanshoku doujun can be scored with an open hand (the called sequences count towards the three sequences),
+/// but is worth 1 han less when the hand is open (2 han closed, 1 han open)
+pub fn han_from_sanshoku_doujun_yaku(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> Option<u32> {
+    let mut is_hand_open = false;
+    // mapping of the starting rank of a sequence -> the numbered suits that have a sequence starting at that rank
+    let mut sequence_rank_to_tile_suits: HashMap<u32, HashSet<tiles::TileSuit>> = HashMap::new();
+    for tile_group in tile_grouping {
+        if tile_group.is_open() {
+            is_hand_open = true;
         }
-        state::WinningTileSource::RobbingKan => {
-            // TODO is there any fu for robbing a kan?
-            0
+        match tile_group {
+            tiles::TileGroup::Sequence { tiles, .. } => {
+                assert!(tile_group.is_valid());
+                let lowest_rank = tiles
+                    .iter()
+                    .map(|tile| {
+                        tile.sequence_rank_num()
+                            .expect("Sequence tile should be in a numbered suit")
+                    })
+                    .min()
+                    .expect("Sequence should have tiles");
+                sequence_rank_to_tile_suits
+                    .entry(lowest_rank)
+                    .or_default()
+                    .insert(tiles[0].suit());
+            }
+            _ => continue,
         }
-    };
+    }
 
-    fu_from_groups + fu_from_pair + fu_from_wait + fu_from_winning_condition
+    for tile_suits in sequence_rank_to_tile_suits.values() {
+        if tile_suits.len() == 3 {
+            return if is_hand_open { Some(1) } else { Some(2) };
+        }
+    }
+    None
 }
 
-#[cfg(test)]
-mod tests {
-    // importing names from outer (for mod tests) scope.
-    use super::*;
+/// Chiitoitsu (seven pairs) is a closed hand of seven different pairs, which is scored with its seven pairs grouping
+/// (see `tile_grouping::seven_pairs_tile_grouping()`). A hand that can also be grouped as a standard hand is scored
+/// with whichever grouping scores more (see `max_han_and_fu_over_groupings()`).
+pub fn has_chiitoitsu(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    tile_grouping::number_pair_groups(tile_grouping) == 7
+}
 
-    #[test]
-    fn test_yakuhai_closed_white_dragon_triplet() {
-        // winning hands taken from my Mahjong Soul logs
-        // game: 4-player East round, Silver room, 2023-06-03 09:26
-        // round: East 4 (0 repeat), winning hand by West (open hand, ron)
-        // scoring: 4 han, 30 fu = 7700 pts (white dragon, dora x3 (7m, 8p))
-        let tile_groups: Vec<tiles::TileGroup> = vec![
-            tiles::TileGroup::Sequence {
-                open: true,
-                tiles: [
-                    tiles::Tile::from_string("4s"),
-                    tiles::Tile::from_string("5s"),
-                    tiles::Tile::from_string("3s"),
-                ],
-            },
-            tiles::TileGroup::Triplet {
-                open: true,
-                tiles: [
-                    tiles::Tile::from_string("7m"),
-                    tiles::Tile::from_string("7m"),
-                    tiles::Tile::from_string("7m"),
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("3m"),
-                    tiles::Tile::from_string("4m"),
-                    tiles::Tile::from_string("2m"),
-                ],
-            },
-            tiles::TileGroup::Triplet {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("5z"), // white dragon
-                    tiles::Tile::from_string("5z"),
-                    tiles::Tile::from_string("5z"),
-                ],
-            },
-            tiles::TileGroup::Pair {
-                tiles: [
-                    tiles::Tile::from_string("8m"),
-                    tiles::Tile::from_string("8m"),
-                ],
-            },
-        ];
+/// Iipeikou can only be scored with a closed hand, and requires exactly one pair of identical sequences
+/// (two pairs of identical sequences is ryanpeikou instead, see `has_ryanpeikou()`)
+pub fn has_iipeikou(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    num_identical_sequence_pairs(tile_grouping) == 1
+}
 
-        // check yaku
-        let hand_state = state::HandState {
-            round_wind: state::WindDirection::East,
-            any_calls_made: true,
-            tiles_remaining: 12,
-            dora_indicators: vec![
-                tiles::Tile::from_string("6m"),
-                tiles::Tile::from_string("7p"),
-            ],
-            riichi_sticks: 1,
-            honba_sticks: 0,
-        };
-        let player_state = state::PlayerState {
-            discards: vec![
-                tiles::Tile::from_string("4z"),
-                tiles::Tile::from_string("3z"),
-                tiles::Tile::from_string("8p"),
-                tiles::Tile::from_string("1s"),
-                tiles::Tile::from_string("1z"),
-                tiles::Tile::from_string("1p"),
-                tiles::Tile::from_string("2p"),
-                tiles::Tile::from_string("9s"),
-                tiles::Tile::from_string("9s"),
-                tiles::Tile::from_string("0p"),
-                tiles::Tile::from_string("2p"),
-                tiles::Tile::from_string("6m"),
-                tiles::Tile::from_string("4p"),
-                tiles::Tile::from_string("8m"),
-                tiles::Tile::from_string("1s"),
-            ],
-            seat_wind: state::WindDirection::West,
-            in_riichi: false,
-            in_double_riichi: false,
-            in_ippatsu_turn: false,
-            any_discards_called_by_others: false,
-            winning_tile_source: Some(state::WinningTileSource::Discard), // from East (opposite player / toimen)
-        };
-        assert_eq!(
-            han_from_yakuhai_yaku(&tile_groups, &hand_state, &player_state),
-            Some(1)
-        );
+/// Ryanpeikou can only be scored with a closed hand, and requires two pairs of identical sequences
+/// (it replaces iipeikou, and a hand with ryanpeikou can't also score chiitoitsu)
+pub fn has_ryanpeikou(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    num_identical_sequence_pairs(tile_grouping) == 2
+}
+
+/// The number of pairs of identical sequences in a closed hand grouping (0 if the hand is open)
+fn num_identical_sequence_pairs(tile_grouping: &[tiles::TileGroup]) -> usize {
+    if tile_grouping.iter().any(|tile_group| tile_group.is_open()) {
+        return 0;
     }
+    // the number of sequences starting at each tile kind
+    let mut sequence_counts = [0usize; tiles::NUM_TILE_KINDS];
+    for tile_group in tile_grouping {
+        if let tiles::TileGroup::Sequence { tiles, .. } = tile_group {
+            let lowest_kind_index = tiles
+                .iter()
+                .map(|tile| tile.kind_index())
+                .min()
+                .expect("Sequence should have tiles");
+            sequence_counts[lowest_kind_index] += 1;
+        }
+    }
+    sequence_counts.iter().map(|count| count / 2).sum()
+}
 
-    #[test]
-    fn test_yakuhai_same_round_and_seat_wind() {
-        // test multiple han from yakuhai
-        let tile_groups: Vec<tiles::TileGroup> = vec![
-            tiles::TileGroup::Triplet {
-                open: true,
-                tiles: [
-                    tiles::Tile::from_string("2z"), // south wind
-                    tiles::Tile::from_string("2z"),
-                    tiles::Tile::from_string("2z"),
-                ],
-            },
-            tiles::TileGroup::Triplet {
-                open: true,
-                tiles: [
-                    tiles::Tile::from_string("1z"), // east wind
-                    tiles::Tile::from_string("1z"),
-                    tiles::Tile::from_string("1z"),
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("1s"),
+/// The yaku (out of the yaku that are scored so far) present in the winning hand grouping, each listed once
+pub fn get_yaku_list(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    winning_tile: &tiles::Tile,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Vec<Yaku> {
+    let mut yaku_list: Vec<Yaku> = Vec::new();
+    if has_menzen_tsumo(tile_grouping, hand_state, player_state) {
+        yaku_list.push(Yaku::MenzenTsumo);
+    }
+    if has_riichi_yaku(tile_grouping, hand_state, player_state) {
+        yaku_list.push(Yaku::Riichi);
+    }
+    if has_tanyao(tile_grouping, hand_state, player_state) {
+        yaku_list.push(Yaku::Tanyao);
+    }
+    if has_pinfu(tile_grouping, winning_tile, hand_state, player_state) {
+        yaku_list.push(Yaku::Pinfu);
+    }
+    if has_iipeikou(tile_grouping, hand_state, player_state) {
+        yaku_list.push(Yaku::Iipeikou);
+    }
+    if han_from_yakuhai_yaku(tile_grouping, hand_state, player_state).is_some() {
+        yaku_list.push(Yaku::Yakuhai);
+    }
+    if han_from_ittsu_yaku(tile_grouping, hand_state, player_state).is_some() {
+        yaku_list.push(Yaku::Ittsu);
+    }
+    if han_from_sanshoku_doujun_yaku(tile_grouping, hand_state, player_state).is_some() {
+        yaku_list.push(Yaku::SanshokuDoujun);
+    }
+    if has_sanshoku_doukou(tile_grouping, hand_state, player_state) {
+        yaku_list.push(Yaku::SanshokuDoukou);
+    }
+    if has_sanankou(tile_grouping, winning_tile, hand_state, player_state) {
+        yaku_list.push(Yaku::Sanankou);
+    }
+    if has_sankantsu(tile_grouping, hand_state, player_state) {
+        yaku_list.push(Yaku::Sankantsu);
+    }
+    if has_chiitoitsu(tile_grouping, hand_state, player_state) {
+        yaku_list.push(Yaku::Chiitoitsu);
+    }
+    if has_ryanpeikou(tile_grouping, hand_state, player_state) {
+        yaku_list.push(Yaku::Ryanpeikou);
+    }
+    yaku_list
+}
+
+/// For a tenpai hand, the maximum number of distinct yaku that the hand can score across all of its waits
+/// (and across all groupings of each winning hand). Useful for deciding whether calling a tile (i.e. opening
+/// the hand) gives up too much value. Returns 0 if the hand is not in tenpai.
+pub fn max_num_yaku_across_waits(
+    tiles: &Vec<tiles::Tile>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> usize {
+    if shanten::get_shanten(tiles) != 0 {
+        return 0;
+    }
+    let mut max_num_yaku = 0;
+    for wait_tile in shanten::get_ukiere(tiles) {
+        let mut winning_tiles = tiles.clone();
+        winning_tiles.push(wait_tile);
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+        if let Some(groupings) = tile_grouping::tile_grouping(&winning_tiles, &empty_groups) {
+            for grouping in groupings.iter() {
+                let num_yaku = get_yaku_list(grouping, &wait_tile, hand_state, player_state).len();
+                max_num_yaku = max_num_yaku.max(num_yaku);
+            }
+        }
+    }
+    max_num_yaku
+}
+
+/// Sanshoku doukou can be scored with an open hand: a triplet (or quad) of the same rank in each of the three numbered
+/// suits, where called triplets and quads count towards the three triplets
+pub fn has_sanshoku_doukou(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    // mapping of the rank of a triplet (or quad) -> the numbered suits that have a triplet of that rank
+    let mut triplet_rank_to_tile_suits: HashMap<u32, HashSet<tiles::TileSuit>> = HashMap::new();
+    for tile_group in tile_grouping {
+        let tile = match tile_group {
+            tiles::TileGroup::Triplet { tiles, .. } => tiles[0],
+            tiles::TileGroup::Quad { tiles, .. } => tiles[0],
+            _ => continue,
+        };
+        if let Some(rank) = tile.sequence_rank_num() {
+            triplet_rank_to_tile_suits
+                .entry(rank)
+                .or_default()
+                .insert(tile.suit());
+        }
+    }
+    triplet_rank_to_tile_suits
+        .values()
+        .any(|tile_suits| tile_suits.len() == 3)
+}
+
+/// The total han from the yaku that are scored so far (menzen tsumo, riichi, tanyao, pinfu, iipeikou, yakuhai, ittsu, sanshoku doujun, sanshoku doukou, sanankou, sankantsu, chiitoitsu, and ryanpeikou), not including dora
+pub fn han_from_yaku(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    winning_tile: &tiles::Tile,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> u32 {
+    let mut han: u32 = 0;
+    if has_menzen_tsumo(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::MenzenTsumo);
+    }
+    if has_riichi_yaku(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Riichi);
+    }
+    if has_tanyao(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Tanyao);
+    }
+    if has_pinfu(tile_grouping, winning_tile, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Pinfu);
+    }
+    if has_iipeikou(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Iipeikou);
+    }
+    han += han_from_yakuhai_yaku(tile_grouping, hand_state, player_state).unwrap_or(0);
+    han += han_from_ittsu_yaku(tile_grouping, hand_state, player_state).unwrap_or(0);
+    han += han_from_sanshoku_doujun_yaku(tile_grouping, hand_state, player_state).unwrap_or(0);
+    if has_sanshoku_doukou(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::SanshokuDoukou);
+    }
+    if has_sanankou(tile_grouping, winning_tile, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Sanankou);
+    }
+    if has_sankantsu(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Sankantsu);
+    }
+    if has_chiitoitsu(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Chiitoitsu);
+    }
+    if has_ryanpeikou(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Ryanpeikou);
+    }
+    han
+}
+
+/// The han from dora in the winning hand grouping: 1 han for each tile in the hand (including called groups) that is
+/// a dora tile, counted once per dora indicator that indicates it. Ura dora are only counted if the player declared
+/// riichi (or double riichi), and are ignored otherwise. Dora aren't a yaku, so they don't make a hand a winning hand.
+pub fn han_from_dora(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> u32 {
+    let mut han = num_dora_from_indicators(tile_grouping, &hand_state.dora_indicators);
+    if player_state.in_riichi || player_state.in_double_riichi {
+        han += num_dora_from_indicators(tile_grouping, &hand_state.ura_dora_indicators);
+    }
+    han
+}
+
+/// The number of dora in the winning hand grouping (including called groups) from the dora indicators, where a tile
+/// is counted once per dora indicator that indicates it (see `han_from_dora`)
+pub fn num_dora_from_indicators(
+    tile_grouping: &[tiles::TileGroup],
+    dora_indicators: &[tiles::Tile],
+) -> u32 {
+    let mut num_dora: u32 = 0;
+    for tile in tile_grouping
+        .iter()
+        .flat_map(|tile_group| tile_group.tiles())
+    {
+        num_dora += dora_indicators
+            .iter()
+            .filter(|indicator| tile.is_dora_from_indicator(indicator))
+            .count() as u32;
+    }
+    num_dora
+}
+
+/// The han from red fives (aka dora) in the winning hand grouping: 1 han for each red five in the hand (including
+/// called groups). A red five is otherwise the same as a normal five, so it can also be a dora (see `han_from_dora`).
+pub fn han_from_red_fives(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> u32 {
+    tile_grouping
+        .iter()
+        .flat_map(|tile_group| tile_group.tiles())
+        .filter(|tile| tile.is_red_five())
+        .count() as u32
+}
+
+/// The expected number of ura dora in the hand when winning after riichi, where each ura dora indicator
+/// (one, plus one more for each kan) is equally likely to be any of the unseen tiles (not in the hand or visible).
+/// Since every ura dora indicator is flipped independently of the hand's shape, the expectation for each indicator
+/// is the sum over each possible indicator of (chance of that indicator) * (tiles in the hand that it makes dora).
+pub fn expected_num_ura_dora(
+    tiles: &Vec<tiles::Tile>,
+    visible_tiles: &Vec<tiles::Tile>,
+    num_ura_indicators: u32,
+) -> f64 {
+    let mut seen_tiles = tiles.clone();
+    seen_tiles.extend(visible_tiles.iter());
+    let seen_counts = shanten::count_tiles_by_kind(&seen_tiles);
+    let num_unseen: u32 = seen_counts.iter().map(|count| 4 - *count as u32).sum();
+    if num_unseen == 0 {
+        return 0.0;
+    }
+
+    let mut expected_ura_per_indicator = 0.0;
+    for (kind_index, seen_count) in seen_counts.iter().enumerate() {
+        let num_live = 4 - *seen_count as u32;
+        if num_live == 0 {
+            continue;
+        }
+        let indicator = tiles::Tile::from_kind_index(kind_index);
+        let num_dora_in_hand = tiles
+            .iter()
+            .filter(|tile| tile.is_dora_from_indicator(&indicator))
+            .count();
+        expected_ura_per_indicator +=
+            (num_live as f64 / num_unseen as f64) * num_dora_in_hand as f64;
+    }
+    num_ura_indicators as f64 * expected_ura_per_indicator
+}
+
+/// Daisangen can be scored with an open hand: a triplet (or quad) of each of the three dragons
+pub fn has_daisangen(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    let num_dragon_groups = tile_grouping
+        .iter()
+        .filter(|tile_group| match tile_group {
+            tiles::TileGroup::Triplet { tiles, .. } => tiles[0].is_dragon(),
+            tiles::TileGroup::Quad { tiles, .. } => tiles[0].is_dragon(),
+            _ => false,
+        })
+        .count();
+    num_dragon_groups == 3
+}
+
+/// Whether the winning tile completed a closed triplet in the winning hand grouping from a discard (by ron), i.e. on a
+/// shanpon wait (wait on either of two pairs). Such a triplet isn't concealed: it's scored as an open triplet for fu,
+/// and it doesn't count towards sanankou or suuankou. If the winning tile could instead have completed the pair or a
+/// sequence in the grouping, that interpretation is used and the triplet stays concealed.
+pub fn is_triplet_completed_by_ron(
+    tile_grouping: &[tiles::TileGroup],
+    winning_tile: &tiles::Tile,
+    player_state: &state::PlayerState,
+) -> bool {
+    let is_ron = matches!(
+        player_state.winning_tile_source,
+        Some(state::WinningTileSource::Discard { .. }) | Some(state::WinningTileSource::RobbingKan)
+    );
+    if !is_ron {
+        return false;
+    }
+    let is_winning_kind = |tile: &tiles::Tile| tile.kind_index() == winning_tile.kind_index();
+    let completes_pair_or_sequence = tile_grouping.iter().any(|tile_group| match tile_group {
+        tiles::TileGroup::Pair { tiles } => is_winning_kind(&tiles[0]),
+        tiles::TileGroup::Sequence { open: false, tiles } => tiles.iter().any(is_winning_kind),
+        _ => false,
+    });
+    let completes_triplet = tile_grouping.iter().any(|tile_group| match tile_group {
+        tiles::TileGroup::Triplet { open: false, tiles } => is_winning_kind(&tiles[0]),
+        _ => false,
+    });
+    completes_triplet && !completes_pair_or_sequence
+}
+
+// the number of concealed triplets (closed triplets and closed quads) in the winning hand grouping, not including a
+// triplet completed by ron (see `is_triplet_completed_by_ron`)
+fn num_concealed_triplets(
+    tile_grouping: &[tiles::TileGroup],
+    winning_tile: &tiles::Tile,
+    player_state: &state::PlayerState,
+) -> usize {
+    let num_closed_triplets = tile_grouping
+        .iter()
+        .filter(|tile_group| match tile_group {
+            tiles::TileGroup::Triplet { open, .. } => !open,
+            tiles::TileGroup::Quad { open, .. } => !open,
+            _ => false,
+        })
+        .count();
+    if is_triplet_completed_by_ron(tile_grouping, winning_tile, player_state) {
+        num_closed_triplets - 1
+    } else {
+        num_closed_triplets
+    }
+}
+
+/// Sanankou can be scored with an open hand: three concealed triplets (closed triplets or closed quads). A triplet
+/// completed by ron isn't concealed (see `is_triplet_completed_by_ron`), so the yaku depends on the winning tile.
+pub fn has_sanankou(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    winning_tile: &tiles::Tile,
+    _hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> bool {
+    num_concealed_triplets(tile_grouping, winning_tile, player_state) == 3
+}
+
+/// Suuankou requires four closed triplets (or closed quads). A triplet completed by ron isn't concealed, so winning
+/// on a shanpon wait (wait on either of two pairs) by ron is only sanankou, while winning by tsumo is suuankou.
+/// Winning on a pair wait (tanki) with four closed triplets is a double yakuman (26 han instead of 13 han).
+pub fn han_from_suuankou_yaku(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    winning_tile: &tiles::Tile,
+    _hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Option<u32> {
+    let is_tanki_wait = tile_grouping.iter().any(|tile_group| match tile_group {
+        tiles::TileGroup::Pair { tiles } => tiles[0].kind_index() == winning_tile.kind_index(),
+        _ => false,
+    });
+    if num_concealed_triplets(tile_grouping, winning_tile, player_state) < 4 {
+        None
+    } else if is_tanki_wait {
+        Some(2 * Yaku::han_value(&Yaku::Suuankou))
+    } else {
+        Some(Yaku::han_value(&Yaku::Suuankou))
+    }
+}
+
+/// Kokushi musou (thirteen orphans) is one of each terminal and honor tile, plus one more terminal or honor tile as the
+/// pair. It isn't made of groups, so it's checked from the player's tiles (before the winning tile) and the winning tile.
+/// Winning on a 13-sided wait (all 13 different terminals and honors before the winning tile) is a double yakuman.
+pub fn han_from_kokushi_yaku(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> Option<u32> {
+    if player_tiles.len() != 13
+        || player_tiles
+            .iter()
+            .chain(std::iter::once(winning_tile))
+            .any(|tile| !tile.is_terminal() && !tile.is_honor())
+    {
+        return None;
+    }
+    let mut winning_tiles = player_tiles.clone();
+    winning_tiles.push(*winning_tile);
+    let num_kinds = shanten::count_tiles_by_kind(&winning_tiles)
+        .iter()
+        .filter(|count| **count >= 1)
+        .count();
+    if num_kinds != 13 {
+        return None;
+    }
+
+    let is_thirteen_sided_wait = shanten::count_tiles_by_kind(player_tiles)
+        .iter()
+        .all(|count| *count <= 1);
+    if is_thirteen_sided_wait {
+        Some(2 * Yaku::han_value(&Yaku::KokushiMusou))
+    } else {
+        Some(Yaku::han_value(&Yaku::KokushiMusou))
+    }
+}
+
+// the number of tiles of each rank (1 to 9) in the nine gates pattern 1112345678999
+const CHUUREN_POUTOU_RANK_COUNTS: [u8; 9] = [3, 1, 1, 1, 1, 1, 1, 1, 3];
+
+/// Chuuren poutou (nine gates) is a closed hand of a single numbered suit with the pattern 1112345678999, plus one more
+/// tile of the same suit. Since the groups are ambiguous, it's checked from the tile counts of the player's tiles
+/// (before the winning tile) and the winning tile. Winning on the pure nine-sided wait (the player's tiles are exactly
+/// 1112345678999, waiting on any tile of the suit) is a double yakuman.
+pub fn han_from_chuuren_poutou_yaku(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> Option<u32> {
+    // the hand must be closed without any quads, i.e. all 13 tiles before the winning tile are concealed
+    if player_tiles.len() != 13
+        || !winning_tile.is_number_suit()
+        || player_tiles
+            .iter()
+            .any(|tile| tile.suit() != winning_tile.suit())
+    {
+        return None;
+    }
+    let mut winning_tiles = player_tiles.clone();
+    winning_tiles.push(*winning_tile);
+    let suit_start_index = winning_tile.kind_index() - winning_tile.kind_index() % 9;
+    let winning_counts = shanten::count_tiles_by_kind(&winning_tiles);
+    let is_chuuren_poutou = (0..9).all(|rank_index| {
+        winning_counts[suit_start_index + rank_index] >= CHUUREN_POUTOU_RANK_COUNTS[rank_index]
+    });
+    if !is_chuuren_poutou {
+        return None;
+    }
+
+    let player_counts = shanten::count_tiles_by_kind(player_tiles);
+    let is_nine_sided_wait = (0..9).all(|rank_index| {
+        player_counts[suit_start_index + rank_index] == CHUUREN_POUTOU_RANK_COUNTS[rank_index]
+    });
+    if is_nine_sided_wait {
+        Some(2 * Yaku::han_value(&Yaku::ChuurenPoutou))
+    } else {
+        Some(Yaku::han_value(&Yaku::ChuurenPoutou))
+    }
+}
+
+/// Nagashi mangan is a special hand at an exhaustive draw: every tile the player discarded is a terminal or honor tile,
+/// and none of their discards were called by other players
+pub fn is_nagashi_mangan(discards: &Vec<tiles::Tile>, any_discards_called_by_others: bool) -> bool {
+    !discards.is_empty()
+        && !any_discards_called_by_others
+        && discards
+            .iter()
+            .all(|tile| tile.is_terminal() || tile.is_honor())
+}
+
+/// The yakuman in the winning hand grouping (the yakuman counterpart of `get_yaku_list`)
+pub fn get_yakuman_list(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Vec<Yaku> {
+    let mut yakuman_list: Vec<Yaku> = Vec::new();
+    if han_from_suuankou_yaku(tile_grouping, winning_tile, hand_state, player_state).is_some() {
+        yakuman_list.push(Yaku::Suuankou);
+    }
+    if has_daisangen(tile_grouping, hand_state, player_state) {
+        yakuman_list.push(Yaku::Daisangen);
+    }
+    if han_from_daisuushii_yaku(tile_grouping, hand_state, player_state).is_some() {
+        yakuman_list.push(Yaku::Daisuushii);
+    }
+    if has_shousuushii(tile_grouping, hand_state, player_state) {
+        yakuman_list.push(Yaku::Shousuushii);
+    }
+    if has_tsuuiisou(tile_grouping, hand_state, player_state) {
+        yakuman_list.push(Yaku::Tsuuiisou);
+    }
+    if has_chinroutou(tile_grouping, hand_state, player_state) {
+        yakuman_list.push(Yaku::Chinroutou);
+    }
+    if has_suukantsu(tile_grouping, hand_state, player_state) {
+        yakuman_list.push(Yaku::Suukantsu);
+    }
+    if has_tenhou(tile_grouping, hand_state, player_state) {
+        yakuman_list.push(Yaku::Tenhou);
+    }
+    if has_chiihou(tile_grouping, hand_state, player_state) {
+        yakuman_list.push(Yaku::Chiihou);
+    }
+    if han_from_chuuren_poutou_yaku(player_tiles, winning_tile, hand_state, player_state).is_some()
+    {
+        yakuman_list.push(Yaku::ChuurenPoutou);
+    }
+    yakuman_list
+}
+
+/// The total han from the yakuman in the winning hand grouping (13 han per yakuman, or 26 han for a double yakuman),
+/// the yakuman counterpart of `han_from_yaku`
+pub fn han_from_yakuman(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> u32 {
+    let mut han: u32 = 0;
+    han +=
+        han_from_suuankou_yaku(tile_grouping, winning_tile, hand_state, player_state).unwrap_or(0);
+    if has_daisangen(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Daisangen);
+    }
+    han += han_from_daisuushii_yaku(tile_grouping, hand_state, player_state).unwrap_or(0);
+    if has_shousuushii(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Shousuushii);
+    }
+    if has_tsuuiisou(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Tsuuiisou);
+    }
+    if has_chinroutou(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Chinroutou);
+    }
+    if has_suukantsu(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Suukantsu);
+    }
+    if has_tenhou(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Tenhou);
+    }
+    if has_chiihou(tile_grouping, hand_state, player_state) {
+        han += Yaku::han_value(&Yaku::Chiihou);
+    }
+    han += han_from_chuuren_poutou_yaku(player_tiles, winning_tile, hand_state, player_state)
+        .unwrap_or(0);
+    han
+}
+
+/// Daisuushii (big four winds) can be scored with an open hand: a triplet (or quad) of each of the four winds.
+/// It's a double yakuman (26 han instead of 13 han).
+pub fn han_from_daisuushii_yaku(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> Option<u32> {
+    if num_wind_triplets(tile_grouping) == 4 {
+        Some(2 * Yaku::han_value(&Yaku::Daisuushii))
+    } else {
+        None
+    }
+}
+
+/// Shousuushii (small four winds) can be scored with an open hand: a triplet (or quad) of three of the winds,
+/// and a pair of the fourth wind. A hand with all four wind triplets is daisuushii instead.
+pub fn has_shousuushii(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    let has_wind_pair = tile_grouping.iter().any(|tile_group| match tile_group {
+        tiles::TileGroup::Pair { tiles } => tiles[0].is_wind(),
+        _ => false,
+    });
+    num_wind_triplets(tile_grouping) == 3 && has_wind_pair
+}
+
+fn num_wind_triplets(tile_grouping: &[tiles::TileGroup]) -> usize {
+    tile_grouping
+        .iter()
+        .filter(|tile_group| match tile_group {
+            tiles::TileGroup::Triplet { tiles, .. } => tiles[0].is_wind(),
+            tiles::TileGroup::Quad { tiles, .. } => tiles[0].is_wind(),
+            _ => false,
+        })
+        .count()
+}
+
+/// Suukantsu (four quads) can be scored with an open hand: both called quads and closed quads count
+pub fn has_suukantsu(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    let num_quads = tile_grouping
+        .iter()
+        .filter(|tile_group| matches!(tile_group, tiles::TileGroup::Quad { .. }))
+        .count();
+    num_quads == 4
+}
+
+/// Tenhou (heavenly hand) is the dealer winning by self-draw with their initial hand,
+/// i.e. before their first discard (and before any calls were made, see `is_first_draw_win`)
+pub fn has_tenhou(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> bool {
+    matches!(player_state.seat_wind, state::WindDirection::East)
+        && is_first_draw_win(tile_grouping, hand_state, player_state)
+}
+
+/// Chiihou (earthly hand) is a non-dealer winning by self-draw on their first draw,
+/// i.e. before their first discard (and before any calls were made, see `is_first_draw_win`)
+pub fn has_chiihou(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> bool {
+    !matches!(player_state.seat_wind, state::WindDirection::East)
+        && is_first_draw_win(tile_grouping, hand_state, player_state)
+}
+
+/// Renhou (hand of man) is a non-dealer winning by ron in the first go-around, i.e. before their first draw
+/// (and before any calls were made), as set by the winning discard (see `state::WinningTileSource::Discard`).
+/// It's a local yaku, so it's only scored if the rules allow it (see `scoring::ScoringRules::renhou`),
+/// and isn't included in the yaku or yakuman from this module.
+pub fn has_renhou(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> bool {
+    !matches!(player_state.seat_wind, state::WindDirection::East)
+        && !has_any_calls(tile_grouping, hand_state)
+        && matches!(
+            player_state.winning_tile_source,
+            Some(state::WinningTileSource::Discard {
+                is_first_go_around: true
+            })
+        )
+}
+
+/// The local yakuman for a closed hand with two of each of the 2-8 tiles of a single suit (see `Yaku::Daisharin`),
+/// from the number of tiles of each tile kind in the winning hand (see `shanten::count_tiles_by_kind()`).
+/// The hand must be closed, which isn't checked here since the counts don't include the called groups.
+/// It's a local yaku, so it's only scored if the rules allow it (see `scoring::LocalYakuConfig`).
+pub fn daisharin_yaku_from_counts(tile_kind_counts: &[u8; tiles::NUM_TILE_KINDS]) -> Option<Yaku> {
+    let suit_yaku = [Yaku::Daisuurin, Yaku::Daisharin, Yaku::Daichikurin];
+    for (suit_index, yaku) in suit_yaku.into_iter().enumerate() {
+        let has_two_of_each = (0..tiles::NUM_TILE_KINDS).all(|kind_index| {
+            let is_two_to_eight =
+                kind_index / 9 == suit_index && (1..=7).contains(&(kind_index % 9));
+            tile_kind_counts[kind_index] == if is_two_to_eight { 2 } else { 0 }
+        });
+        if has_two_of_each {
+            return Some(yaku);
+        }
+    }
+    None
+}
+
+/// Whether the player won by self-draw in the first go-around, as set by the winning draw
+/// (see `state::WinningTileSource::SelfDraw`), and no calls were made
+fn is_first_draw_win(
+    tile_grouping: &[tiles::TileGroup],
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> bool {
+    !has_any_calls(tile_grouping, hand_state)
+        && matches!(
+            player_state.winning_tile_source,
+            Some(state::WinningTileSource::SelfDraw {
+                is_first_go_around: true
+            })
+        )
+}
+
+/// Whether any calls were made, which interrupts the first go-around
+fn has_any_calls(tile_grouping: &[tiles::TileGroup], hand_state: &state::HandState) -> bool {
+    // any call (including a closed quad) interrupts the first go-around
+    let has_calls = tile_grouping.iter().any(|tile_group| {
+        matches!(tile_group, tiles::TileGroup::Quad { .. }) || tile_group.is_open()
+    });
+    has_calls || hand_state.any_calls_made
+}
+
+/// Chinroutou (all terminals) can be scored with an open hand: every group (including the pair) is made of
+/// terminal tiles, so the hand is always all triplets (toitoi)
+pub fn has_chinroutou(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    tile_grouping.iter().all(|tile_group| match tile_group {
+        tiles::TileGroup::Triplet { tiles, .. } => tiles[0].is_terminal(),
+        tiles::TileGroup::Quad { tiles, .. } => tiles[0].is_terminal(),
+        tiles::TileGroup::Pair { tiles } => tiles[0].is_terminal(),
+        // a sequence always has a simple tile in the middle
+        _ => false,
+    })
+}
+
+/// Tsuuiisou (all honors) can be scored with an open hand: every group (including the pair) is made of honor tiles,
+/// so the hand is always all triplets (toitoi) or seven pairs (chiitoitsu)
+pub fn has_tsuuiisou(
+    tile_grouping: &Vec<tiles::TileGroup>,
+    _hand_state: &state::HandState,
+    _player_state: &state::PlayerState,
+) -> bool {
+    tile_grouping.iter().all(|tile_group| match tile_group {
+        tiles::TileGroup::Triplet { tiles, .. } => tiles[0].is_honor(),
+        tiles::TileGroup::Quad { tiles, .. } => tiles[0].is_honor(),
+        tiles::TileGroup::Pair { tiles } => tiles[0].is_honor(),
+        // a sequence is always made of numbered tiles
+        _ => false,
+    })
+}
+
+/// Only checks for yakuman (and skips the regular yaku), since a yakuman hand isn't scored with han and fu.
+/// Returns the yakuman in the winning hand grouping, or None if there aren't any.
+pub fn check_yakuman(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Option<Vec<Yaku>> {
+    let yakuman_list = get_yakuman_list(
+        player_tiles,
+        winning_tile,
+        tile_grouping,
+        hand_state,
+        player_state,
+    );
+    if yakuman_list.is_empty() {
+        None
+    } else {
+        Some(yakuman_list)
+    }
+}
+
+/// The (han, fu) of the winning hand grouping. If the hand has any yakuman, the han is the total of the yakuman
+/// (13 han each) and the fu isn't computed (returned as 0), since yakuman are scored without fu.
+pub fn han_and_fu(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> (u32, u32) {
+    if check_yakuman(
+        player_tiles,
+        winning_tile,
+        tile_grouping,
+        hand_state,
+        player_state,
+    )
+    .is_some()
+    {
+        let yakuman_han = han_from_yakuman(
+            player_tiles,
+            winning_tile,
+            tile_grouping,
+            hand_state,
+            player_state,
+        );
+        return (yakuman_han, 0);
+    }
+    (
+        han_from_yaku(tile_grouping, winning_tile, hand_state, player_state),
+        scoring_fu(
+            player_tiles,
+            winning_tile,
+            tile_grouping,
+            hand_state,
+            player_state,
+        ),
+    )
+}
+
+/// The (han, fu) of the winning hand (the player's tiles and the winning tile, with the declared groups) if it was won
+/// by ron, and if it was won by tsumo, each using the grouping that scores the most (see
+/// `max_han_and_fu_over_groupings()`). The groupings are only enumerated once for both. Returns None if the tiles
+/// aren't a winning hand.
+pub fn han_and_fu_ron_and_tsumo(
+    player_tiles: &Vec<tiles::Tile>,
+    declared_groups: &Vec<tiles::TileGroup>,
+    winning_tile: &tiles::Tile,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Option<((u32, u32), (u32, u32))> {
+    let mut ron_player_state = player_state.clone();
+    ron_player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+        is_first_go_around: false,
+    });
+    let mut tsumo_player_state = player_state.clone();
+    tsumo_player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+        is_first_go_around: false,
+    });
+
+    let mut winning_tiles = player_tiles.clone();
+    winning_tiles.push(*winning_tile);
+    let groupings = tile_grouping::all_winning_groupings(&winning_tiles, declared_groups);
+    let (_, ron_han_and_fu) = max_han_and_fu_in_groupings(
+        groupings.clone(),
+        player_tiles,
+        winning_tile,
+        hand_state,
+        &ron_player_state,
+    )?;
+    let (_, tsumo_han_and_fu) = max_han_and_fu_in_groupings(
+        groupings,
+        player_tiles,
+        winning_tile,
+        hand_state,
+        &tsumo_player_state,
+    )?;
+    Some((ron_han_and_fu, tsumo_han_and_fu))
+}
+
+/// The grouping of the winning hand (the player's tiles and the winning tile) that scores the most (by han, then by fu)
+/// and its (han, fu), out of all of its standard groupings and its seven pairs grouping
+/// (see `tile_grouping::all_winning_groupings()`). Returns None if the tiles aren't a winning hand.
+pub fn max_han_and_fu_over_groupings(
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Option<(Vec<tiles::TileGroup>, (u32, u32))> {
+    let mut winning_tiles = player_tiles.clone();
+    winning_tiles.push(*winning_tile);
+    let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+    max_han_and_fu_in_groupings(
+        tile_grouping::all_winning_groupings(&winning_tiles, &empty_groups),
+        player_tiles,
+        winning_tile,
+        hand_state,
+        player_state,
+    )
+}
+
+fn max_han_and_fu_in_groupings(
+    groupings: Vec<Vec<tiles::TileGroup>>,
+    player_tiles: &Vec<tiles::Tile>,
+    winning_tile: &tiles::Tile,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Option<(Vec<tiles::TileGroup>, (u32, u32))> {
+    let mut max_grouping: Option<(Vec<tiles::TileGroup>, (u32, u32))> = None;
+    for grouping in groupings {
+        let grouping_han_and_fu = han_and_fu(
+            player_tiles,
+            winning_tile,
+            &grouping,
+            hand_state,
+            player_state,
+        );
+        if max_grouping
+            .as_ref()
+            .is_none_or(|(_, max_han_and_fu)| grouping_han_and_fu > *max_han_and_fu)
+        {
+            max_grouping = Some((grouping, grouping_han_and_fu));
+        }
+    }
+    max_grouping
+}
+
+/// The wait tile of the 13-tile tenpai hand that scores the most (by han, then by fu) and its (han, fu),
+/// using the grouping of each winning hand that scores the most. Whether the hand wins by ron or tsumo is set by
+/// the player state's winning tile source. Returns None if the hand isn't in tenpai.
+pub fn max_han_and_fu_over_waits(
+    player_tiles: &Vec<tiles::Tile>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> Option<(tiles::Tile, (u32, u32))> {
+    if shanten::get_shanten(player_tiles) != 0 {
+        return None;
+    }
+    let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+    let mut max_wait: Option<(tiles::Tile, (u32, u32))> = None;
+    for wait_tile in shanten::get_ukiere(player_tiles) {
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(wait_tile);
+        let groupings = tile_grouping::all_winning_groupings(&winning_tiles, &empty_groups);
+        if groupings.is_empty() {
+            // thirteen orphans can't be grouped, and is scored without fu
+            if let Some(kokushi_han) =
+                han_from_kokushi_yaku(player_tiles, &wait_tile, hand_state, player_state)
+            {
+                if max_wait.is_none_or(|(_, max_han_and_fu)| (kokushi_han, 0) > max_han_and_fu) {
+                    max_wait = Some((wait_tile, (kokushi_han, 0)));
+                }
+            }
+            continue;
+        }
+        for grouping in groupings.iter() {
+            let wait_han_and_fu =
+                han_and_fu(player_tiles, &wait_tile, grouping, hand_state, player_state);
+            if max_wait.is_none_or(|(_, max_han_and_fu)| wait_han_and_fu > max_han_and_fu) {
+                max_wait = Some((wait_tile, wait_han_and_fu));
+            }
+        }
+    }
+    max_wait
+}
+
+// TODO do we assume that the winning grouping is already checked as a winning hand?
+// TODO we need to make sure that the fu is scored using the grouping that produces the maximum han
+pub fn scoring_fu(
+    player_tiles: &Vec<tiles::Tile>,
+    added_tile: &tiles::Tile,
+    tile_grouping: &Vec<tiles::TileGroup>,
+    hand_state: &state::HandState,
+    player_state: &state::PlayerState,
+) -> u32 {
+    let mut new_tiles = player_tiles.clone();
+    new_tiles.push(added_tile.clone());
+    // the player's tiles don't include the declared groups (called groups and kans)
+    let declared_tile_groups: Vec<tiles::TileGroup> = tile_grouping
+        .iter()
+        .filter(|tile_group| {
+            tile_group.is_open() || matches!(tile_group, tiles::TileGroup::Quad { .. })
+        })
+        .cloned()
+        .collect();
+
+    // special case: chiitoitsu (seven pairs) is always scored as 25 fu, but only when the hand is scored with its
+    // seven pairs grouping (the same tiles can also be grouped as a standard hand, e.g. as ryanpeikou)
+    if tile_grouping::number_pair_groups(tile_grouping) == 7 {
+        return 25;
+    }
+
+    // assert this is a winning hand, and get hand grouping(s)
+    let _tile_groups = tile_grouping::tile_grouping(&new_tiles, &declared_tile_groups)
+        .expect("Should be a winning hand");
+
+    // TODO we need to make sure that the fu is consistent with the grouping that scores the maximum han
+
+    // fu from tile groups (triplets and quads earn fu based on open/closed and if the tile is simple or not)
+    // (a closed triplet completed by ron is scored as an open triplet)
+    let is_ron_triplet = is_triplet_completed_by_ron(tile_grouping, added_tile, player_state);
+    let fu_from_groups: u32 = tile_grouping
+        .iter()
+        .map(|tile_group| match tile_group {
+            tiles::TileGroup::Triplet { open: false, tiles }
+                if is_ron_triplet && tiles[0].kind_index() == added_tile.kind_index() =>
+            {
+                fu_from_tile_group(&tiles::TileGroup::Triplet {
+                    open: true,
+                    tiles: *tiles,
+                })
+            }
+            _ => fu_from_tile_group(tile_group),
+        })
+        .sum();
+
+    // fu from waits
+    let fu_from_wait = fu_from_wait(tile_grouping, added_tile);
+
+    // fu from pair (earns 2 fu if the tile would be yakuhai, 4 fu if the wind is both seat and dealer wind)
+    let round_wind_rank = hand_state.round_wind.to_rank();
+    let seat_wind_rank = player_state.seat_wind.to_rank();
+    let pair_tile = tiles::get_head_tile(tile_grouping).expect("Should be a pair in winning hand");
+    let fu_from_pair = if is_yakuhai_tile(&pair_tile, round_wind_rank, seat_wind_rank) {
+        if pair_tile.rank() == round_wind_rank && pair_tile.rank() == seat_wind_rank {
+            4
+        } else {
+            2
+        }
+    } else {
+        0
+    };
+
+    // fu from winning condition
+    let is_hand_closed: bool = !tile_grouping.iter().any(|tile_group| tile_group.is_open());
+    let winning_condition = player_state.winning_tile_source;
+
+    let fu_from_winning_condition = match winning_condition.expect("Must be a winning tile source")
+    {
+        state::WinningTileSource::Discard { .. } => {
+            if is_hand_closed {
+                10
+            } else {
+                // TODO if no fu from tile groups or waiting pattern, then the 20 fu hand is forced to 30 fu
+                0
+            }
+        }
+        state::WinningTileSource::SelfDraw { .. } => {
+            // if the closed hand with tsumo satisfies all other criteria for pinfu, these 2 fu are not awarded (the 1 han for pinfu is awarded instead)
+            if has_pinfu(tile_grouping, added_tile, hand_state, player_state) {
+                0
+            } else {
+                2
+            }
+        }
+        state::WinningTileSource::DeadWall => {
+            // TODO some scoring rule variations (rishan fu) don't award 2 fu for tsumo win off of kan replacement tile, as winning off of this tile awards the rinshan yaku (1 han)
+            2
+        }
+        state::WinningTileSource::RobbingKan => {
+            // TODO is there any fu for robbing a kan?
+            0
+        }
+    };
+
+    fu_from_groups + fu_from_pair + fu_from_wait + fu_from_winning_condition
+}
+
+#[cfg(test)]
+mod tests {
+    // importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_yakuhai_closed_white_dragon_triplet() {
+        // winning hands taken from my Mahjong Soul logs
+        // game: 4-player East round, Silver room, 2023-06-03 09:26
+        // round: East 4 (0 repeat), winning hand by West (open hand, ron)
+        // scoring: 4 han, 30 fu = 7700 pts (white dragon, dora x3 (7m, 8p))
+        let tile_groups: Vec<tiles::TileGroup> = vec![
+            tiles::TileGroup::Sequence {
+                open: true,
+                tiles: [
+                    tiles::Tile::from_string("4s"),
+                    tiles::Tile::from_string("5s"),
+                    tiles::Tile::from_string("3s"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: true,
+                tiles: [
+                    tiles::Tile::from_string("7m"),
+                    tiles::Tile::from_string("7m"),
+                    tiles::Tile::from_string("7m"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("3m"),
+                    tiles::Tile::from_string("4m"),
+                    tiles::Tile::from_string("2m"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("5z"), // white dragon
+                    tiles::Tile::from_string("5z"),
+                    tiles::Tile::from_string("5z"),
+                ],
+            },
+            tiles::TileGroup::Pair {
+                tiles: [
+                    tiles::Tile::from_string("8m"),
+                    tiles::Tile::from_string("8m"),
+                ],
+            },
+        ];
+
+        // check yaku
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 12,
+            dora_indicators: vec![
+                tiles::Tile::from_string("6m"),
+                tiles::Tile::from_string("7p"),
+            ],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 1,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![
+                tiles::Tile::from_string("4z"),
+                tiles::Tile::from_string("3z"),
+                tiles::Tile::from_string("8p"),
+                tiles::Tile::from_string("1s"),
+                tiles::Tile::from_string("1z"),
+                tiles::Tile::from_string("1p"),
+                tiles::Tile::from_string("2p"),
+                tiles::Tile::from_string("9s"),
+                tiles::Tile::from_string("9s"),
+                tiles::Tile::from_string("0p"),
+                tiles::Tile::from_string("2p"),
+                tiles::Tile::from_string("6m"),
+                tiles::Tile::from_string("4p"),
+                tiles::Tile::from_string("8m"),
+                tiles::Tile::from_string("1s"),
+            ],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            // from East (opposite player / toimen)
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        assert_eq!(
+            han_from_yakuhai_yaku(&tile_groups, &hand_state, &player_state),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_yakuhai_same_round_and_seat_wind() {
+        // test multiple han from yakuhai
+        let tile_groups: Vec<tiles::TileGroup> = vec![
+            tiles::TileGroup::Triplet {
+                open: true,
+                tiles: [
+                    tiles::Tile::from_string("2z"), // south wind
+                    tiles::Tile::from_string("2z"),
+                    tiles::Tile::from_string("2z"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: true,
+                tiles: [
+                    tiles::Tile::from_string("1z"), // east wind
+                    tiles::Tile::from_string("1z"),
+                    tiles::Tile::from_string("1z"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("1s"),
+                    tiles::Tile::from_string("2s"),
+                    tiles::Tile::from_string("3s"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("7z"), // red dragon
+                    tiles::Tile::from_string("7z"),
+                    tiles::Tile::from_string("7z"),
+                ],
+            },
+            tiles::TileGroup::Pair {
+                tiles: [
+                    tiles::Tile::from_string("8m"),
+                    tiles::Tile::from_string("8m"),
+                ],
+            },
+        ];
+
+        // check yaku
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::South,
+            any_calls_made: true,
+            tiles_remaining: 10,
+            dora_indicators: vec![tiles::Tile::from_string("1m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![
+                tiles::Tile::from_string("8p"),
+                tiles::Tile::from_string("1s"),
+                tiles::Tile::from_string("0p"),
+                tiles::Tile::from_string("2p"),
+                tiles::Tile::from_string("6m"),
+                tiles::Tile::from_string("4p"),
+                tiles::Tile::from_string("8m"),
+                tiles::Tile::from_string("1s"),
+            ],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        // south wind = 2 han (seat wind + round wind)
+        // east wind = 0 han
+        // red dragon = 1 han
+        assert_eq!(
+            han_from_yakuhai_yaku(&tile_groups, &hand_state, &player_state),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_yakuhai_quads() {
+        // test yakuhai from quads
+        let tile_groups: Vec<tiles::TileGroup> = vec![
+            tiles::TileGroup::Sequence {
+                open: true,
+                tiles: [
+                    tiles::Tile::from_string("2p"),
+                    tiles::Tile::from_string("3p"),
+                    tiles::Tile::from_string("1p"),
+                ],
+            },
+            tiles::TileGroup::Quad {
+                open: true,
+                added: false,
+                tiles: [
+                    tiles::Tile::from_string("1z"), // east wind
+                    tiles::Tile::from_string("1z"),
+                    tiles::Tile::from_string("1z"),
+                    tiles::Tile::from_string("1z"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("1s"),
+                    tiles::Tile::from_string("2s"),
+                    tiles::Tile::from_string("3s"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("7s"),
+                    tiles::Tile::from_string("7s"),
+                    tiles::Tile::from_string("7s"),
+                ],
+            },
+            tiles::TileGroup::Pair {
+                tiles: [
+                    tiles::Tile::from_string("8m"),
+                    tiles::Tile::from_string("8m"),
+                ],
+            },
+        ];
+
+        // check yaku
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 10,
+            dora_indicators: vec![tiles::Tile::from_string("1m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![
+                tiles::Tile::from_string("8p"),
+                tiles::Tile::from_string("1s"),
+                tiles::Tile::from_string("0p"),
+                tiles::Tile::from_string("2p"),
+                tiles::Tile::from_string("6m"),
+                tiles::Tile::from_string("4p"),
+                tiles::Tile::from_string("8m"),
+                tiles::Tile::from_string("1s"),
+            ],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        // east wind = 1 han (round wind)
+        assert_eq!(
+            han_from_yakuhai_yaku(&tile_groups, &hand_state, &player_state),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_yakuhai_not_from_guest_winds() {
+        // test no yakuhai from guest winds (neither seat wind nor round wind)
+        let tile_groups: Vec<tiles::TileGroup> = vec![
+            tiles::TileGroup::Sequence {
+                open: true,
+                tiles: [
+                    tiles::Tile::from_string("2p"),
+                    tiles::Tile::from_string("3p"),
+                    tiles::Tile::from_string("1p"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: true,
+                tiles: [
+                    tiles::Tile::from_string("2z"), // south wind
+                    tiles::Tile::from_string("2z"),
+                    tiles::Tile::from_string("2z"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("1s"),
+                    tiles::Tile::from_string("2s"),
+                    tiles::Tile::from_string("3s"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("7s"),
+                    tiles::Tile::from_string("7s"),
+                    tiles::Tile::from_string("7s"),
+                ],
+            },
+            tiles::TileGroup::Pair {
+                tiles: [
+                    tiles::Tile::from_string("8m"),
+                    tiles::Tile::from_string("8m"),
+                ],
+            },
+        ];
+
+        // check yaku
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 10,
+            dora_indicators: vec![tiles::Tile::from_string("1m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![
+                tiles::Tile::from_string("8p"),
+                tiles::Tile::from_string("1s"),
+                tiles::Tile::from_string("0p"),
+                tiles::Tile::from_string("2p"),
+                tiles::Tile::from_string("6m"),
+                tiles::Tile::from_string("4p"),
+                tiles::Tile::from_string("8m"),
+                tiles::Tile::from_string("1s"),
+            ],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        // south wind = 0 han (round wind)
+        assert_eq!(
+            han_from_yakuhai_yaku(&tile_groups, &hand_state, &player_state),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tanyao_closed() {
+        // test tanyao (closed hand)
+        // example hand from https://riichi.wiki/Tanyao
+        let tile_groups: Vec<tiles::TileGroup> = vec![
+            tiles::TileGroup::Triplet {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("2s"),
+                    tiles::Tile::from_string("2s"),
+                    tiles::Tile::from_string("2s"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("6s"),
+                    tiles::Tile::from_string("7s"),
+                    tiles::Tile::from_string("8s"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("4m"),
+                    tiles::Tile::from_string("5m"),
+                    tiles::Tile::from_string("6m"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("3p"),
+                    tiles::Tile::from_string("3p"),
+                    tiles::Tile::from_string("3p"),
+                ],
+            },
+            tiles::TileGroup::Pair {
+                tiles: [
+                    tiles::Tile::from_string("5p"),
+                    tiles::Tile::from_string("5p"),
+                ],
+            },
+        ];
+
+        // check yaku
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("2m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![
+                tiles::Tile::from_string("8p"),
+                tiles::Tile::from_string("1s"),
+            ],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        assert!(has_tanyao(&tile_groups, &hand_state, &player_state));
+    }
+
+    #[test]
+    fn test_tanyao_open() {
+        // test tanyao (open hand)
+        // example hand from https://riichi.wiki/Tanyao
+        let tile_groups: Vec<tiles::TileGroup> = vec![
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("6m"),
+                    tiles::Tile::from_string("7m"),
+                    tiles::Tile::from_string("8m"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("3s"),
+                    tiles::Tile::from_string("4s"),
+                    tiles::Tile::from_string("5s"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("3p"),
+                    tiles::Tile::from_string("3p"),
+                    tiles::Tile::from_string("3p"),
+                ],
+            },
+            tiles::TileGroup::Triplet {
+                open: true,
+                tiles: [
+                    tiles::Tile::from_string("2m"),
+                    tiles::Tile::from_string("2m"),
+                    tiles::Tile::from_string("2m"),
+                ],
+            },
+            tiles::TileGroup::Pair {
+                tiles: [
+                    tiles::Tile::from_string("6p"),
+                    tiles::Tile::from_string("6p"),
+                ],
+            },
+        ];
+
+        // check yaku
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("2m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![
+                tiles::Tile::from_string("8p"),
+                tiles::Tile::from_string("1s"),
+            ],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        assert!(has_tanyao(&tile_groups, &hand_state, &player_state));
+    }
+
+    #[test]
+    fn test_fu_from_tile_group() {
+        let kan_fu = |kan_type: state::KanType, tiles_string: &str| {
+            let called_from = if kan_type == state::KanType::Ankan {
+                None
+            } else {
+                Some(state::WindDirection::East)
+            };
+            let called_meld = state::CalledMeld::new(
+                state::MeldType::Kan(kan_type),
+                tiles::tiles_from_string(tiles_string),
+                called_from,
+            )
+            .expect("Valid kan");
+            fu_from_tile_group(&called_meld.tile_group())
+        };
+        assert_eq!(kan_fu(state::KanType::Ankan, "1111m"), 32);
+        assert_eq!(kan_fu(state::KanType::Minkan, "5555p"), 8);
+        assert_eq!(kan_fu(state::KanType::Ankan, "5555p"), 16);
+        assert_eq!(kan_fu(state::KanType::Minkan, "9999s"), 16);
+        // an added kan is an open kan
+        assert_eq!(kan_fu(state::KanType::Shouminkan, "7777z"), 16);
+        assert_eq!(kan_fu(state::KanType::Shouminkan, "0555m"), 8);
+
+        let (_, melds) =
+            tiles::hand_and_melds_from_string("1m + pon222m + pon1z + chi345s").unwrap();
+        assert_eq!(fu_from_tile_group(&melds[0]), 2);
+        assert_eq!(fu_from_tile_group(&melds[1]), 4);
+        assert_eq!(fu_from_tile_group(&melds[2]), 0);
+        let closed_triplet = tiles::TileGroup::Triplet {
+            open: false,
+            tiles: [tiles::Tile::from_string("9p"); 3],
+        };
+        assert_eq!(fu_from_tile_group(&closed_triplet), 8);
+    }
+
+    #[test]
+    fn test_pinfu() {
+        // test pinfu
+        // https://riichi.wiki/Pinfu
+        let tile_groups: Vec<tiles::TileGroup> = vec![
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("1m"),
+                    tiles::Tile::from_string("2m"),
+                    tiles::Tile::from_string("3m"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
                     tiles::Tile::from_string("2s"),
                     tiles::Tile::from_string("3s"),
+                    tiles::Tile::from_string("4s"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("7s"),
+                    tiles::Tile::from_string("8s"),
+                    tiles::Tile::from_string("9s"), // winning tile
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("5p"),
+                    tiles::Tile::from_string("6p"),
+                    tiles::Tile::from_string("7p"),
+                ],
+            },
+            tiles::TileGroup::Pair {
+                tiles: [
+                    tiles::Tile::from_string("9p"),
+                    tiles::Tile::from_string("9p"),
+                ],
+            },
+        ];
+
+        // check yaku
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("2m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![
+                tiles::Tile::from_string("8p"),
+                tiles::Tile::from_string("1s"),
+            ],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        assert!(has_pinfu(
+            &tile_groups,
+            &tiles::Tile::from_string("9s"),
+            &hand_state,
+            &player_state
+        ));
+        // winning on 8s (a closed wait on 79s), 3s (a closed wait on 24s), or 9p (a pair wait) isn't pinfu
+        for winning_tile_string in ["8s", "3s", "9p"] {
+            assert!(!has_pinfu(
+                &tile_groups,
+                &tiles::Tile::from_string(winning_tile_string),
+                &hand_state,
+                &player_state
+            ));
+        }
+
+        // 123m 456p 789s 13s 55m by ron on 2s (a closed wait), and 234m 23457p 234s 88m by tsumo on 6p (a closed wait
+        // on 57p, as 23457p can only be grouped as 234p + 57p)
+        let mut tsumo_player_state = player_state.clone();
+        tsumo_player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+        for (hand_string, winning_tile_string, player_state) in [
+            ("123m456p789s13s55m", "2s", &player_state),
+            ("234m23457p234s88m", "6p", &tsumo_player_state),
+        ] {
+            let player_tiles = tiles::tiles_from_string(hand_string);
+            let winning_tile = tiles::Tile::from_string(winning_tile_string);
+            let groupings =
+                tile_grouping::decompose_winning_hand(&player_tiles, &vec![], &winning_tile);
+            assert!(!groupings.is_empty());
+            for grouping in groupings.iter() {
+                assert!(
+                    !get_yaku_list(grouping, &winning_tile, &hand_state, player_state)
+                        .contains(&Yaku::Pinfu)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ittsu_closed() {
+        // test ittsu (closed hand)
+        // https://riichi.wiki/Ikkitsuukan
+        let tile_groups: Vec<tiles::TileGroup> = vec![
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("1m"),
+                    tiles::Tile::from_string("2m"),
+                    tiles::Tile::from_string("3m"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("4m"),
+                    tiles::Tile::from_string("5m"),
+                    tiles::Tile::from_string("6m"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("7m"),
+                    tiles::Tile::from_string("8m"),
+                    tiles::Tile::from_string("9m"), // winning tile
                 ],
             },
             tiles::TileGroup::Triplet {
                 open: false,
+                tiles: [
+                    tiles::Tile::from_string("3p"),
+                    tiles::Tile::from_string("3p"),
+                    tiles::Tile::from_string("3p"),
+                ],
+            },
+            tiles::TileGroup::Pair {
+                tiles: [
+                    tiles::Tile::from_string("5s"),
+                    tiles::Tile::from_string("5s"),
+                ],
+            },
+        ];
+
+        // check yaku
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("2m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![
+                tiles::Tile::from_string("8p"),
+                tiles::Tile::from_string("1s"),
+            ],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        assert_eq!(
+            han_from_ittsu_yaku(&tile_groups, &hand_state, &player_state),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_ittsu_open_called_sequence() {
+        // test ittsu (open hand), where the 456m sequence was called (chi)
+        let tile_groups: Vec<tiles::TileGroup> = vec![
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("1s"),
+                    tiles::Tile::from_string("2s"),
+                    tiles::Tile::from_string("3s"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: true,
+                tiles: [
+                    tiles::Tile::from_string("4s"),
+                    tiles::Tile::from_string("5s"),
+                    tiles::Tile::from_string("6s"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("7s"),
+                    tiles::Tile::from_string("8s"),
+                    tiles::Tile::from_string("9s"),
+                ],
+            },
+            tiles::TileGroup::Sequence {
+                open: false,
+                tiles: [
+                    tiles::Tile::from_string("2p"),
+                    tiles::Tile::from_string("3p"),
+                    tiles::Tile::from_string("4p"),
+                ],
+            },
+            tiles::TileGroup::Pair {
                 tiles: [
                     tiles::Tile::from_string("7z"), // red dragon
                     tiles::Tile::from_string("7z"),
-                    tiles::Tile::from_string("7z"),
                 ],
             },
+        ];
+
+        // check yaku
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 30,
+            dora_indicators: vec![tiles::Tile::from_string("2m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![
+                tiles::Tile::from_string("8p"),
+                tiles::Tile::from_string("1m"),
+                tiles::Tile::from_string("9m"),
+            ],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::SelfDraw {
+                is_first_go_around: false,
+            }),
+        };
+        // ittsu is worth 1 han less when the hand is open
+        assert_eq!(
+            han_from_ittsu_yaku(&tile_groups, &hand_state, &player_state),
+            Some(1)
+        );
+
+        // the 123-456-789 run must be in a single suit
+        let mut mixed_suit_tile_groups = tile_groups.clone();
+        mixed_suit_tile_groups[2] = tiles::TileGroup::Sequence {
+            open: false,
+            tiles: [
+                tiles::Tile::from_string("7m"),
+                tiles::Tile::from_string("8m"),
+                tiles::Tile::from_string("9m"),
+            ],
+        };
+        assert_eq!(
+            han_from_ittsu_yaku(&mixed_suit_tile_groups, &hand_state, &player_state),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ittsu_open_from_hand_with_called_sequence() {
+        // concealed 123s 789s 234p 7z + called chi 456s, winning on 7z: the called sequence is part of the
+        // winning grouping, so the hand still scores open ittsu
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 30,
+            dora_indicators: vec![tiles::Tile::from_string("2m")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("8p")],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        let (player_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234p123789s7z + chi456s").unwrap();
+        let winning_tile = tiles::Tile::from_string("7z");
+        let decompositions =
+            tile_grouping::decompose_winning_hand(&player_tiles, &declared_groups, &winning_tile);
+        assert_eq!(decompositions.len(), 1);
+        let grouping = &decompositions[0];
+        assert_eq!(grouping.len(), 5);
+        assert_eq!(
+            get_yaku_list(grouping, &winning_tile, &hand_state, &player_state),
+            vec![Yaku::Ittsu]
+        );
+        assert_eq!(
+            han_and_fu(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            )
+            .0,
+            1
+        );
+    }
+
+    #[test]
+    fn test_sanshoku_doujun_closed() {
+        // 234m 234p 234s 567m 88p: sequences starting at 2 in all three numbered suits
+        let tiles = tiles::tiles_from_string("234567m23488p234s");
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+        let groupings =
+            tile_grouping::tile_grouping(&tiles, &empty_groups).expect("Should be a winning hand");
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 50,
+            dora_indicators: vec![tiles::Tile::from_string("9s")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("1z")],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        for grouping in groupings.iter() {
+            assert_eq!(
+                han_from_sanshoku_doujun_yaku(grouping, &hand_state, &player_state),
+                Some(2)
+            );
+        }
+
+        // 123m 234p 234s 567m 88p: the man sequence starts at a different rank
+        let tiles = tiles::tiles_from_string("123567m23488p234s");
+        let groupings =
+            tile_grouping::tile_grouping(&tiles, &empty_groups).expect("Should be a winning hand");
+        for grouping in groupings.iter() {
+            assert_eq!(
+                han_from_sanshoku_doujun_yaku(grouping, &hand_state, &player_state),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_sanshoku_doujun_with_called_sequence() {
+        // concealed 234m 567m 234p 8p + called chi 234s, winning on 8p: the called sequence is part of the
+        // winning grouping, so the hand scores open sanshoku doujun
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 50,
+            dora_indicators: vec![tiles::Tile::from_string("9s")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("1z")],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        let (player_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234567m2348p + chi234s").unwrap();
+        let winning_tile = tiles::Tile::from_string("8p");
+        let decompositions =
+            tile_grouping::decompose_winning_hand(&player_tiles, &declared_groups, &winning_tile);
+        assert_eq!(decompositions.len(), 1);
+        let grouping = &decompositions[0];
+        assert_eq!(
+            han_from_sanshoku_doujun_yaku(grouping, &hand_state, &player_state),
+            Some(1)
+        );
+        assert!(
+            get_yaku_list(grouping, &winning_tile, &hand_state, &player_state)
+                .contains(&Yaku::SanshokuDoujun)
+        );
+
+        // the same hand with 234s concealed is closed sanshoku doujun
+        let mut closed_hand_state = hand_state.clone();
+        closed_hand_state.any_calls_made = false;
+        let player_tiles = tiles::tiles_from_string("234567m2348p234s");
+        let decompositions =
+            tile_grouping::decompose_winning_hand(&player_tiles, &vec![], &winning_tile);
+        assert_eq!(decompositions.len(), 1);
+        assert_eq!(
+            han_from_sanshoku_doujun_yaku(&decompositions[0], &closed_hand_state, &player_state),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_max_num_yaku_across_waits() {
+        // 234m 567m 234p 88p 23s: waiting on 1s (pinfu, as 123s breaks the sanshoku) or 4s (tanyao + pinfu + sanshoku)
+        let tiles = tiles::tiles_from_string("234567m23488p23s");
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 50,
+            dora_indicators: vec![tiles::Tile::from_string("9s")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("1z")],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        assert_eq!(
+            max_num_yaku_across_waits(&tiles, &hand_state, &player_state),
+            3
+        );
+
+        // winning on 4s is the wait with the most yaku
+        let mut winning_tiles = tiles.clone();
+        winning_tiles.push(tiles::Tile::from_string("4s"));
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        let yaku_list = get_yaku_list(
+            &groupings[0],
+            &tiles::Tile::from_string("4s"),
+            &hand_state,
+            &player_state,
+        );
+        assert!(yaku_list.contains(&Yaku::Tanyao));
+        assert!(yaku_list.contains(&Yaku::SanshokuDoujun));
+
+        // not in tenpai
+        let tiles = tiles::tiles_from_string("234567m23488p29s");
+        assert_eq!(
+            max_num_yaku_across_waits(&tiles, &hand_state, &player_state),
+            0
+        );
+    }
+
+    #[test]
+    fn test_nobetan_wait_is_pair_wait() {
+        // 123m 456p 789p 3456s: a nobetan wait on 3s or 6s (two pair waits, not an open wait)
+        let tiles = tiles::tiles_from_string("123m456789p3456s");
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 50,
+            dora_indicators: vec![tiles::Tile::from_string("9s")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("1z")],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+
+        for winning_tile_string in ["3s", "6s"] {
+            let winning_tile = tiles::Tile::from_string(winning_tile_string);
+            let mut winning_tiles = tiles.clone();
+            winning_tiles.push(winning_tile);
+            let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+                .expect("Should be a winning hand");
+            for grouping in groupings.iter() {
+                // the hand shape is all sequences and a non-yakuhai pair, but the wait isn't an open wait, so no pinfu
+                assert!(!has_pinfu(
+                    grouping,
+                    &winning_tile,
+                    &hand_state,
+                    &player_state
+                ));
+                assert!(!is_ryanmen_wait(grouping, &winning_tile));
+                assert_eq!(fu_from_wait(grouping, &winning_tile), 2);
+                // closed hand ron (10 fu) + pair wait (2 fu)
+                assert_eq!(
+                    scoring_fu(&tiles, &winning_tile, grouping, &hand_state, &player_state),
+                    12
+                );
+            }
+        }
+
+        // 123m 456p 789p 33s 45s: an open wait on 3s or 6s earns no fu from the wait
+        let tiles = tiles::tiles_from_string("123m456789p3345s");
+        for winning_tile_string in ["3s", "6s"] {
+            let winning_tile = tiles::Tile::from_string(winning_tile_string);
+            let mut winning_tiles = tiles.clone();
+            winning_tiles.push(winning_tile);
+            let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+                .expect("Should be a winning hand");
+            assert!(groupings
+                .iter()
+                .any(|grouping| is_ryanmen_wait(grouping, &winning_tile)));
+        }
+    }
+
+    #[test]
+    fn test_expected_num_ura_dora() {
+        // triplets: none of the indicators for the hand's tiles (9m, 3m, 6m, 9p, 3p) are in the hand
+        let triplet_tiles = tiles::tiles_from_string("111444777m11144p");
+        // connected sequences: most of the indicators for the hand's tiles are in the hand (fewer live copies)
+        let sequence_tiles = tiles::tiles_from_string("123456789m12345p");
+        let no_visible_tiles: Vec<tiles::Tile> = Vec::new();
+
+        let triplet_ura = expected_num_ura_dora(&triplet_tiles, &no_visible_tiles, 1);
+        let sequence_ura = expected_num_ura_dora(&sequence_tiles, &no_visible_tiles, 1);
+        // each of the 14 tiles has 4 live indicators out of the 122 unseen tiles
+        assert!((triplet_ura - 14.0 * 4.0 / 122.0).abs() < 1e-9);
+        assert!(triplet_ura > sequence_ura);
+
+        // an extra ura dora indicator from a kan doubles the expectation
+        let triplet_ura_with_kan = expected_num_ura_dora(&triplet_tiles, &no_visible_tiles, 2);
+        assert!((triplet_ura_with_kan - 2.0 * triplet_ura).abs() < 1e-9);
+    }
+
+    fn quad_group(tile_string: &str, open: bool) -> tiles::TileGroup {
+        let tile = tiles::Tile::from_string(tile_string);
+        tiles::TileGroup::Quad {
+            open,
+            added: false,
+            tiles: [0, 1, 2, 3]
+                .map(|copy| tiles::Tile::from_suit_and_rank(tile.suit(), tile.rank(), copy)),
+        }
+    }
+
+    #[test]
+    fn test_sanshoku_doukou_with_called_triplet() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 30,
+            dora_indicators: vec![tiles::Tile::from_string("9s")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("1s")],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        let winning_grouping = |hand_string: &str, winning_tile_string: &str| {
+            let (player_tiles, declared_groups) =
+                tiles::hand_and_melds_from_string(hand_string).unwrap();
+            let mut winning_tiles = player_tiles.clone();
+            winning_tiles.push(tiles::Tile::from_string(winning_tile_string));
+            let groupings = tile_grouping::tile_grouping(&winning_tiles, &declared_groups)
+                .expect("Should be a winning hand");
+            assert_eq!(groupings.len(), 1);
+            groupings[0].clone()
+        };
+
+        // concealed 555m 555s 234p 11z + called pon 555p, winning on 1z
+        let grouping = winning_grouping("555m234p555s1z + pon5p", "1z");
+        assert!(has_sanshoku_doukou(&grouping, &hand_state, &player_state));
+        assert!(get_yaku_list(
+            &grouping,
+            &tiles::Tile::from_string("1z"),
+            &hand_state,
+            &player_state
+        )
+        .contains(&Yaku::SanshokuDoukou));
+        assert_eq!(
+            han_from_yaku(
+                &grouping,
+                &tiles::Tile::from_string("1z"),
+                &hand_state,
+                &player_state
+            ),
+            2
+        );
+
+        // a called quad also counts, and the same hand closed is 2 han + tanyao + sanankou (its three closed triplets)
+        let grouping = winning_grouping("555m234p555s8s + kan5p", "8s");
+        assert!(has_sanshoku_doukou(&grouping, &hand_state, &player_state));
+        let grouping = winning_grouping("555m555p234p555s8s", "8s");
+        assert!(has_sanshoku_doukou(&grouping, &hand_state, &player_state));
+        assert_eq!(
+            han_from_yaku(
+                &grouping,
+                &tiles::Tile::from_string("8s"),
+                &hand_state,
+                &player_state
+            ),
+            5
+        );
+
+        // the triplets must be of the same rank in all three suits
+        let grouping = winning_grouping("555m234p666s1z + pon5p", "1z");
+        assert!(!has_sanshoku_doukou(&grouping, &hand_state, &player_state));
+    }
+
+    #[test]
+    fn test_sankantsu_open_and_closed_quads() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 30,
+            dora_indicators: vec![tiles::Tile::from_string("9s")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("1s")],
+            seat_wind: state::WindDirection::South,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        // concealed tiles 34s 99s, winning on 5s (open wait)
+        let concealed_tiles = tiles::tiles_from_string("3499s");
+        let winning_tile = tiles::Tile::from_string("5s");
+        let mut winning_tiles = concealed_tiles.clone();
+        winning_tiles.push(winning_tile);
+
+        for quads_open in [true, false] {
+            let declared_quads = vec![
+                quad_group("2m", quads_open),
+                quad_group("7p", quads_open),
+                quad_group("1z", quads_open),
+            ];
+            let groupings = tile_grouping::tile_grouping(&winning_tiles, &declared_quads)
+                .expect("Should be a winning hand");
+            assert_eq!(groupings.len(), 1);
+            let grouping = &groupings[0];
+            // the declared quads are only counted once
+            assert_eq!(
+                grouping
+                    .iter()
+                    .filter(|tile_group| matches!(tile_group, tiles::TileGroup::Quad { .. }))
+                    .count(),
+                3
+            );
+            assert!(has_sankantsu(grouping, &hand_state, &player_state));
+            // sankantsu (2 han) + yakuhai from the east wind quad (1 han), plus sanankou (2 han) if the quads are closed
+            assert_eq!(
+                han_from_yaku(grouping, &winning_tile, &hand_state, &player_state),
+                if quads_open { 3 } else { 5 }
+            );
+
+            let fu = scoring_fu(
+                &concealed_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state,
+            );
+            if quads_open {
+                // open simple quads (8 fu each) + open honor quad (16 fu), no fu for an open hand ron
+                assert_eq!(fu, 8 + 8 + 16);
+            } else {
+                // closed simple quads (16 fu each) + closed honor quad (32 fu) + closed hand ron (10 fu)
+                assert_eq!(fu, 16 + 16 + 32 + 10);
+            }
+        }
+
+        // four quads is not sankantsu
+        let four_quads = vec![
+            quad_group("2m", true),
+            quad_group("7p", true),
+            quad_group("1z", true),
+            quad_group("3s", true),
             tiles::TileGroup::Pair {
                 tiles: [
-                    tiles::Tile::from_string("8m"),
-                    tiles::Tile::from_string("8m"),
+                    tiles::Tile::from_string("9s"),
+                    tiles::Tile::from_string("9s"),
                 ],
             },
         ];
+        assert!(!has_sankantsu(&four_quads, &hand_state, &player_state));
+    }
+
+    #[test]
+    fn test_han_and_fu_ron_and_tsumo() {
+        // 22m 345m 678m 456p 234s, in riichi, winning on 2s (open wait)
+        let player_tiles = tiles::tiles_from_string("22345678m456p34s");
+        let winning_tile = tiles::Tile::from_string("2s");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        let grouping = &groupings[0];
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 1,
+            honba_sticks: 0,
+        };
+        let ron_player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("9p")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: true,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        let mut tsumo_player_state = ron_player_state.clone();
+        tsumo_player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+
+        let (ron_han_and_fu, tsumo_han_and_fu) = han_and_fu_ron_and_tsumo(
+            &player_tiles,
+            &empty_groups,
+            &winning_tile,
+            &hand_state,
+            &ron_player_state,
+        )
+        .expect("Should be a winning hand");
+        assert_eq!(
+            ron_han_and_fu,
+            (
+                han_from_yaku(grouping, &winning_tile, &hand_state, &ron_player_state),
+                scoring_fu(
+                    &player_tiles,
+                    &winning_tile,
+                    grouping,
+                    &hand_state,
+                    &ron_player_state
+                )
+            )
+        );
+        assert_eq!(
+            tsumo_han_and_fu,
+            (
+                han_from_yaku(grouping, &winning_tile, &hand_state, &tsumo_player_state),
+                scoring_fu(
+                    &player_tiles,
+                    &winning_tile,
+                    grouping,
+                    &hand_state,
+                    &tsumo_player_state
+                )
+            )
+        );
+        // the same as scoring each winning tile source separately over all of the groupings
+        for (han_and_fu, player_state) in [
+            (ron_han_and_fu, &ron_player_state),
+            (tsumo_han_and_fu, &tsumo_player_state),
+        ] {
+            assert_eq!(
+                max_han_and_fu_over_groupings(
+                    &player_tiles,
+                    &winning_tile,
+                    &hand_state,
+                    player_state
+                )
+                .map(|(_, max_han_and_fu)| max_han_and_fu),
+                Some(han_and_fu)
+            );
+        }
+        // riichi + tanyao + pinfu, plus menzen tsumo when winning by self-draw
+        assert_eq!(ron_han_and_fu.0, 3);
+        assert_eq!(tsumo_han_and_fu.0, 4);
+    }
+
+    #[test]
+    fn test_check_yakuman_daisangen() {
+        // 555z 666z 777z 234m 88p, winning on 8p
+        let player_tiles = tiles::tiles_from_string("234m8p555666777z");
+        let winning_tile = tiles::Tile::from_string("8p");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        let grouping = &groupings[0];
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("9p")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+
+        assert_eq!(
+            check_yakuman(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            ),
+            Some(vec![Yaku::Daisangen])
+        );
+        // the yakuman path skips computing the fu
+        assert_eq!(
+            han_and_fu(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            ),
+            (13, 0)
+        );
+
+        // two dragon triplets is not daisangen
+        let player_tiles = tiles::tiles_from_string("234m8p999s555666z");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        assert_eq!(
+            check_yakuman(
+                &player_tiles,
+                &winning_tile,
+                &groupings[0],
+                &hand_state,
+                &player_state
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_chiitoitsu_and_ryanpeikou_interpretations() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1z")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("1s")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+
+        // 22334m 556677p 88s, winning on 4m: seven pairs (chiitoitsu + tanyao, 25 fu),
+        // or 234m 234m 567p 567p 88s (ryanpeikou + pinfu + tanyao, which scores more)
+        let player_tiles = tiles::tiles_from_string("22334m556677p88s");
+        let winning_tile = tiles::Tile::from_string("4m");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let groupings = tile_grouping::all_winning_groupings(&winning_tiles, &vec![]);
+        let seven_pairs_grouping = groupings
+            .iter()
+            .find(|grouping| tile_grouping::number_pair_groups(grouping) == 7)
+            .expect("Hand should have a seven pairs grouping");
+        assert_eq!(
+            get_yaku_list(
+                seven_pairs_grouping,
+                &winning_tile,
+                &hand_state,
+                &player_state
+            ),
+            vec![Yaku::Tanyao, Yaku::Chiitoitsu]
+        );
+        assert_eq!(
+            han_and_fu(
+                &player_tiles,
+                &winning_tile,
+                seven_pairs_grouping,
+                &hand_state,
+                &player_state
+            ),
+            (3, 25)
+        );
+        let standard_grouping = groupings
+            .iter()
+            .find(|grouping| tile_grouping::number_pair_groups(grouping) == 1)
+            .expect("Hand should have a standard grouping");
+        assert_eq!(
+            get_yaku_list(standard_grouping, &winning_tile, &hand_state, &player_state),
+            vec![Yaku::Tanyao, Yaku::Pinfu, Yaku::Ryanpeikou]
+        );
+
+        let (max_grouping, max_han_and_fu) =
+            max_han_and_fu_over_groupings(&player_tiles, &winning_tile, &hand_state, &player_state)
+                .expect("Hand should be a winning hand");
+        assert_eq!(tile_grouping::number_pair_groups(&max_grouping), 1);
+        assert_eq!(max_han_and_fu.0, 5);
+
+        // every standard grouping of seven pairs uses two pairs of identical sequences, so chiitoitsu is only chosen
+        // when the hand has no standard grouping, e.g. 1122m 3344p 5566s 77z
+        let player_tiles = tiles::tiles_from_string("1122m3344p5566s7z");
+        let winning_tile = tiles::Tile::from_string("7z");
+        let (max_grouping, max_han_and_fu) =
+            max_han_and_fu_over_groupings(&player_tiles, &winning_tile, &hand_state, &player_state)
+                .expect("Hand should be a winning hand");
+        assert_eq!(tile_grouping::number_pair_groups(&max_grouping), 7);
+        assert_eq!(max_han_and_fu, (2, 25));
+
+        // 123m 123m 456p 678s 99s: one pair of identical sequences is iipeikou
+        let player_tiles = tiles::tiles_from_string("112233m456p78s99s");
+        let winning_tile = tiles::Tile::from_string("6s");
+        let (max_grouping, _) =
+            max_han_and_fu_over_groupings(&player_tiles, &winning_tile, &hand_state, &player_state)
+                .expect("Hand should be a winning hand");
+        assert_eq!(
+            get_yaku_list(&max_grouping, &winning_tile, &hand_state, &player_state),
+            vec![Yaku::Pinfu, Yaku::Iipeikou]
+        );
+    }
+
+    #[test]
+    fn test_iipeikou_closed_only_and_replaced_by_ryanpeikou() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1z")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("1s")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        let winning_grouping = |hand_string: &str, winning_tile_string: &str| {
+            let (player_tiles, declared_groups) =
+                tiles::hand_and_melds_from_string(hand_string).unwrap();
+            let winning_tile = tiles::Tile::from_string(winning_tile_string);
+            let decompositions = tile_grouping::decompose_winning_hand(
+                &player_tiles,
+                &declared_groups,
+                &winning_tile,
+            );
+            // the standard grouping (a hand with ryanpeikou can also be grouped as seven pairs)
+            decompositions
+                .into_iter()
+                .find(|grouping| tile_grouping::number_pair_groups(grouping) == 1)
+                .expect("Hand should have a standard grouping")
+        };
+
+        // closed 123m 123m 456p 777s 99s (tanki wait on 9s): iipeikou
+        let grouping = winning_grouping("112233m456p777s9s", "9s");
+        assert!(has_iipeikou(&grouping, &hand_state, &player_state));
+        assert!(!has_ryanpeikou(&grouping, &hand_state, &player_state));
+        assert_eq!(
+            get_yaku_list(
+                &grouping,
+                &tiles::Tile::from_string("9s"),
+                &hand_state,
+                &player_state
+            ),
+            vec![Yaku::Iipeikou]
+        );
+        assert_eq!(
+            han_from_yaku(
+                &grouping,
+                &tiles::Tile::from_string("9s"),
+                &hand_state,
+                &player_state
+            ),
+            1
+        );
+
+        // closed 123m 123m 456p 456p 33z (the seat wind): ryanpeikou, which replaces iipeikou
+        let grouping = winning_grouping("112233m445566p3z", "3z");
+        assert!(!has_iipeikou(&grouping, &hand_state, &player_state));
+        assert!(has_ryanpeikou(&grouping, &hand_state, &player_state));
+        assert_eq!(
+            get_yaku_list(
+                &grouping,
+                &tiles::Tile::from_string("3z"),
+                &hand_state,
+                &player_state
+            ),
+            vec![Yaku::Ryanpeikou]
+        );
+        assert_eq!(
+            han_from_yaku(
+                &grouping,
+                &tiles::Tile::from_string("3z"),
+                &hand_state,
+                &player_state
+            ),
+            3
+        );
+
+        let mut open_hand_state = hand_state.clone();
+        open_hand_state.any_calls_made = true;
+        // concealed 123m 123m with a called pon of 5z: the hand is open, so no iipeikou (only yakuhai)
+        let grouping = winning_grouping("112233m789s9s + pon5z", "9s");
+        assert!(!has_iipeikou(&grouping, &open_hand_state, &player_state));
+        assert_eq!(
+            get_yaku_list(
+                &grouping,
+                &tiles::Tile::from_string("9s"),
+                &open_hand_state,
+                &player_state
+            ),
+            vec![Yaku::Yakuhai]
+        );
+        assert_eq!(
+            han_from_yaku(
+                &grouping,
+                &tiles::Tile::from_string("9s"),
+                &open_hand_state,
+                &player_state
+            ),
+            1
+        );
+
+        // concealed 123m with a called chi of 123m "looks" like iipeikou, but the hand is open
+        let grouping = winning_grouping("123m456p789s9s + chi123m", "9s");
+        assert!(!has_iipeikou(&grouping, &open_hand_state, &player_state));
+        assert!(!has_ryanpeikou(&grouping, &open_hand_state, &player_state));
+        assert_eq!(
+            han_from_yaku(
+                &grouping,
+                &tiles::Tile::from_string("9s"),
+                &open_hand_state,
+                &player_state
+            ),
+            0
+        );
+    }
+
+    #[test]
+    fn test_max_han_and_fu_over_waits() {
+        // 12345678m 234p 99p: waiting on 3m, 6m, or 9m. Winning on 3m is pinfu, since it can be read as the open wait
+        // on 45m (123m 45m 678m) rather than the edge wait on 12m. 6m is also pinfu, and 9m is pinfu + ittsu
+        let player_tiles = tiles::tiles_from_string("12345678m23499p");
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1z")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("1s")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        let (max_wait_tile, (max_han, _)) =
+            max_han_and_fu_over_waits(&player_tiles, &hand_state, &player_state)
+                .expect("Hand should be in tenpai");
+        assert_eq!(max_wait_tile.to_string(), "9m");
+        assert_eq!(max_han, 3);
+        for (wait_tile_string, expected_han) in [("3m", 1), ("6m", 1), ("9m", 3)] {
+            let (_, (han, _)) = max_han_and_fu_over_groupings(
+                &player_tiles,
+                &tiles::Tile::from_string(wait_tile_string),
+                &hand_state,
+                &player_state,
+            )
+            .expect("Hand should be a winning hand");
+            assert_eq!(han, expected_han);
+        }
+        // 12m 456p 789p 234s 99s: winning on 3m can only be read as the edge wait on 12m, which isn't pinfu
+        let (_, (han, _)) = max_han_and_fu_over_groupings(
+            &tiles::tiles_from_string("12m456789p234s99s"),
+            &tiles::Tile::from_string("3m"),
+            &hand_state,
+            &player_state,
+        )
+        .expect("Hand should be a winning hand");
+        assert_eq!(han, 0);
 
-        // check yaku
+        // winning by tsumo adds menzen tsumo
+        let mut tsumo_player_state = player_state.clone();
+        tsumo_player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+        let (max_wait_tile, (max_han, _)) =
+            max_han_and_fu_over_waits(&player_tiles, &hand_state, &tsumo_player_state)
+                .expect("Hand should be in tenpai");
+        assert_eq!(max_wait_tile.to_string(), "9m");
+        assert_eq!(max_han, 4);
+
+        // not in tenpai
+        let player_tiles = tiles::tiles_from_string("12345678m23459p");
+        assert!(max_han_and_fu_over_waits(&player_tiles, &hand_state, &player_state).is_none());
+    }
+
+    #[test]
+    fn test_daisangen_winning_tiles() {
         let hand_state = state::HandState {
-            round_wind: state::WindDirection::South,
-            any_calls_made: true,
-            tiles_remaining: 10,
-            dora_indicators: vec![tiles::Tile::from_string("1m")],
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
             riichi_sticks: 0,
             honba_sticks: 0,
         };
         let player_state = state::PlayerState {
-            discards: vec![
-                tiles::Tile::from_string("8p"),
-                tiles::Tile::from_string("1s"),
-                tiles::Tile::from_string("0p"),
-                tiles::Tile::from_string("2p"),
-                tiles::Tile::from_string("6m"),
-                tiles::Tile::from_string("4p"),
-                tiles::Tile::from_string("8m"),
-                tiles::Tile::from_string("1s"),
-            ],
-            seat_wind: state::WindDirection::South,
+            discards: vec![tiles::Tile::from_string("9p")],
+            seat_wind: state::WindDirection::West,
             in_riichi: false,
             in_double_riichi: false,
+            in_open_riichi: false,
             in_ippatsu_turn: false,
             any_discards_called_by_others: false,
-            winning_tile_source: Some(state::WinningTileSource::Discard),
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
         };
-        // south wind = 2 han (seat wind + round wind)
-        // east wind = 0 han
-        // red dragon = 1 han
+        let yakuman_lists = |hand_string: &str, winning_tile_string: &str| -> Vec<Vec<Yaku>> {
+            let (player_tiles, declared_groups) =
+                tiles::hand_and_melds_from_string(hand_string).unwrap();
+            let winning_tile = tiles::Tile::from_string(winning_tile_string);
+            let mut winning_tiles = player_tiles.clone();
+            winning_tiles.push(winning_tile);
+            tile_grouping::tile_grouping(&winning_tiles, &declared_groups)
+                .expect("Should be a winning hand")
+                .iter()
+                .map(|grouping| {
+                    get_yakuman_list(
+                        &player_tiles,
+                        &winning_tile,
+                        grouping,
+                        &hand_state,
+                        &player_state,
+                    )
+                })
+                .collect()
+        };
+
+        // 555z 666z 777z 234m 88p, winning on the sequence (either side of a two-sided wait) or the pair
+        for winning_tile_string in ["1m", "4m"] {
+            assert_eq!(
+                yakuman_lists("23m88p555666777z", winning_tile_string),
+                vec![vec![Yaku::Daisangen]]
+            );
+        }
         assert_eq!(
-            han_from_yakuhai_yaku(&tile_groups, &hand_state, &player_state),
-            Some(3)
+            yakuman_lists("234m8p555666777z", "8p"),
+            vec![vec![Yaku::Daisangen]]
+        );
+
+        // shanpon wait: winning on the dragon is daisangen, but winning on 8p leaves the dragon as the pair
+        assert_eq!(
+            yakuman_lists("234m88p55566677z", "7z"),
+            vec![vec![Yaku::Daisangen]]
+        );
+        assert_eq!(
+            yakuman_lists("234m88p55566677z", "8p"),
+            vec![Vec::<Yaku>::new()]
+        );
+
+        // open hand with called dragon triplets and quads
+        assert_eq!(
+            yakuman_lists("234m88p77z + pon555z + pon666z", "7z"),
+            vec![vec![Yaku::Daisangen]]
+        );
+        assert_eq!(
+            yakuman_lists("234m8p + kan5z + ankan6z + pon7z", "8p"),
+            vec![vec![Yaku::Daisangen]]
         );
     }
 
     #[test]
-    fn test_yakuhai_quads() {
-        // test yakuhai from quads
-        let tile_groups: Vec<tiles::TileGroup> = vec![
-            tiles::TileGroup::Sequence {
-                open: true,
-                tiles: [
-                    tiles::Tile::from_string("2p"),
-                    tiles::Tile::from_string("3p"),
-                    tiles::Tile::from_string("1p"),
-                ],
-            },
-            tiles::TileGroup::Quad {
-                open: true,
-                added: false,
-                tiles: [
-                    tiles::Tile::from_string("1z"), // east wind
-                    tiles::Tile::from_string("1z"),
-                    tiles::Tile::from_string("1z"),
-                    tiles::Tile::from_string("1z"),
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("1s"),
-                    tiles::Tile::from_string("2s"),
-                    tiles::Tile::from_string("3s"),
-                ],
-            },
-            tiles::TileGroup::Triplet {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("7s"),
-                    tiles::Tile::from_string("7s"),
-                    tiles::Tile::from_string("7s"),
-                ],
-            },
-            tiles::TileGroup::Pair {
-                tiles: [
-                    tiles::Tile::from_string("8m"),
-                    tiles::Tile::from_string("8m"),
-                ],
-            },
-        ];
+    fn test_suuankou_ron_and_tsumo() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("9p")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: None,
+        };
+        // (ron han, tsumo han) from yakuman for the hand winning on the winning tile
+        let yakuman_han_ron_and_tsumo = |hand_string: &str, winning_tile_string: &str| {
+            let (player_tiles, declared_groups) =
+                tiles::hand_and_melds_from_string(hand_string).unwrap();
+            let winning_tile = tiles::Tile::from_string(winning_tile_string);
+            let ((ron_han, _), (tsumo_han, _)) = han_and_fu_ron_and_tsumo(
+                &player_tiles,
+                &declared_groups,
+                &winning_tile,
+                &hand_state,
+                &player_state,
+            )
+            .expect("Should be a winning hand");
+            (ron_han, tsumo_han)
+        };
 
-        // check yaku
+        // 111m 222p 333s 444z 55m: a shanpon wait (44z and 55m) is only suuankou by tsumo (sanankou by ron)
+        assert_eq!(
+            yakuman_han_ron_and_tsumo("111m55m222p333s44z", "4z"),
+            (2, 13)
+        );
+        assert_eq!(
+            yakuman_han_ron_and_tsumo("111m55m222p333s44z", "5m"),
+            (2, 13)
+        );
+        // a pair wait (tanki) is a double yakuman, by ron or tsumo
+        assert_eq!(
+            yakuman_han_ron_and_tsumo("111m5m222p333s444z", "5m"),
+            (26, 26)
+        );
+        // a closed quad counts as a concealed triplet, but a called triplet doesn't
+        assert_eq!(
+            yakuman_han_ron_and_tsumo("111m5m222p333s + ankan4z", "5m"),
+            (26, 26)
+        );
+        // (the three closed triplets are still sanankou)
+        assert_eq!(
+            yakuman_han_ron_and_tsumo("111m5m222p333s + pon4z", "5m"),
+            (2, 2)
+        );
+    }
+
+    #[test]
+    fn test_shanpon_triplet_ron_and_tsumo() {
         let hand_state = state::HandState {
             round_wind: state::WindDirection::East,
-            any_calls_made: true,
-            tiles_remaining: 10,
-            dora_indicators: vec![tiles::Tile::from_string("1m")],
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let mut player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("9p")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+
+        // 111m 333p 123s 99s 77z: a shanpon wait (99s and 77z), winning on 9s
+        let player_tiles = tiles::tiles_from_string("111m333p123s99s77z");
+        let winning_tile = tiles::Tile::from_string("9s");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        assert_eq!(groupings.len(), 1);
+        let grouping = &groupings[0];
+
+        // by ron, 999s is scored as an open triplet (4 fu), and the other two triplets are still closed:
+        // 8 fu (111m) + 4 fu (333p) + 4 fu (999s) + 2 fu (pair of dragons) + 10 fu (closed ron)
+        assert!(is_triplet_completed_by_ron(
+            grouping,
+            &winning_tile,
+            &player_state
+        ));
+        assert_eq!(
+            scoring_fu(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            ),
+            28
+        );
+        assert!(!has_sanankou(
+            grouping,
+            &winning_tile,
+            &hand_state,
+            &player_state
+        ));
+
+        // by tsumo, 999s is a closed triplet (8 fu): 8 fu + 4 fu + 8 fu + 2 fu + 2 fu (tsumo)
+        player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+        assert!(!is_triplet_completed_by_ron(
+            grouping,
+            &winning_tile,
+            &player_state
+        ));
+        assert_eq!(
+            scoring_fu(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            ),
+            24
+        );
+        assert!(has_sanankou(
+            grouping,
+            &winning_tile,
+            &hand_state,
+            &player_state
+        ));
+        // sanankou (2 han) + menzen tsumo (1 han)
+        assert_eq!(
+            han_and_fu(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            )
+            .0,
+            3
+        );
+
+        // winning on the pair (tanki) by ron doesn't complete a triplet: 111m 222p 333s 444s 7z
+        player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+            is_first_go_around: false,
+        });
+        let player_tiles = tiles::tiles_from_string("111m222p333444s7z");
+        let winning_tile = tiles::Tile::from_string("7z");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        for grouping in groupings.iter() {
+            assert!(!is_triplet_completed_by_ron(
+                grouping,
+                &winning_tile,
+                &player_state
+            ));
+        }
+    }
+
+    #[test]
+    fn test_kokushi_one_sided_and_thirteen_sided_waits() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
             riichi_sticks: 0,
             honba_sticks: 0,
         };
         let player_state = state::PlayerState {
-            discards: vec![
-                tiles::Tile::from_string("8p"),
-                tiles::Tile::from_string("1s"),
-                tiles::Tile::from_string("0p"),
-                tiles::Tile::from_string("2p"),
-                tiles::Tile::from_string("6m"),
-                tiles::Tile::from_string("4p"),
-                tiles::Tile::from_string("8m"),
-                tiles::Tile::from_string("1s"),
-            ],
+            discards: vec![tiles::Tile::from_string("5p")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+
+        // 1m19p19s12345667z: only waiting on 9m
+        let player_tiles = tiles::tiles_from_string("1m19p19s12345667z");
+        assert_eq!(
+            han_from_kokushi_yaku(
+                &player_tiles,
+                &tiles::Tile::from_string("9m"),
+                &hand_state,
+                &player_state
+            ),
+            Some(13)
+        );
+        assert_eq!(
+            han_from_kokushi_yaku(
+                &player_tiles,
+                &tiles::Tile::from_string("1m"),
+                &hand_state,
+                &player_state
+            ),
+            None
+        );
+        let (max_wait_tile, max_han_and_fu) =
+            max_han_and_fu_over_waits(&player_tiles, &hand_state, &player_state).unwrap();
+        assert_eq!(max_wait_tile.to_string(), "9m");
+        assert_eq!(max_han_and_fu, (13, 0));
+
+        // 19m19p19s1234567z: a 13-sided wait on every terminal and honor tile
+        let player_tiles = tiles::tiles_from_string("19m19p19s1234567z");
+        for winning_tile_string in [
+            "1m", "9m", "1p", "9p", "1s", "9s", "1z", "2z", "3z", "4z", "5z", "6z", "7z",
+        ] {
+            assert_eq!(
+                han_from_kokushi_yaku(
+                    &player_tiles,
+                    &tiles::Tile::from_string(winning_tile_string),
+                    &hand_state,
+                    &player_state
+                ),
+                Some(26)
+            );
+        }
+        assert_eq!(
+            han_from_kokushi_yaku(
+                &player_tiles,
+                &tiles::Tile::from_string("5m"),
+                &hand_state,
+                &player_state
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_chuuren_poutou_nine_sided_wait() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("5p")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+
+        // 1112345678999m: waiting on each of 1-9m, always a double yakuman
+        let player_tiles = tiles::tiles_from_string("1112345678999m");
+        for rank in 1..=9 {
+            let winning_tile = tiles::Tile::from_string(&format!("{}m", rank));
+            assert_eq!(
+                han_from_chuuren_poutou_yaku(
+                    &player_tiles,
+                    &winning_tile,
+                    &hand_state,
+                    &player_state
+                ),
+                Some(26)
+            );
+            let mut winning_tiles = player_tiles.clone();
+            winning_tiles.push(winning_tile);
+            let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+                .expect("Should be a winning hand");
+            for grouping in groupings.iter() {
+                assert_eq!(
+                    han_and_fu(
+                        &player_tiles,
+                        &winning_tile,
+                        grouping,
+                        &hand_state,
+                        &player_state
+                    ),
+                    (26, 0)
+                );
+            }
+        }
+
+        // 1112345678999m minus a 5m, plus a 2m: only waiting on 5m (single yakuman)
+        let player_tiles = tiles::tiles_from_string("1112234678999m");
+        assert_eq!(
+            han_from_chuuren_poutou_yaku(
+                &player_tiles,
+                &tiles::Tile::from_string("5m"),
+                &hand_state,
+                &player_state
+            ),
+            Some(13)
+        );
+
+        // mixing suits isn't chuuren poutou
+        let player_tiles = tiles::tiles_from_string("111234567899m9p");
+        assert_eq!(
+            han_from_chuuren_poutou_yaku(
+                &player_tiles,
+                &tiles::Tile::from_string("9m"),
+                &hand_state,
+                &player_state
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tsuuiisou_seven_pairs_and_all_triplets() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("5p")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
+        };
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+
+        // seven pairs: 11z 22z 33z 44z 55z 66z 7z, winning on 7z
+        let player_tiles = tiles::tiles_from_string("1122334455667z");
+        let winning_tile = tiles::Tile::from_string("7z");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let grouping = tile_grouping::seven_pairs_tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a seven pairs hand");
+        assert_eq!(
+            get_yakuman_list(
+                &player_tiles,
+                &winning_tile,
+                &grouping,
+                &hand_state,
+                &player_state
+            ),
+            vec![Yaku::Tsuuiisou]
+        );
+        // the regular yaku (e.g. yakuhai) aren't counted on top of the yakuman
+        assert_eq!(
+            han_and_fu(
+                &player_tiles,
+                &winning_tile,
+                &grouping,
+                &hand_state,
+                &player_state
+            ),
+            (13, 0)
+        );
+
+        // all triplets: 111z 222z 333z 55z 77z, winning on 5z (shanpon wait, by ron)
+        let player_tiles = tiles::tiles_from_string("1112223335577z");
+        let winning_tile = tiles::Tile::from_string("5z");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        assert_eq!(
+            get_yakuman_list(
+                &player_tiles,
+                &winning_tile,
+                &groupings[0],
+                &hand_state,
+                &player_state
+            ),
+            vec![Yaku::Tsuuiisou]
+        );
+        assert_eq!(
+            han_and_fu(
+                &player_tiles,
+                &winning_tile,
+                &groupings[0],
+                &hand_state,
+                &player_state
+            ),
+            (13, 0)
+        );
+
+        // a single numbered group isn't all honors
+        let player_tiles = tiles::tiles_from_string("111z222z333z55z99m");
+        let winning_tile = tiles::Tile::from_string("5z");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        assert!(!has_tsuuiisou(&groupings[0], &hand_state, &player_state));
+    }
+
+    #[test]
+    fn test_shousuushii_and_daisuushii() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: false,
+            tiles_remaining: 40,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("5p")],
+            seat_wind: state::WindDirection::West,
+            in_riichi: false,
+            in_double_riichi: false,
+            in_open_riichi: false,
+            in_ippatsu_turn: false,
+            any_discards_called_by_others: false,
+            winning_tile_source: None,
+        };
+        // the (yakuman list, han from yakuman) of the hand winning on the winning tile, by ron and by tsumo
+        let yakuman_ron_and_tsumo = |hand_string: &str, winning_tile_string: &str| {
+            let (player_tiles, declared_groups) =
+                tiles::hand_and_melds_from_string(hand_string).unwrap();
+            let winning_tile = tiles::Tile::from_string(winning_tile_string);
+            let mut winning_tiles = player_tiles.clone();
+            winning_tiles.push(winning_tile);
+            let groupings = tile_grouping::tile_grouping(&winning_tiles, &declared_groups)
+                .expect("Should be a winning hand");
+            [
+                state::WinningTileSource::Discard {
+                    is_first_go_around: false,
+                },
+                state::WinningTileSource::SelfDraw {
+                    is_first_go_around: false,
+                },
+            ]
+            .map(|winning_tile_source| {
+                let mut player_state = player_state.clone();
+                player_state.winning_tile_source = Some(winning_tile_source);
+                (
+                    get_yakuman_list(
+                        &player_tiles,
+                        &winning_tile,
+                        &groupings[0],
+                        &hand_state,
+                        &player_state,
+                    ),
+                    han_from_yakuman(
+                        &player_tiles,
+                        &winning_tile,
+                        &groupings[0],
+                        &hand_state,
+                        &player_state,
+                    ),
+                )
+            })
+        };
+
+        // 111z 222z 333z 44z 567m
+        let shousuushii = (vec![Yaku::Shousuushii], 13);
+        assert_eq!(
+            yakuman_ron_and_tsumo("111z222z333z4z567m", "4z"),
+            [shousuushii.clone(), shousuushii.clone()]
+        );
+        assert_eq!(
+            yakuman_ron_and_tsumo("4z567m + pon1z + pon2z + pon3z", "4z"),
+            [shousuushii.clone(), shousuushii.clone()]
+        );
+
+        // 111z 222z 333z 444z 55m: always daisuushii and never also shousuushii
+        let daisuushii = (vec![Yaku::Daisuushii], 26);
+        assert_eq!(
+            yakuman_ron_and_tsumo("5m + pon1z + pon2z + pon3z + pon4z", "5m"),
+            [daisuushii.clone(), daisuushii.clone()]
+        );
+        assert_eq!(
+            yakuman_ron_and_tsumo("5m + pon1z + pon2z + pon3z + ankan4z", "5m"),
+            [daisuushii.clone(), daisuushii.clone()]
+        );
+        // closed, winning on a pair wait (tanki) is also suuankou (double yakuman)
+        let suuankou_and_daisuushii = (vec![Yaku::Suuankou, Yaku::Daisuushii], 52);
+        assert_eq!(
+            yakuman_ron_and_tsumo("111z222z333z444z5m", "5m"),
+            [
+                suuankou_and_daisuushii.clone(),
+                suuankou_and_daisuushii.clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suukantsu_four_called_quads() {
+        let hand_state = state::HandState {
+            round_wind: state::WindDirection::East,
+            any_calls_made: true,
+            tiles_remaining: 30,
+            dora_indicators: tiles::tiles_from_string("1p2p3p4p5p"),
+            ura_dora_indicators: vec![],
+            riichi_sticks: 0,
+            honba_sticks: 0,
+        };
+        let player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("5p")],
             seat_wind: state::WindDirection::West,
             in_riichi: false,
             in_double_riichi: false,
+            in_open_riichi: false,
             in_ippatsu_turn: false,
             any_discards_called_by_others: false,
-            winning_tile_source: Some(state::WinningTileSource::Discard),
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
         };
-        // east wind = 1 han (round wind)
+
+        // 1111m 2222p 3333s 4444z 55m: four quads (only the pair is concealed), winning on 5m
+        let (player_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("5m + kan1m + kan2p + ankan3s + kakan4z").unwrap();
+        let winning_tile = tiles::Tile::from_string("5m");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &declared_groups)
+            .expect("Should be a winning hand");
+        assert_eq!(groupings.len(), 1);
+        let grouping = &groupings[0];
+
+        assert!(has_suukantsu(grouping, &hand_state, &player_state));
+        assert!(!has_sankantsu(grouping, &hand_state, &player_state));
         assert_eq!(
-            han_from_yakuhai_yaku(&tile_groups, &hand_state, &player_state),
-            Some(1)
+            get_yakuman_list(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            ),
+            vec![Yaku::Suukantsu]
+        );
+        assert_eq!(
+            han_and_fu(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            ),
+            (13, 0)
         );
     }
 
     #[test]
-    fn test_yakuhai_not_from_guest_winds() {
-        // test no yakuhai from guest winds (neither seat wind nor round wind)
-        let tile_groups: Vec<tiles::TileGroup> = vec![
-            tiles::TileGroup::Sequence {
-                open: true,
-                tiles: [
-                    tiles::Tile::from_string("2p"),
-                    tiles::Tile::from_string("3p"),
-                    tiles::Tile::from_string("1p"),
-                ],
-            },
-            tiles::TileGroup::Triplet {
-                open: true,
-                tiles: [
-                    tiles::Tile::from_string("2z"), // south wind
-                    tiles::Tile::from_string("2z"),
-                    tiles::Tile::from_string("2z"),
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("1s"),
-                    tiles::Tile::from_string("2s"),
-                    tiles::Tile::from_string("3s"),
-                ],
-            },
-            tiles::TileGroup::Triplet {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("7s"),
-                    tiles::Tile::from_string("7s"),
-                    tiles::Tile::from_string("7s"),
-                ],
-            },
-            tiles::TileGroup::Pair {
-                tiles: [
-                    tiles::Tile::from_string("8m"),
-                    tiles::Tile::from_string("8m"),
-                ],
-            },
-        ];
+    fn test_daisharin_yaku_from_counts() {
+        let daisharin_yaku = |hand_string: &str| {
+            daisharin_yaku_from_counts(&shanten::count_tiles_by_kind(&tiles::tiles_from_string(
+                hand_string,
+            )))
+        };
+        assert_eq!(daisharin_yaku("22334455667788p"), Some(Yaku::Daisharin));
+        assert_eq!(daisharin_yaku("22334455667788s"), Some(Yaku::Daichikurin));
+        assert_eq!(daisharin_yaku("22334405667788m"), Some(Yaku::Daisuurin));
+        // near misses: a terminal instead of a 2-8 tile, three of a tile, or a tile from another suit
+        assert_eq!(daisharin_yaku("33445566778899p"), None);
+        assert_eq!(daisharin_yaku("22233445566778p"), None);
+        assert_eq!(daisharin_yaku("2233445566778p8s"), None);
+    }
 
-        // check yaku
+    #[test]
+    fn test_tenhou_and_chiihou() {
         let hand_state = state::HandState {
             round_wind: state::WindDirection::East,
-            any_calls_made: true,
-            tiles_remaining: 10,
-            dora_indicators: vec![tiles::Tile::from_string("1m")],
+            any_calls_made: false,
+            tiles_remaining: 69,
+            dora_indicators: vec![tiles::Tile::from_string("1p")],
+            ura_dora_indicators: vec![],
             riichi_sticks: 0,
             honba_sticks: 0,
         };
-        let player_state = state::PlayerState {
-            discards: vec![
-                tiles::Tile::from_string("8p"),
-                tiles::Tile::from_string("1s"),
-                tiles::Tile::from_string("0p"),
-                tiles::Tile::from_string("2p"),
-                tiles::Tile::from_string("6m"),
-                tiles::Tile::from_string("4p"),
-                tiles::Tile::from_string("8m"),
-                tiles::Tile::from_string("1s"),
-            ],
-            seat_wind: state::WindDirection::West,
+        let mut player_state = state::PlayerState {
+            discards: vec![],
+            seat_wind: state::WindDirection::East,
             in_riichi: false,
             in_double_riichi: false,
+            in_open_riichi: false,
             in_ippatsu_turn: false,
             any_discards_called_by_others: false,
-            winning_tile_source: Some(state::WinningTileSource::Discard),
+            winning_tile_source: Some(state::WinningTileSource::SelfDraw {
+                is_first_go_around: true,
+            }),
         };
-        // south wind = 0 han (round wind)
+        let player_tiles = tiles::tiles_from_string("123m456p99p78s111z");
+        let winning_tile = tiles::Tile::from_string("9s");
+        let mut winning_tiles = player_tiles.clone();
+        winning_tiles.push(winning_tile);
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        let grouping = &groupings[0];
+
+        // the dealer wins on their initial draw
         assert_eq!(
-            han_from_yakuhai_yaku(&tile_groups, &hand_state, &player_state),
-            None
+            get_yakuman_list(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            ),
+            vec![Yaku::Tenhou]
+        );
+
+        // a non-dealer wins on their first draw
+        player_state.seat_wind = state::WindDirection::South;
+        assert_eq!(
+            get_yakuman_list(
+                &player_tiles,
+                &winning_tile,
+                grouping,
+                &hand_state,
+                &player_state
+            ),
+            vec![Yaku::Chiihou]
         );
+
+        // not the first draw
+        player_state.winning_tile_source = Some(state::WinningTileSource::SelfDraw {
+            is_first_go_around: false,
+        });
+        assert!(get_yakuman_list(
+            &player_tiles,
+            &winning_tile,
+            grouping,
+            &hand_state,
+            &player_state
+        )
+        .is_empty());
+
+        // winning by ron on the first go-around isn't chiihou (it's renhou, which is a local yaku)
+        player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+            is_first_go_around: true,
+        });
+        assert!(get_yakuman_list(
+            &player_tiles,
+            &winning_tile,
+            grouping,
+            &hand_state,
+            &player_state
+        )
+        .is_empty());
+        assert!(has_renhou(grouping, &hand_state, &player_state));
+        // a later discard isn't renhou
+        player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+            is_first_go_around: false,
+        });
+        assert!(!has_renhou(grouping, &hand_state, &player_state));
+        player_state.winning_tile_source = Some(state::WinningTileSource::Discard {
+            is_first_go_around: true,
+        });
+        // the dealer can't win by ron before their first draw
+        player_state.seat_wind = state::WindDirection::East;
+        assert!(!has_renhou(grouping, &hand_state, &player_state));
     }
 
     #[test]
-    fn test_tanyao_closed() {
-        // test tanyao (closed hand)
-        // example hand from https://riichi.wiki/Tanyao
-        let tile_groups: Vec<tiles::TileGroup> = vec![
-            tiles::TileGroup::Triplet {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("2s"),
-                    tiles::Tile::from_string("2s"),
-                    tiles::Tile::from_string("2s"),
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("6s"),
-                    tiles::Tile::from_string("7s"),
-                    tiles::Tile::from_string("8s"),
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("4m"),
-                    tiles::Tile::from_string("5m"),
-                    tiles::Tile::from_string("6m"),
-                ],
-            },
-            tiles::TileGroup::Triplet {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("3p"),
-                    tiles::Tile::from_string("3p"),
-                    tiles::Tile::from_string("3p"),
-                ],
-            },
-            tiles::TileGroup::Pair {
-                tiles: [
-                    tiles::Tile::from_string("5p"),
-                    tiles::Tile::from_string("5p"),
-                ],
-            },
-        ];
-
-        // check yaku
-        let hand_state = state::HandState {
+    fn test_han_from_dora_and_ura_dora() {
+        let mut hand_state = state::HandState {
             round_wind: state::WindDirection::East,
-            any_calls_made: true,
+            any_calls_made: false,
             tiles_remaining: 40,
-            dora_indicators: vec![tiles::Tile::from_string("2m")],
+            dora_indicators: tiles::tiles_from_string("4p"),
+            ura_dora_indicators: tiles::tiles_from_string("1m"),
             riichi_sticks: 0,
             honba_sticks: 0,
         };
-        let player_state = state::PlayerState {
-            discards: vec![
-                tiles::Tile::from_string("8p"),
-                tiles::Tile::from_string("1s"),
-            ],
+        let mut player_state = state::PlayerState {
+            discards: vec![tiles::Tile::from_string("9p")],
             seat_wind: state::WindDirection::West,
-            in_riichi: false,
+            in_riichi: true,
             in_double_riichi: false,
+            in_open_riichi: false,
             in_ippatsu_turn: false,
             any_discards_called_by_others: false,
-            winning_tile_source: Some(state::WinningTileSource::Discard),
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
         };
-        assert!(has_tanyao(&tile_groups, &hand_state, &player_state));
+        // 234m 567m 234p 567p 22s: 5p is dora, 2m is ura dora
+        let winning_tiles = tiles::tiles_from_string("234567m234567p22s");
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        let grouping = &groupings[0];
+        assert_eq!(han_from_dora(grouping, &hand_state, &player_state), 2);
+
+        // the same hand without riichi ignores the ura dora
+        player_state.in_riichi = false;
+        assert_eq!(han_from_dora(grouping, &hand_state, &player_state), 1);
+
+        // each indicator counts separately, e.g. two indicators for the same dora tile
+        hand_state.dora_indicators = tiles::tiles_from_string("44p");
+        assert_eq!(han_from_dora(grouping, &hand_state, &player_state), 2);
     }
 
     #[test]
-    fn test_tanyao_open() {
-        // test tanyao (open hand)
-        // example hand from https://riichi.wiki/Tanyao
-        let tile_groups: Vec<tiles::TileGroup> = vec![
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("6m"),
-                    tiles::Tile::from_string("7m"),
-                    tiles::Tile::from_string("8m"),
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("3s"),
-                    tiles::Tile::from_string("4s"),
-                    tiles::Tile::from_string("5s"),
-                ],
-            },
-            tiles::TileGroup::Triplet {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("3p"),
-                    tiles::Tile::from_string("3p"),
-                    tiles::Tile::from_string("3p"),
-                ],
-            },
-            tiles::TileGroup::Triplet {
-                open: true,
-                tiles: [
-                    tiles::Tile::from_string("2m"),
-                    tiles::Tile::from_string("2m"),
-                    tiles::Tile::from_string("2m"),
-                ],
-            },
-            tiles::TileGroup::Pair {
-                tiles: [
-                    tiles::Tile::from_string("6p"),
-                    tiles::Tile::from_string("6p"),
-                ],
-            },
-        ];
-
-        // check yaku
+    fn test_han_from_dora_with_quad() {
         let hand_state = state::HandState {
             round_wind: state::WindDirection::East,
-            any_calls_made: true,
+            any_calls_made: false,
             tiles_remaining: 40,
-            dora_indicators: vec![tiles::Tile::from_string("2m")],
+            dora_indicators: tiles::tiles_from_string("4m"),
+            ura_dora_indicators: vec![],
             riichi_sticks: 0,
             honba_sticks: 0,
         };
         let player_state = state::PlayerState {
-            discards: vec![
-                tiles::Tile::from_string("8p"),
-                tiles::Tile::from_string("1s"),
-            ],
+            discards: vec![tiles::Tile::from_string("9p")],
             seat_wind: state::WindDirection::West,
             in_riichi: false,
             in_double_riichi: false,
+            in_open_riichi: false,
             in_ippatsu_turn: false,
             any_discards_called_by_others: false,
-            winning_tile_source: Some(state::WinningTileSource::Discard),
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
         };
-        assert!(has_tanyao(&tile_groups, &hand_state, &player_state));
+        // 234m 567p 345s 22s + closed quad 5555m, winning on 2s: all four copies of the dora (5m) are in the quad
+        let (player_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p345s2s + ankan5m").unwrap();
+        let winning_tile = tiles::Tile::from_string("2s");
+        let groupings =
+            tile_grouping::decompose_winning_hand(&player_tiles, &declared_groups, &winning_tile);
+        assert_eq!(groupings.len(), 1);
+        let grouping = &groupings[0];
+        assert_eq!(han_from_dora(grouping, &hand_state, &player_state), 4);
+        assert!(has_tanyao(grouping, &hand_state, &player_state));
+
+        // an open quad counts the same
+        let (player_tiles, declared_groups) =
+            tiles::hand_and_melds_from_string("234m567p345s2s + kan5m").unwrap();
+        let groupings =
+            tile_grouping::decompose_winning_hand(&player_tiles, &declared_groups, &winning_tile);
+        assert_eq!(han_from_dora(&groupings[0], &hand_state, &player_state), 4);
     }
 
     #[test]
-    fn test_pinfu() {
-        // test pinfu
-        // https://riichi.wiki/Pinfu
-        let tile_groups: Vec<tiles::TileGroup> = vec![
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("1m"),
-                    tiles::Tile::from_string("2m"),
-                    tiles::Tile::from_string("3m"),
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("2s"),
-                    tiles::Tile::from_string("3s"),
-                    tiles::Tile::from_string("4s"),
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("7s"),
-                    tiles::Tile::from_string("8s"),
-                    tiles::Tile::from_string("9s"), // winning tile
-                ],
-            },
-            tiles::TileGroup::Sequence {
-                open: false,
-                tiles: [
-                    tiles::Tile::from_string("5p"),
-                    tiles::Tile::from_string("6p"),
-                    tiles::Tile::from_string("7p"),
-                ],
-            },
-            tiles::TileGroup::Pair {
-                tiles: [
-                    tiles::Tile::from_string("9p"),
-                    tiles::Tile::from_string("9p"),
-                ],
-            },
-        ];
-
-        // check yaku
+    fn test_han_from_red_fives() {
         let hand_state = state::HandState {
             round_wind: state::WindDirection::East,
-            any_calls_made: true,
+            any_calls_made: false,
             tiles_remaining: 40,
-            dora_indicators: vec![tiles::Tile::from_string("2m")],
+            dora_indicators: tiles::tiles_from_string("4m"),
+            ura_dora_indicators: vec![],
             riichi_sticks: 0,
             honba_sticks: 0,
         };
         let player_state = state::PlayerState {
-            discards: vec![
-                tiles::Tile::from_string("8p"),
-                tiles::Tile::from_string("1s"),
-            ],
+            discards: vec![tiles::Tile::from_string("9p")],
             seat_wind: state::WindDirection::West,
             in_riichi: false,
             in_double_riichi: false,
+            in_open_riichi: false,
             in_ippatsu_turn: false,
             any_discards_called_by_others: false,
-            winning_tile_source: Some(state::WinningTileSource::Discard),
+            winning_tile_source: Some(state::WinningTileSource::Discard {
+                is_first_go_around: false,
+            }),
         };
-        assert!(has_pinfu(&tile_groups, &hand_state, &player_state));
+        let empty_groups: Vec<tiles::TileGroup> = Vec::new();
+
+        // 234m 340m 678p 345s 66s: the red 5m is in a sequence, and is also a dora (the indicator is 4m)
+        let winning_tiles = tiles::tiles_from_string("234m340m678p345s66s");
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        assert!(has_tanyao(&groupings[0], &hand_state, &player_state));
+        assert_eq!(
+            han_from_red_fives(&groupings[0], &hand_state, &player_state),
+            1
+        );
+        assert_eq!(han_from_dora(&groupings[0], &hand_state, &player_state), 1);
+
+        // 234m 678m 055p 345s 66s: the red 5p is in a triplet
+        let winning_tiles = tiles::tiles_from_string("234678m055p345s66s");
+        let groupings = tile_grouping::tile_grouping(&winning_tiles, &empty_groups)
+            .expect("Should be a winning hand");
+        assert!(has_tanyao(&groupings[0], &hand_state, &player_state));
+        assert_eq!(
+            han_from_red_fives(&groupings[0], &hand_state, &player_state),
+            1
+        );
+        assert_eq!(han_from_dora(&groupings[0], &hand_state, &player_state), 0);
+    }
+
+    #[test]
+    fn test_is_nagashi_mangan() {
+        let discards = tiles::tiles_from_string("19m9p1s1234567z");
+        assert!(is_nagashi_mangan(&discards, false));
+        // a terminal was called by another player
+        assert!(!is_nagashi_mangan(&discards, true));
+        // a simple tile was discarded
+        let discards = tiles::tiles_from_string("19m9p1s1234z5s");
+        assert!(!is_nagashi_mangan(&discards, false));
     }
 }